@@ -8,6 +8,8 @@ page-settings = Settings
 sidebar-inventory = Inventory
 sidebar-rcon = RCON
 sidebar-settings = Settings
+sidebar-loadout = Loadout
+sidebar-history = History
 
 # Settings Page
 settings-title = Editor
@@ -19,6 +21,54 @@ settings-last-update = Last Update:
 settings-never-updated = Never
 settings-update-now = Update Now
 settings-updating = Updating...
+settings-item-ids = Item IDs
+settings-id-strategy = New item IDs:
+settings-renumber-hint = Gives every item a new ID in its current order. Items in storage units follow their unit.
+btn-renumber-items = Renumber items
+settings-backups = Backups
+settings-backup-count = Backups to keep per file:
+settings-no-backups = No backups yet
+backup-item-count = %1 items
+btn-refresh-backups = Refresh
+btn-restore-backup = Restore
+history-title = Inventory history
+history-snapshot-count = Snapshots to keep:
+history-no-snapshots = No snapshots yet. One is recorded every time the inventory is saved.
+history-message = Message:
+history-no-changes = The current inventory matches this snapshot
+history-changes-since = Changes since this snapshot:
+btn-take-snapshot = Take snapshot
+btn-compare-snapshot = Compare
+btn-roll-back = Roll back
+loadout-title = Loadout
+loadout-empty = Empty
+loadout-default = stock
+btn-equip = Equip…
+btn-unequip = Unequip
+team-noteam = No team
+team-t = Terrorists
+team-ct = Counter-Terrorists
+loadout-slot-melee = Knife
+loadout-slot-c4 = C4
+loadout-slot-secondary = Pistol
+loadout-slot-smg = SMG
+loadout-slot-rifle = Rifle
+loadout-slot-heavy = Heavy
+loadout-slot-grenade = Grenade
+loadout-slot-equipment = Equipment
+loadout-slot-customplayer = Agent
+loadout-slot-clothing = Gloves
+loadout-slot-musickit = Music Kit
+loadout-slot-flair0 = Pin
+loadout-slot-spray0 = Graffiti
+merge-inventory-title = inventory.txt was changed by another program
+merge-inventory-hint = These items were changed both here and in the file. Choose which version to keep for each.
+merge-unreadable-hint = The file cannot be read, so the editor will not save over it. It may still be being written; this closes once it can be read. Otherwise keep your version, or load what can be read of the file.
+merge-config-title = config.txt was changed by another program
+merge-config-hint = These values were changed both here and in the file:
+merge-keep-mine = Keep mine
+merge-keep-theirs = Use the file's
+btn-apply-merge = Apply
 theme-light = Light
 theme-dark = Dark
 theme-system = System
@@ -52,6 +102,20 @@ search = Search...
 
 # Add Item
 btn-add-item = Add Item
+btn-undo = Undo
+btn-redo = Redo
+btn-problems = Problems (%1)
+btn-close-problems = Close
+btn-quick-fix = Fix
+problems-title = Problems
+problems-none = No problems found
+btn-trade-up-contract = Trade-up
+trade-up-title = Trade-up Contract
+trade-up-hint = Pick %1 skins of the same rarity (%2 picked).
+trade-up-wear = Wear
+btn-trade-up = Trade up
+problem-error = Error
+problem-warning = Warning
 select-item-to-add = Select Item to Add
 select-weapon-case-to-add = Select Weapon Case to Add
 select-paintkit = Select Paint Kit
@@ -133,6 +197,8 @@ btn-add-attribute = Add Attribute
 btn-delete-attribute = Delete
 btn-confirm = Confirm
 status-unsaved = Unsaved
+changes-on-save = Changes on save
+invalid-attribute = Invalid value for attribute %1
 
 # Delete Confirmation Modal
 modal-delete-title = Delete Item
@@ -146,6 +212,18 @@ quality-id = Quality ID
 rarity = Rarity
 custom-name = Custom Name
 item-properties = Item Properties
+tab-item-details = Details
+tab-case-contents = Contents
+btn-open-case = Open case
+btn-simulate-case = Simulate
+case-seed = Seed:
+case-simulation-runs = Openings:
+case-odds = Chance
+case-simulated = Simulated (%1 openings)
+wear-out-of-range = Outside the paint kit's range of %1 to %2
+btn-clamp-wear = Clamp
+seed-out-of-range = The game only uses seeds from 0 to %1
+seed-no-effect = This paint kit looks the same with any seed
 
 # Property Table Headers
 prop-index = Index
@@ -206,12 +284,19 @@ attr-232 = Spray Remaining
 attr-233 = Spray Color
 load-errors-help = Check that the required game data files (items_game.txt, language files) exist next to the executable in the csgo directory, or verify file permissions.
 load-errors-title = Failed to Load Game Data
+btn-rewrite-inventory = Back up and rewrite
+load-warnings-details = Details
+load-warnings-dismiss = Dismiss
+load-warnings-help = The entries listed below could not be read. Skipped entries are hidden here but written back to inventory.txt unchanged when saving; values kept as text are saved as they are.
+load-warnings-syntax = inventory.txt has syntax errors, so saving cannot update it in place and your changes are not saved. Back it up and rewrite it to save what was read.
+load-warnings-title = Parts of inventory.txt could not be read
 
 # Runtime Mode
 readonly-rcon-message = RCON is connected. inventory.txt and config.txt are read-only until you disconnect.
 
 # Config RCON
 appid-override = App ID override:
+config-rarity-weights = Case drop weight of each rarity:
 config-rcon-title = Target GC RCON
 config-rcon-enabled = Enable RCON listener:
 config-rcon-bind-address = Bind address:
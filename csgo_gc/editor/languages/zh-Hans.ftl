@@ -7,6 +7,8 @@ page-settings = 设置
 # Sidebar
 sidebar-inventory = 库存
 sidebar-settings = 设置
+sidebar-loadout = 装备配置
+sidebar-history = 历史
 
 # Settings Page
 settings-title = 编辑器
@@ -18,6 +20,54 @@ settings-last-update = 最后更新：
 settings-never-updated = 从未
 settings-update-now = 立即更新
 settings-updating = 正在更新...
+settings-item-ids = 物品 ID
+settings-id-strategy = 新物品 ID:
+settings-renumber-hint = 按当前顺序为所有物品重新分配 ID。储物柜中的物品会跟随其储物柜。
+btn-renumber-items = 重新编号物品
+settings-backups = 备份
+settings-backup-count = 每个文件保留的备份数:
+settings-no-backups = 暂无备份
+backup-item-count = %1 个物品
+btn-refresh-backups = 刷新
+btn-restore-backup = 恢复
+history-title = 库存历史
+history-snapshot-count = 保留的快照数:
+history-no-snapshots = 暂无快照。每次保存库存时都会记录一个快照。
+history-message = 说明:
+history-no-changes = 当前库存与此快照相同
+history-changes-since = 此快照之后的更改:
+btn-take-snapshot = 创建快照
+btn-compare-snapshot = 比较
+btn-roll-back = 回滚
+loadout-title = 装备配置
+loadout-empty = 空
+loadout-default = 默认
+btn-equip = 装备…
+btn-unequip = 卸下
+team-noteam = 无阵营
+team-t = 恐怖分子
+team-ct = 反恐精英
+loadout-slot-melee = 刀
+loadout-slot-c4 = C4
+loadout-slot-secondary = 手枪
+loadout-slot-smg = 微型冲锋枪
+loadout-slot-rifle = 步枪
+loadout-slot-heavy = 重型武器
+loadout-slot-grenade = 投掷物
+loadout-slot-equipment = 装备
+loadout-slot-customplayer = 探员
+loadout-slot-clothing = 手套
+loadout-slot-musickit = 音乐盒
+loadout-slot-flair0 = 胸章
+loadout-slot-spray0 = 涂鸦
+merge-inventory-title = inventory.txt 已被其他程序修改
+merge-inventory-hint = 以下物品在编辑器和文件中都被修改过。请为每个物品选择要保留的版本。
+merge-unreadable-hint = 该文件无法读取，因此编辑器不会覆盖它。它可能仍在写入中；文件可读后此窗口会自动关闭。也可以保留你的版本，或加载文件中可读取的部分。
+merge-config-title = config.txt 已被其他程序修改
+merge-config-hint = 以下数值在编辑器和文件中都被修改过:
+merge-keep-mine = 保留我的
+merge-keep-theirs = 使用文件中的
+btn-apply-merge = 应用
 theme-light = 亮色
 theme-dark = 暗色
 theme-system = 跟随系统
@@ -28,6 +78,7 @@ config-title = 个人战绩
 player-cur-xp = 当前经验值:
 destroy-used-items = 销毁已使用物品:
 show-csgo-gc-servers-only = 在服务器浏览器中仅显示 csgo_gc 服务器:
+config-rarity-weights = 开箱时各稀有度的掉落权重:
 cmd-leader = 因领导团队而获得的称赞数:
 cmd-teaching = 因诲人不倦而获得的称赞数:
 cmd-friendly = 因待人友善而获得的称赞数:
@@ -51,6 +102,20 @@ search = 搜索…
 
 # Add Item
 btn-add-item = 添加物品
+btn-undo = 撤销
+btn-redo = 重做
+btn-problems = 问题 (%1)
+btn-close-problems = 关闭
+btn-quick-fix = 修复
+problems-title = 问题
+problems-none = 未发现问题
+btn-trade-up-contract = 汰换
+trade-up-title = 汰换合同
+trade-up-hint = 选择 %1 件相同品质的皮肤（已选 %2 件）。
+trade-up-wear = 磨损
+btn-trade-up = 汰换
+problem-error = 错误
+problem-warning = 警告
 select-item-to-add = 选择要添加的物品
 select-weapon-case-to-add = 选择要添加的武器箱
 select-paintkit = 选择皮肤涂装
@@ -139,6 +204,18 @@ quality-id = 性质编号
 rarity = 稀有度
 custom-name = 命名标签
 item-properties = 物品属性
+tab-item-details = 详情
+tab-case-contents = 箱子内容
+btn-open-case = 开箱
+btn-simulate-case = 模拟
+case-seed = 种子:
+case-simulation-runs = 开箱次数:
+case-odds = 概率
+case-simulated = 模拟结果 (%1 次)
+wear-out-of-range = 超出该皮肤的磨损范围 %1 至 %2
+btn-clamp-wear = 限制到范围内
+seed-out-of-range = 游戏只使用 0 至 %1 的图案模板
+seed-no-effect = 该皮肤的外观不受图案模板影响
 
 # Property Table Headers
 prop-index = 属性索引
@@ -202,7 +279,15 @@ select-attribute = 选择属性
 header-attribute-id = 属性 ID
 header-attribute-name = 属性名称
 status-unsaved = 未保存
+changes-on-save = 保存时的更改
+invalid-attribute = 属性 %1 的值无效
 inventory-error-title = 读取库存错误
 inventory-error-help = 请检查游戏目录设置或文件权限
 load-errors-help = 请确保可执行文件旁边的 csgo 目录中包含所需的游戏数据文件（items_game.txt、语言文件），或检查文件权限。
 load-errors-title = 游戏数据加载失败
+btn-rewrite-inventory = 备份并重写
+load-warnings-details = 详细信息
+load-warnings-dismiss = 忽略
+load-warnings-help = 以下条目无法读取。被跳过的条目不会在此显示，但保存时会原样写回 inventory.txt；按文本保留的数值会按原样保存。
+load-warnings-syntax = inventory.txt 存在语法错误，保存时无法原地更新，你的修改尚未保存。请备份并重写该文件，以保存已读取的内容。
+load-warnings-title = inventory.txt 的部分内容无法读取
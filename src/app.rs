@@ -1,8 +1,10 @@
-use crate::config::{Config, ConfigLoader};
-use crate::core::GameDir;
+use crate::config::{Config, ConfigLoader, ConfigMerge};
+use crate::core::{BackupEntry, BackupStore, FileWatcher, GameDir};
 use crate::inventory::{
-    AVAILABLE_ATTRIBUTES, GameTranslation, Inventory, InventoryLoader, ItemAttribute, ItemsGame,
-    ItemsGameLoader, LanguageFileParser, get_attribute_fluent_key,
+    AVAILABLE_ATTRIBUTES, AppliedEdit, AttributeValue, AttributeValueError, CaseOpener, CaseRng,
+    CaseStats, EditHistory, GameTranslation, Inventory, InventoryEdit, InventoryLoader,
+    InventoryMerge, Item, ItemAttribute, ItemsGame, ItemsGameLoader, LanguageFileParser, Problem,
+    SnapshotEntry, SnapshotStore, Team, TradeUp, get_attribute_fluent_key, loadout, validate,
 };
 use crate::online_data::{
     DataProvider, OnlineGameData, fetch_online_data_with_progress, load_cached_data,
@@ -14,12 +16,12 @@ use eframe::egui;
 use egui_i18n::tr;
 use egui_i18n::{load_translations_from_path, set_fallback, set_language};
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::mpsc::{self, Receiver};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 // Type alias for select window items: (id, name, optional_color)
 pub type SelectWindowItem = (String, String, Option<String>);
@@ -127,28 +129,46 @@ impl ItemTemplate {
         match self {
             ItemTemplate::Empty => {}
             ItemTemplate::NormalWeapon => {
-                attributes.insert(ItemAttribute::SkinPaintIndex.id(), "0".to_string());
-                attributes.insert(ItemAttribute::SkinPaintSeed.id(), "0".to_string());
-                attributes.insert(ItemAttribute::SkinPaintWear.id(), "0.001".to_string());
+                attributes.insert(
+                    ItemAttribute::SkinPaintIndex.id(),
+                    AttributeValue::float(0.0),
+                );
+                attributes.insert(
+                    ItemAttribute::SkinPaintSeed.id(),
+                    AttributeValue::float(0.0),
+                );
+                attributes.insert(
+                    ItemAttribute::SkinPaintWear.id(),
+                    AttributeValue::float(0.001),
+                );
             }
             ItemTemplate::StatTrakWeapon => {
                 quality = 9;
-                attributes.insert(ItemAttribute::SkinPaintIndex.id(), "0".to_string());
-                attributes.insert(ItemAttribute::SkinPaintSeed.id(), "0".to_string());
-                attributes.insert(ItemAttribute::SkinPaintWear.id(), "0.001".to_string());
-                attributes.insert(ItemAttribute::StatTrakCount.id(), "0".to_string());
-                attributes.insert(ItemAttribute::StatTrakType.id(), "0".to_string());
+                attributes.insert(
+                    ItemAttribute::SkinPaintIndex.id(),
+                    AttributeValue::float(0.0),
+                );
+                attributes.insert(
+                    ItemAttribute::SkinPaintSeed.id(),
+                    AttributeValue::float(0.0),
+                );
+                attributes.insert(
+                    ItemAttribute::SkinPaintWear.id(),
+                    AttributeValue::float(0.001),
+                );
+                attributes.insert(ItemAttribute::StatTrakCount.id(), AttributeValue::uint32(0));
+                attributes.insert(ItemAttribute::StatTrakType.id(), AttributeValue::uint32(0));
             }
             ItemTemplate::NormalMusicKit => {
-                attributes.insert(ItemAttribute::MusicID.id(), "0".to_string());
-                attributes.insert(ItemAttribute::StatTrakCount.id(), "0".to_string());
-                attributes.insert(ItemAttribute::StatTrakType.id(), "1".to_string());
+                attributes.insert(ItemAttribute::MusicID.id(), AttributeValue::uint32(0));
+                attributes.insert(ItemAttribute::StatTrakCount.id(), AttributeValue::uint32(0));
+                attributes.insert(ItemAttribute::StatTrakType.id(), AttributeValue::uint32(1));
             }
             ItemTemplate::StatTrakMusicKit => {
                 quality = 9;
-                attributes.insert(ItemAttribute::MusicID.id(), "0".to_string());
-                attributes.insert(ItemAttribute::StatTrakCount.id(), "0".to_string());
-                attributes.insert(ItemAttribute::StatTrakType.id(), "1".to_string());
+                attributes.insert(ItemAttribute::MusicID.id(), AttributeValue::uint32(0));
+                attributes.insert(ItemAttribute::StatTrakCount.id(), AttributeValue::uint32(0));
+                attributes.insert(ItemAttribute::StatTrakType.id(), AttributeValue::uint32(1));
             }
             ItemTemplate::WeaponCase => {}
         }
@@ -170,6 +190,7 @@ impl ItemTemplate {
             custom_name: None,
             attributes,
             equipped_state: HashMap::new(),
+            extra: Default::default(),
         }
     }
 
@@ -179,15 +200,21 @@ impl ItemTemplate {
 
         match self {
             ItemTemplate::NormalMusicKit => {
-                attributes.insert(ItemAttribute::MusicID.id(), music_id.to_string());
-                attributes.insert(ItemAttribute::StatTrakCount.id(), "0".to_string());
-                attributes.insert(ItemAttribute::StatTrakType.id(), "1".to_string());
+                attributes.insert(
+                    ItemAttribute::MusicID.id(),
+                    AttributeValue::uint32(music_id),
+                );
+                attributes.insert(ItemAttribute::StatTrakCount.id(), AttributeValue::uint32(0));
+                attributes.insert(ItemAttribute::StatTrakType.id(), AttributeValue::uint32(1));
             }
             ItemTemplate::StatTrakMusicKit => {
                 quality = 9;
-                attributes.insert(ItemAttribute::MusicID.id(), music_id.to_string());
-                attributes.insert(ItemAttribute::StatTrakCount.id(), "0".to_string());
-                attributes.insert(ItemAttribute::StatTrakType.id(), "1".to_string());
+                attributes.insert(
+                    ItemAttribute::MusicID.id(),
+                    AttributeValue::uint32(music_id),
+                );
+                attributes.insert(ItemAttribute::StatTrakCount.id(), AttributeValue::uint32(0));
+                attributes.insert(ItemAttribute::StatTrakType.id(), AttributeValue::uint32(1));
             }
             _ => {}
         }
@@ -205,6 +232,7 @@ impl ItemTemplate {
             custom_name: None,
             attributes,
             equipped_state: HashMap::new(),
+            extra: Default::default(),
         }
     }
 
@@ -224,7 +252,9 @@ impl ItemTemplate {
 pub enum Page {
     #[default]
     Inventory,
+    Loadout,
     Rcon,
+    History,
     Settings,
 }
 
@@ -252,12 +282,63 @@ pub struct EditItemState {
     pub attributes: HashMap<u32, String>,
 }
 
+impl EditItemState {
+    pub fn from_item(item: &Item) -> Self {
+        Self {
+            level: item.level,
+            custom_name: item.custom_name.clone().unwrap_or_default(),
+            rarity: item.rarity,
+            quality: item.quality,
+            attributes: item
+                .attributes
+                .iter()
+                .map(|(id, value)| (*id, value.to_string()))
+                .collect(),
+        }
+    }
+
+    /// The edited attributes, checked against the types items_game gives them.
+    pub fn typed_attributes(
+        &self,
+        items_game: &ItemsGame,
+    ) -> Result<HashMap<u32, AttributeValue>, (u32, AttributeValueError)> {
+        self.attributes
+            .iter()
+            .map(|(id, text)| {
+                AttributeValue::parse(items_game.get_attribute_value_type(*id), text)
+                    .map(|value| (*id, value))
+                    .map_err(|e| (*id, e))
+            })
+            .collect()
+    }
+
+    /// Copies the edits into `item`. Nothing is changed if an attribute value
+    /// does not fit its type.
+    pub fn apply_to(&self, item: &mut Item, items_game: &ItemsGame) -> Result<(), String> {
+        let attributes = self
+            .typed_attributes(items_game)
+            .map_err(|(id, e)| format!("Attribute {}: {}", id, e))?;
+
+        item.level = self.level;
+        item.rarity = self.rarity;
+        item.quality = self.quality;
+        item.custom_name = if self.custom_name.is_empty() {
+            None
+        } else {
+            Some(self.custom_name.clone())
+        };
+        item.attributes = attributes;
+        Ok(())
+    }
+}
+
 pub struct CsgoInventoryEditor {
     pub inventory: Inventory,
     pub items_game: Arc<ItemsGame>,
     pub translations: Arc<GameTranslation>,
     pub open_item_windows: HashSet<u64>,
     pub edit_item_states: HashMap<u64, EditItemState>,
+    pub edit_history: EditHistory,
     pub select_window_open: bool,
     pub select_window_purpose: Option<SelectWindowPurpose>,
     pub select_window_items: SelectWindowItems,
@@ -299,9 +380,53 @@ pub struct CsgoInventoryEditor {
     cached_items_count: usize,
     cached_item_display_names: RefCell<HashMap<u64, String>>,
     load_errors: Vec<String>,
+    load_warnings: Vec<String>,
+    /// inventory.txt has syntax errors, so saves cannot update it in place
+    /// until the user has it rewritten.
+    inventory_needs_rewrite: bool,
+    backup_list: Option<Vec<BackupListEntry>>,
+    snapshot_list: Option<Vec<SnapshotEntry>>,
+    inventory_watch: Option<FileWatcher>,
+    config_watch: Option<FileWatcher>,
+    /// inventory.txt and config.txt as last read or written by the editor,
+    /// the common ancestor when merging changes made by other programs.
+    inventory_base: Inventory,
+    config_base: Config,
+    last_external_check: Option<Instant>,
+    /// External changes to inventory.txt that conflict with the editor's.
+    pub inventory_merge: Option<InventoryMerge>,
+    /// Why inventory.txt, changed by another program, cannot be read.
+    pub unreadable_inventory_change: Option<String>,
+    pub config_merge: Option<ConfigMerge>,
+    /// Why config.txt, changed by another program, cannot be read.
+    unreadable_config_change: Option<String>,
+    /// Problems found in the inventory, checked again after every change.
+    problems: Option<Vec<Problem>>,
+    pub show_problems: bool,
+    /// The team shown on the loadout page.
+    pub loadout_team: Team,
+    /// The seed the case simulator was last started from.
+    pub case_seed: u64,
+    case_rng: CaseRng,
+    pub case_simulation_runs: u32,
+    /// The case last simulated and what it dropped.
+    pub case_statistics: Option<(u32, CaseStats)>,
+    pub show_trade_up: bool,
+    /// The items picked for the trade-up contract.
+    pub trade_up_inputs: BTreeSet<u64>,
+    /// The snapshot picked on the history page and how the current inventory
+    /// differs from it, one line per change.
+    pub selected_snapshot: Option<(SnapshotEntry, Vec<String>)>,
+    pub snapshot_message: String,
     last_theme: Option<Theme>,
 }
 
+pub struct BackupListEntry {
+    pub entry: BackupEntry,
+    /// Items in an inventory.txt backup, if it could be read.
+    pub item_count: Option<usize>,
+}
+
 #[derive(Debug, Clone)]
 pub struct RconUiState {
     pub address: String,
@@ -347,6 +472,17 @@ impl Default for RconUiState {
     }
 }
 
+/// Appends `new` to `warnings`, leaving out ones already there: attributes
+/// that fit neither the built-in nor the items_game type are reported by both
+/// the parser and the items_game typing.
+fn add_warnings(warnings: &mut Vec<String>, new: Vec<String>) {
+    for warning in new {
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+}
+
 fn init_i18n(language: &str) {
     let languages_path = get_exe_dir()
         .join("csgo_gc")
@@ -362,6 +498,9 @@ fn init_i18n(language: &str) {
 impl CsgoInventoryEditor {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         let settings = Settings::load().unwrap_or_default();
+        let case_seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
 
         let mut fonts = egui::FontDefinitions::default();
 
@@ -396,9 +535,13 @@ impl CsgoInventoryEditor {
         let detected_game_dir = GameDir::new().ok();
 
         let mut load_errors = Vec::new();
+        let mut load_warnings = Vec::new();
         let inventory = if let Some(ref game_dir) = detected_game_dir {
-            match InventoryLoader::load_from_game_dir(game_dir.path()) {
-                Ok(inv) => inv,
+            match InventoryLoader::load_lenient_from_game_dir(game_dir.path()) {
+                Ok((inv, warnings)) => {
+                    load_warnings = warnings;
+                    inv
+                }
                 Err(e) => {
                     load_errors.push(format!("Failed to load inventory: {}", e));
                     Inventory::default()
@@ -489,6 +632,17 @@ impl CsgoInventoryEditor {
             Config::default()
         };
 
+        let mut inventory = inventory;
+        add_warnings(
+            &mut load_warnings,
+            items_game.apply_attribute_types(&mut inventory),
+        );
+        let inventory_needs_rewrite = detected_game_dir.as_ref().is_some_and(|game_dir| {
+            InventoryLoader::has_syntax_errors(
+                game_dir.path().join("csgo_gc").join("inventory.txt"),
+            )
+        });
+
         let items_game = Arc::new(items_game);
         let translations = Arc::new(translations);
         let rcon_ui = RconUiState {
@@ -504,6 +658,7 @@ impl CsgoInventoryEditor {
             translations: Arc::clone(&translations),
             open_item_windows: HashSet::new(),
             edit_item_states: HashMap::new(),
+            edit_history: EditHistory::default(),
             select_window_open: false,
             select_window_purpose: None,
             select_window_items: Vec::new(),
@@ -548,6 +703,30 @@ impl CsgoInventoryEditor {
             cached_items_count: 0,
             cached_item_display_names: RefCell::new(HashMap::new()),
             load_errors,
+            load_warnings,
+            inventory_needs_rewrite,
+            backup_list: None,
+            snapshot_list: None,
+            inventory_watch: None,
+            config_watch: None,
+            inventory_base: Inventory::default(),
+            config_base: Config::default(),
+            last_external_check: None,
+            inventory_merge: None,
+            unreadable_inventory_change: None,
+            config_merge: None,
+            unreadable_config_change: None,
+            problems: None,
+            show_problems: false,
+            loadout_team: Team::Terrorist,
+            case_seed,
+            case_rng: CaseRng::new(case_seed),
+            case_simulation_runs: 1000,
+            case_statistics: None,
+            show_trade_up: false,
+            trade_up_inputs: BTreeSet::new(),
+            selected_snapshot: None,
+            snapshot_message: String::new(),
             last_theme: Some(settings.theme),
         };
 
@@ -562,6 +741,7 @@ impl CsgoInventoryEditor {
         }
 
         app.refresh_display_metadata_cache();
+        app.watch_game_files();
         app
     }
 
@@ -651,15 +831,52 @@ impl CsgoInventoryEditor {
         }
     }
 
-    pub fn save_inventory(&mut self) -> Result<(), String> {
+    /// Saves inventory.txt and records a snapshot of it under `message`.
+    pub fn save_inventory(&mut self, message: &str) -> Result<(), String> {
+        self.write_inventory(message, false)
+    }
+
+    /// Saves inventory.txt, writing it from scratch with `replace` rather
+    /// than updating what the file holds.
+    fn write_inventory(&mut self, message: &str, replace: bool) -> Result<(), String> {
         if self.is_live_rcon() {
             return Err("RCON is connected; inventory.txt is read-only".to_string());
         }
+        if !self.check_inventory_changes() {
+            return Err(
+                "inventory.txt was changed by another program; resolve the conflicts first"
+                    .to_string(),
+            );
+        }
+        if self.inventory_needs_rewrite && !replace {
+            return Err(
+                "inventory.txt has syntax errors; back it up and rewrite it first".to_string(),
+            );
+        }
         if let Some(ref game_dir) = self.game_dir {
-            let result = InventoryLoader::save_to_game_dir(&self.inventory, game_dir.path())
-                .map_err(|e| e.to_string());
+            let result = if replace {
+                InventoryLoader::replace_with_backup(
+                    &self.inventory,
+                    game_dir.path().join("csgo_gc").join("inventory.txt"),
+                    &self.backups(),
+                )
+            } else {
+                InventoryLoader::save_to_game_dir(&self.inventory, game_dir.path(), &self.backups())
+            }
+            .map_err(|e| e.to_string());
             if result.is_ok() {
+                self.inventory_needs_rewrite = false;
+                self.inventory_base = self.inventory.clone();
+                if let Some(watch) = &mut self.inventory_watch {
+                    watch.mark_seen();
+                }
                 self.update_sorted_cache();
+                self.backup_list = None;
+                self.snapshot_list = None;
+                self.selected_snapshot = None;
+                self.snapshots()
+                    .record(&self.inventory, message)
+                    .map_err(|e| format!("Saved, but failed to record a snapshot: {}", e))?;
             }
             result
         } else {
@@ -667,18 +884,555 @@ impl CsgoInventoryEditor {
         }
     }
 
+    /// Writes inventory.txt from scratch after backing it up, for a file with
+    /// syntax errors that saving cannot update in place. Entries that could
+    /// not be read are written back as they were recovered.
+    pub fn rewrite_inventory(&mut self) -> Result<(), String> {
+        self.write_inventory("Rewrite inventory.txt", true)
+    }
+
+    /// Applies `edit` to the inventory, makes it undoable and saves.
+    pub fn apply_edit(&mut self, edit: InventoryEdit) -> Result<(), String> {
+        if self.is_live_rcon() {
+            return Err("RCON is connected; inventory.txt is read-only".to_string());
+        }
+        let applied = self.edit_history.apply(&mut self.inventory, edit)?;
+        self.after_edit(&applied);
+        self.save_inventory(&applied.description)
+    }
+
+    pub fn undo(&mut self) -> Result<(), String> {
+        if self.is_live_rcon() {
+            return Err("RCON is connected; inventory.txt is read-only".to_string());
+        }
+        match self.edit_history.undo(&mut self.inventory)? {
+            Some(applied) => {
+                self.after_edit(&applied);
+                self.save_inventory(&format!("Undo: {}", applied.description))
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub fn redo(&mut self) -> Result<(), String> {
+        if self.is_live_rcon() {
+            return Err("RCON is connected; inventory.txt is read-only".to_string());
+        }
+        match self.edit_history.redo(&mut self.inventory)? {
+            Some(applied) => {
+                self.after_edit(&applied);
+                self.save_inventory(&format!("Redo: {}", applied.description))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// `count` unused item ids, picked as the settings say.
+    pub fn allocate_item_ids(&self, count: usize) -> Vec<u64> {
+        self.inventory
+            .allocate_item_ids(self.settings.id_strategy, count)
+    }
+
+    /// Gives every item a new, consecutive id, starting where the id strategy
+    /// allocates from. Undoable like any other edit.
+    pub fn renumber_items(&mut self) -> Result<(), String> {
+        if self.is_live_rcon() {
+            return Err("RCON is connected; inventory.txt is read-only".to_string());
+        }
+        let mut renumbered = self.inventory.clone();
+        renumbered.renumber_items(self.settings.id_strategy.first_id());
+        if renumbered == self.inventory {
+            return Ok(());
+        }
+        self.open_item_windows.clear();
+        self.edit_item_states.clear();
+        self.apply_edit(InventoryEdit::SetItems {
+            description: "Renumber items".to_string(),
+            items: renumbered.items,
+        })
+    }
+
+    /// Open item windows show the edited items as they are now, dropping any
+    /// unsaved changes to them.
+    fn after_edit(&mut self, applied: &AppliedEdit) {
+        for item_id in &applied.item_ids {
+            self.edit_item_states.remove(item_id);
+        }
+        self.mark_inventory_changed();
+    }
+
+    /// The inventory.txt entries that saving `edit_state` for `item_id` would
+    /// change, one line each.
+    pub fn preview_item_changes(&self, item_id: u64, edit_state: &EditItemState) -> Vec<String> {
+        let Some(item_idx) = self.get_item_index(item_id) else {
+            return Vec::new();
+        };
+        let mut edited = self.inventory.clone();
+        if let Err(e) = edit_state.apply_to(&mut edited.items[item_idx], &self.items_game) {
+            return vec![e];
+        }
+
+        InventoryLoader::diff(&self.inventory, &edited)
+            .changes
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
     pub fn save_config(&mut self) -> Result<(), String> {
         if self.is_live_rcon() {
             return Err("RCON is connected; config.txt is read-only".to_string());
         }
+        if !self.check_config_changes() {
+            if let Some(error) = &self.unreadable_config_change {
+                return Err(format!(
+                    "config.txt was changed by another program and cannot be read: {}",
+                    error
+                ));
+            }
+            return Err(
+                "config.txt was changed by another program; resolve the conflicts first"
+                    .to_string(),
+            );
+        }
         if let Some(ref game_dir) = self.game_dir {
             let config_path = game_dir.path().join("csgo_gc").join("config.txt");
-            ConfigLoader::save(&self.config, &config_path).map_err(|e| e.to_string())
+            self.backup_list = None;
+            ConfigLoader::save_with_backup(&self.config, &config_path, &self.backups())?;
+            self.config_base = self.config.clone();
+            if let Some(watch) = &mut self.config_watch {
+                watch.mark_seen();
+            }
+            Ok(())
         } else {
             Err("Game directory not found".to_string())
         }
     }
 
+    pub fn backups(&self) -> BackupStore {
+        BackupStore::in_editor_dir(self.settings.backup_count)
+    }
+
+    /// Backups of inventory.txt and config.txt, newest first. Listed once and
+    /// cached until the next save or restore.
+    pub fn get_backup_list(&mut self) -> &[BackupListEntry] {
+        self.backup_list.get_or_insert_with(|| {
+            BackupStore::in_editor_dir(self.settings.backup_count)
+                .list_all()
+                .into_iter()
+                .filter(|entry| matches!(entry.file_name.as_str(), "inventory.txt" | "config.txt"))
+                .map(|entry| BackupListEntry {
+                    item_count: (entry.file_name == "inventory.txt")
+                        .then(|| InventoryLoader::load(&entry.path).ok())
+                        .flatten()
+                        .map(|inventory| inventory.items.len()),
+                    entry,
+                })
+                .collect()
+        })
+    }
+
+    pub fn refresh_backup_list(&mut self) {
+        self.backup_list = None;
+    }
+
+    /// Puts a backup back in place of the game file it was taken from and
+    /// reloads that file. What the file held before is backed up first.
+    pub fn restore_backup(&mut self, entry: &BackupEntry) -> Result<(), String> {
+        if self.is_live_rcon() {
+            return Err("RCON is connected; game files are read-only".to_string());
+        }
+        let Some(ref game_dir) = self.game_dir else {
+            return Err("Game directory not found".to_string());
+        };
+        let target = game_dir.path().join("csgo_gc").join(&entry.file_name);
+        self.backups()
+            .restore(entry, &target)
+            .map_err(|e| format!("Failed to restore backup: {}", e))?;
+        self.backup_list = None;
+
+        if entry.file_name == "config.txt" {
+            self.config = ConfigLoader::load(&target)?;
+            self.config_base = self.config.clone();
+            self.config_merge = None;
+            self.unreadable_config_change = None;
+            if let Some(watch) = &mut self.config_watch {
+                watch.mark_seen();
+            }
+        } else {
+            self.reload_inventory(&target)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the inventory with what `path` holds, dropping the editor's
+    /// unsaved state along with it.
+    fn reload_inventory(&mut self, path: &Path) -> Result<(), String> {
+        let (mut inventory, mut warnings) =
+            InventoryLoader::load_lenient(path).map_err(|e| e.to_string())?;
+        add_warnings(
+            &mut warnings,
+            self.items_game.apply_attribute_types(&mut inventory),
+        );
+        self.inventory = inventory;
+        self.load_warnings = warnings;
+        self.inventory_needs_rewrite = InventoryLoader::has_syntax_errors(path);
+        self.open_item_windows.clear();
+        self.edit_item_states.clear();
+        self.edit_history.clear();
+        self.inventory_base = self.inventory.clone();
+        self.inventory_merge = None;
+        self.unreadable_inventory_change = None;
+        if let Some(watch) = &mut self.inventory_watch {
+            watch.mark_seen();
+        }
+        self.mark_inventory_changed();
+        self.update_sorted_cache();
+        Ok(())
+    }
+
+    pub fn snapshots(&self) -> SnapshotStore {
+        SnapshotStore::in_editor_dir(self.settings.snapshot_count)
+    }
+
+    /// Inventory snapshots, newest first. Listed once and cached until the
+    /// next save or rollback.
+    pub fn get_snapshot_list(&mut self) -> &[SnapshotEntry] {
+        let store = self.snapshots();
+        self.snapshot_list.get_or_insert_with(|| store.list())
+    }
+
+    pub fn refresh_snapshot_list(&mut self) {
+        self.snapshot_list = None;
+        self.selected_snapshot = None;
+    }
+
+    /// Records the current inventory as a snapshot without saving it.
+    pub fn take_snapshot(&mut self, message: &str) -> Result<(), String> {
+        self.snapshots()
+            .record(&self.inventory, message)
+            .map_err(|e| format!("Failed to record snapshot: {}", e))?;
+        self.refresh_snapshot_list();
+        Ok(())
+    }
+
+    /// Picks `entry` on the history page and lists how the current inventory
+    /// differs from it.
+    pub fn select_snapshot(&mut self, entry: &SnapshotEntry) -> Result<(), String> {
+        let patch = self
+            .snapshots()
+            .diff(entry, &self.inventory)
+            .map_err(|e| format!("Failed to read snapshot: {}", e))?;
+        let lines = patch.changes.iter().map(ToString::to_string).collect();
+        self.selected_snapshot = Some((entry.clone(), lines));
+        Ok(())
+    }
+
+    /// Replaces the inventory with the one in `entry` and saves it. The
+    /// rollback is itself recorded as a snapshot, so it can be undone.
+    pub fn roll_back_to_snapshot(&mut self, entry: &SnapshotEntry) -> Result<(), String> {
+        if self.is_live_rcon() {
+            return Err("RCON is connected; inventory.txt is read-only".to_string());
+        }
+        let snapshot = self
+            .snapshots()
+            .load(entry)
+            .map_err(|e| format!("Failed to read snapshot: {}", e))?;
+        self.inventory = snapshot.inventory;
+        self.items_game.apply_attribute_types(&mut self.inventory);
+        self.open_item_windows.clear();
+        self.edit_item_states.clear();
+        self.edit_history.clear();
+        self.mark_inventory_changed();
+        self.save_inventory(&format!(
+            "Roll back to {}",
+            entry.created.format("%Y-%m-%d %H:%M:%S")
+        ))
+    }
+
+    /// Starts watching inventory.txt and config.txt for changes made by
+    /// other programs, taking the loaded copies as what they hold.
+    fn watch_game_files(&mut self) {
+        let Some(ref game_dir) = self.game_dir else {
+            return;
+        };
+        let dir = game_dir.path().join("csgo_gc");
+        self.inventory_watch = Some(FileWatcher::new(dir.join("inventory.txt")));
+        self.config_watch = Some(FileWatcher::new(dir.join("config.txt")));
+        self.inventory_base = self.inventory.clone();
+        self.config_base = self.config.clone();
+    }
+
+    /// Looks for changes to the game files about once a second, merging them
+    /// in when they do not conflict with the editor's.
+    pub fn poll_external_changes(&mut self) {
+        if self.is_live_rcon()
+            || self
+                .last_external_check
+                .is_some_and(|last| last.elapsed() < Duration::from_secs(1))
+        {
+            return;
+        }
+        self.last_external_check = Some(Instant::now());
+        self.check_inventory_changes();
+        self.check_config_changes();
+    }
+
+    /// Merges in changes made to inventory.txt since the editor last read or
+    /// wrote it. Returns `false` while conflicting changes wait to be
+    /// resolved.
+    fn check_inventory_changes(&mut self) -> bool {
+        if self.inventory_merge.is_some() {
+            return false;
+        }
+        let Some(watch) = &mut self.inventory_watch else {
+            return true;
+        };
+        if !watch.has_changed() {
+            return self.unreadable_inventory_change.is_none();
+        }
+        // A file that does not parse may be half written, so it is read
+        // again on the next check. Saving waits until it parses or the user
+        // decides which version to keep.
+        let mut theirs = match InventoryLoader::load(watch.path()) {
+            Ok(theirs) => theirs,
+            Err(e) => {
+                self.unreadable_inventory_change = Some(e.to_string());
+                return false;
+            }
+        };
+        watch.mark_seen();
+        self.unreadable_inventory_change = None;
+        // Typed like the editor's copy, so that only real changes differ.
+        self.items_game.apply_attribute_types(&mut theirs);
+
+        let merge = InventoryMerge::new(&self.inventory_base, &self.inventory, &theirs);
+        if merge.conflicts.is_empty() {
+            self.accept_inventory_merge(merge);
+            self.status_message =
+                Some("inventory.txt was changed by another program and has been reloaded".into());
+            true
+        } else {
+            self.inventory_merge = Some(merge);
+            false
+        }
+    }
+
+    fn accept_inventory_merge(&mut self, merge: InventoryMerge) {
+        let merged = merge.merged();
+        let old_items: HashMap<u64, &Item> = self
+            .inventory
+            .items
+            .iter()
+            .map(|item| (item.id, item))
+            .collect();
+        let changed: Vec<u64> = merged
+            .items
+            .iter()
+            .filter(|item| old_items.get(&item.id) != Some(item))
+            .map(|item| item.id)
+            .collect();
+        for item_id in changed {
+            self.edit_item_states.remove(&item_id);
+        }
+
+        self.inventory = merged;
+        self.inventory_base = merge.theirs;
+        // Undoing past this point would revert the other program's changes.
+        self.edit_history.clear();
+        self.mark_inventory_changed();
+        self.update_sorted_cache();
+    }
+
+    /// Settles a change to inventory.txt that cannot be read, either by
+    /// writing the editor's inventory over it or by loading what can be
+    /// read of it.
+    pub fn resolve_unreadable_inventory_change(&mut self, keep_ours: bool) -> Result<(), String> {
+        if self.unreadable_inventory_change.take().is_none() {
+            return Ok(());
+        }
+        let Some(watch) = &mut self.inventory_watch else {
+            return Ok(());
+        };
+        if keep_ours {
+            watch.mark_seen();
+            // The file may not parse at all, so it cannot be updated in place.
+            self.write_inventory("Overwrite changes made by another program", true)
+        } else {
+            let path = watch.path().to_path_buf();
+            self.reload_inventory(&path)
+        }
+    }
+
+    /// Finishes a merge with conflicts, using the side picked for each, and
+    /// saves the result if it differs from the file.
+    pub fn resolve_inventory_merge(&mut self) -> Result<(), String> {
+        let Some(merge) = self.inventory_merge.take() else {
+            return Ok(());
+        };
+        self.accept_inventory_merge(merge);
+        if self.inventory == self.inventory_base {
+            return Ok(());
+        }
+        self.save_inventory("Merge changes made by another program")
+    }
+
+    /// Like [`check_inventory_changes`](Self::check_inventory_changes), for
+    /// config.txt.
+    fn check_config_changes(&mut self) -> bool {
+        if self.config_merge.is_some() {
+            return false;
+        }
+        let Some(watch) = &mut self.config_watch else {
+            return true;
+        };
+        if !watch.has_changed() {
+            return self.unreadable_config_change.is_none();
+        }
+        // As with inventory.txt, a file that does not parse is read again on
+        // the next check, and is not saved over until it can be read.
+        let theirs = match ConfigLoader::load(watch.path()) {
+            Ok(theirs) => theirs,
+            Err(e) => {
+                if self.unreadable_config_change.is_none() {
+                    self.status_message = Some(format!(
+                        "config.txt was changed by another program and cannot be read: {}",
+                        e
+                    ));
+                }
+                self.unreadable_config_change = Some(e);
+                return false;
+            }
+        };
+        watch.mark_seen();
+        self.unreadable_config_change = None;
+
+        let merge = ConfigLoader::merge(&self.config_base, &self.config, &theirs);
+        self.config_base = theirs;
+        if merge.conflicts.is_empty() {
+            self.config = merge.keep_theirs;
+            self.status_message =
+                Some("config.txt was changed by another program and has been reloaded".into());
+            true
+        } else {
+            self.config_merge = Some(merge);
+            false
+        }
+    }
+
+    /// Finishes a config merge with conflicts, keeping either the editor's or
+    /// the file's value for each. The result is not saved.
+    pub fn resolve_config_merge(&mut self, keep_ours: bool) {
+        if let Some(merge) = self.config_merge.take() {
+            self.config = if keep_ours {
+                merge.keep_ours
+            } else {
+                merge.keep_theirs
+            };
+        }
+    }
+
+    /// Problems in the inventory, checked once and cached until the next
+    /// change to the inventory or the game data.
+    pub fn get_problems(&mut self) -> &[Problem] {
+        self.problems
+            .get_or_insert_with(|| validate(&self.inventory, &self.items_game, &self.data_provider))
+    }
+
+    /// Applies the quick fix of `problem` as an undoable edit.
+    pub fn fix_problem(&mut self, problem: &Problem) -> Result<(), String> {
+        match &problem.fix {
+            Some(fix) => self.apply_edit(fix.clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// Starts the case simulator over from `seed`.
+    pub fn reseed_cases(&mut self, seed: u64) {
+        self.case_seed = seed;
+        self.case_rng = CaseRng::new(seed);
+    }
+
+    /// Opens the case `case_def_index` with the config's rarity weights and
+    /// adds the drop to the inventory. The case itself is kept.
+    pub fn open_case(&mut self, case_def_index: u32) -> Result<(), String> {
+        let opener = CaseOpener::new(&self.items_game, &self.config.rarity_weights);
+        let mut item = opener.open(case_def_index, &mut self.case_rng)?;
+        item.id = self.allocate_item_ids(1)[0];
+        item.inventory = self.inventory.next_inventory_position();
+
+        let item_id = item.id;
+        self.apply_edit(InventoryEdit::Batch {
+            description: format!("Open case {}", case_def_index),
+            edits: vec![InventoryEdit::AddItem(item)],
+        })?;
+        self.open_item_windows.insert(item_id);
+        Ok(())
+    }
+
+    /// Opens the case `case_simulation_runs` times without adding anything.
+    pub fn simulate_case_openings(&mut self, case_def_index: u32) -> Result<(), String> {
+        let opener = CaseOpener::new(&self.items_game, &self.config.rarity_weights);
+        let stats = opener.simulate(
+            case_def_index,
+            self.case_simulation_runs,
+            &mut self.case_rng,
+        )?;
+        self.case_statistics = Some((case_def_index, stats));
+        Ok(())
+    }
+
+    /// The chance of each rarity dropping from `case_def_index`.
+    pub fn case_odds(&self, case_def_index: u32) -> Result<BTreeMap<u32, f64>, String> {
+        CaseOpener::new(&self.items_game, &self.config.rarity_weights).odds(case_def_index)
+    }
+
+    /// The contract the picked items make, if they make one.
+    pub fn plan_trade_up(&self) -> Result<TradeUp, String> {
+        let item_ids: Vec<u64> = self.trade_up_inputs.iter().copied().collect();
+        TradeUp::plan(&self.inventory, &self.items_game, &item_ids)
+    }
+
+    /// Trades the picked items up and opens the window of the result.
+    pub fn execute_trade_up(&mut self) -> Result<(), String> {
+        if self.is_live_rcon() {
+            return Err("RCON is connected; inventory.txt is read-only".to_string());
+        }
+        let plan = self.plan_trade_up()?;
+        let outcome = plan.roll(&mut self.case_rng).clone();
+        let item_id = self.allocate_item_ids(1)[0];
+        let position = self.inventory.next_inventory_position();
+        let edit = plan.execute(&outcome, item_id, position, &mut self.case_rng);
+
+        self.apply_edit(edit)?;
+        for input in &plan.inputs {
+            self.open_item_windows.remove(input);
+        }
+        self.trade_up_inputs.clear();
+        self.open_item_windows.insert(item_id);
+        Ok(())
+    }
+
+    /// Equips `item_id` for `team` in `slot`, taking out whatever the slot
+    /// held before.
+    pub fn equip_item(&mut self, item_id: u64, team: Team, slot: u32) -> Result<(), String> {
+        let edit = loadout::equip(&self.inventory, &self.items_game, item_id, team, slot)?;
+        self.apply_edit(edit)
+    }
+
+    pub fn unequip_item(&mut self, item_id: u64, team: Team) -> Result<(), String> {
+        let edit = loadout::unequip(&self.inventory, item_id, team)?;
+        self.apply_edit(edit)
+    }
+
+    /// Stops equipping the stock item `def_index` by default.
+    pub fn unequip_default(&mut self, def_index: u32) -> Result<(), String> {
+        self.apply_edit(InventoryEdit::SetDefaultEquip {
+            def_index,
+            equip: None,
+        })
+    }
+
     pub fn is_live_rcon(&self) -> bool {
         self.runtime_mode == RuntimeMode::LiveRcon
     }
@@ -853,6 +1607,20 @@ impl CsgoInventoryEditor {
         &self.load_errors
     }
 
+    /// Problems found while reading inventory.txt that did not stop it from
+    /// loading, such as syntax errors or unreadable items that were skipped.
+    pub fn get_load_warnings(&self) -> &[String] {
+        &self.load_warnings
+    }
+
+    pub fn inventory_needs_rewrite(&self) -> bool {
+        self.inventory_needs_rewrite
+    }
+
+    pub fn dismiss_load_warnings(&mut self) {
+        self.load_warnings.clear();
+    }
+
     pub fn get_rarity_name(&self, rarity_id: u32) -> String {
         // Find rarity by value and translate its loc_key
         if let Some(rarity) = self
@@ -960,33 +1728,41 @@ impl CsgoInventoryEditor {
     }
 
     pub fn create_missing_attribute_select_list(&self, item_id: u64) -> SelectWindowItems {
-        let current_attributes = self
+        let current_attributes: Option<HashSet<u32>> = self
             .edit_item_states
             .get(&item_id)
-            .map(|state| &state.attributes)
+            .map(|state| state.attributes.keys().copied().collect())
             .or_else(|| {
                 self.inventory
                     .items
                     .iter()
                     .find(|item| item.id == item_id)
-                    .map(|item| &item.attributes)
+                    .map(|item| item.attributes.keys().copied().collect())
             });
 
         let Some(current_attributes) = current_attributes else {
             return Vec::new();
         };
 
-        let mut items: SelectWindowItems = AVAILABLE_ATTRIBUTES
-            .iter()
-            .filter(|attr_id| !current_attributes.contains_key(attr_id))
-            .map(|attr_id| {
-                let fluent_key = get_attribute_fluent_key(*attr_id);
-                (attr_id.to_string(), tr!(&fluent_key).to_string(), None)
-            })
-            .collect();
+        self.items_game
+            .get_all_attribute_ids()
+            .into_iter()
+            .filter(|attr_id| !current_attributes.contains(attr_id))
+            .map(|attr_id| (attr_id.to_string(), self.get_attribute_name(attr_id), None))
+            .collect()
+    }
 
-        items.sort_by_key(|(id, _, _)| id.parse::<u32>().unwrap_or(0));
-        items
+    /// The editor's translated name for built-in attributes, otherwise the
+    /// items_game description.
+    pub fn get_attribute_name(&self, attr_id: u32) -> String {
+        if AVAILABLE_ATTRIBUTES.contains(&attr_id) {
+            return tr!(&get_attribute_fluent_key(attr_id)).to_string();
+        }
+        self.items_game
+            .attributes
+            .get(&attr_id)
+            .map(|attribute| attribute.get_display_name(&self.translations))
+            .unwrap_or_else(|| get_attribute_fluent_key(attr_id))
     }
 
     pub fn create_skin_select_list_for_weapon(&self, weapon_id: u32) -> SelectWindowItems {
@@ -1058,6 +1834,7 @@ impl CsgoInventoryEditor {
                     self.is_loading_online = false;
                     self.online_data_receiver = None;
                     self.cached_item_display_names.borrow_mut().clear();
+                    self.problems = None;
                 }
                 Ok(Err(e)) => {
                     self.status_message = Some(e);
@@ -1109,6 +1886,7 @@ impl CsgoInventoryEditor {
 
     pub fn mark_inventory_changed(&mut self) {
         self.cached_items_count = usize::MAX;
+        self.problems = None;
         self.cached_item_display_names.borrow_mut().clear();
     }
 
@@ -1128,6 +1906,7 @@ impl CsgoInventoryEditor {
     }
 
     fn refresh_display_metadata_cache(&mut self) {
+        self.problems = None;
         self.cached_quality_names = self
             .items_game
             .get_all_qualities_sorted()
@@ -1149,12 +1928,14 @@ impl CsgoInventoryEditor {
 
 impl Default for CsgoInventoryEditor {
     fn default() -> Self {
+        let case_seed = 0;
         Self {
             inventory: Inventory::default(),
             items_game: Arc::new(ItemsGame::default()),
             translations: Arc::new(GameTranslation::default()),
             open_item_windows: HashSet::new(),
             edit_item_states: HashMap::new(),
+            edit_history: EditHistory::default(),
             select_window_open: false,
             select_window_purpose: None,
             select_window_items: Vec::new(),
@@ -1199,6 +1980,30 @@ impl Default for CsgoInventoryEditor {
             cached_items_count: 0,
             cached_item_display_names: RefCell::new(HashMap::new()),
             load_errors: Vec::new(),
+            load_warnings: Vec::new(),
+            inventory_needs_rewrite: false,
+            backup_list: None,
+            snapshot_list: None,
+            inventory_watch: None,
+            config_watch: None,
+            inventory_base: Inventory::default(),
+            config_base: Config::default(),
+            last_external_check: None,
+            inventory_merge: None,
+            unreadable_inventory_change: None,
+            config_merge: None,
+            unreadable_config_change: None,
+            problems: None,
+            show_problems: false,
+            loadout_team: Team::Terrorist,
+            case_seed,
+            case_rng: CaseRng::new(case_seed),
+            case_simulation_runs: 1000,
+            case_statistics: None,
+            show_trade_up: false,
+            trade_up_inputs: BTreeSet::new(),
+            selected_snapshot: None,
+            snapshot_message: String::new(),
             last_theme: None,
         }
     }
@@ -1,4 +1,6 @@
-use crate::inventory::vdf::{VdfParser, VdfValue};
+use crate::core::{BackupStore, read_existing, write_atomic};
+use crate::inventory::vdf::{VdfDocument, VdfObject, VdfParser, VdfPatch, VdfValue};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
@@ -25,6 +27,8 @@ pub struct Config {
     pub rcon_port: u16,
     pub rcon_password: String,
     pub log_output: u32,
+    /// Relative drop weight of each rarity when csgo_gc opens a case.
+    pub rarity_weights: BTreeMap<u32, u32>,
 }
 
 impl Default for Config {
@@ -50,10 +54,37 @@ impl Default for Config {
             rcon_port: 37016,
             rcon_password: String::new(),
             log_output: 1,
+            rarity_weights: default_rarity_weights(),
         }
     }
 }
 
+/// csgo_gc's default weights, from consumer grade (1) to rare special
+/// items (99).
+fn default_rarity_weights() -> BTreeMap<u32, u32> {
+    BTreeMap::from([
+        (1, 10_000_000),
+        (2, 2_000_000),
+        (3, 400_000),
+        (4, 80_000),
+        (5, 16_000),
+        (6, 3_200),
+        (99, 1_280),
+    ])
+}
+
+/// The result of [`ConfigLoader::merge`]. Without conflicts both configs
+/// are the same.
+#[derive(Debug, Clone)]
+pub struct ConfigMerge {
+    /// The merge, with the editor's values where both sides changed one.
+    pub keep_ours: Config,
+    /// The merge, with the file's values where both sides changed one.
+    pub keep_theirs: Config,
+    /// One line per value both sides changed.
+    pub conflicts: Vec<String>,
+}
+
 pub struct ConfigLoader;
 
 impl ConfigLoader {
@@ -61,82 +92,144 @@ impl ConfigLoader {
         let content =
             fs::read_to_string(path).map_err(|e| format!("Failed to read config file: {}", e))?;
 
-        let vdf = VdfParser::parse(&content)
-            .map_err(|e| format!("Failed to parse config file: {}", e))?;
+        Self::parse_from_str(&content)
+    }
+
+    pub fn parse_from_str(content: &str) -> Result<Config, String> {
+        let vdf =
+            VdfParser::parse(content).map_err(|e| format!("Failed to parse config file: {}", e))?;
 
+        Ok(Self::from_vdf(&vdf))
+    }
+
+    /// Reads the fields of a parsed config.txt; missing or invalid entries
+    /// keep their defaults.
+    fn from_vdf(vdf: &VdfObject) -> Config {
         let mut config = Config::default();
 
-        if let Some(VdfValue::String(s)) = vdf.get("appid_override") {
-            config.appid_override = s.parse().unwrap_or(config.appid_override);
-        }
-        if let Some(VdfValue::Object(ranks)) = vdf.get("ranks") {
-            if let Some(VdfValue::String(s)) = ranks.get("competitive_rank") {
-                config.competitive_rank = s.parse().unwrap_or(config.competitive_rank);
-            }
-            if let Some(VdfValue::String(s)) = ranks.get("competitive_wins") {
-                config.competitive_wins = s.parse().unwrap_or(config.competitive_wins);
-            }
-            if let Some(VdfValue::String(s)) = ranks.get("wingman_rank") {
-                config.wingman_rank = s.parse().unwrap_or(config.wingman_rank);
-            }
-            if let Some(VdfValue::String(s)) = ranks.get("wingman_wins") {
-                config.wingman_wins = s.parse().unwrap_or(config.wingman_wins);
-            }
-            if let Some(VdfValue::String(s)) = ranks.get("dangerzone_rank") {
-                config.dangerzone_rank = s.parse().unwrap_or(config.dangerzone_rank);
-            }
-            if let Some(VdfValue::String(s)) = ranks.get("dangerzone_wins") {
-                config.dangerzone_wins = s.parse().unwrap_or(config.dangerzone_wins);
+        read(vdf, "appid_override", &mut config.appid_override);
+        read(vdf, "ranks/competitive_rank", &mut config.competitive_rank);
+        read(vdf, "ranks/competitive_wins", &mut config.competitive_wins);
+        read(vdf, "ranks/wingman_rank", &mut config.wingman_rank);
+        read(vdf, "ranks/wingman_wins", &mut config.wingman_wins);
+        read(vdf, "ranks/dangerzone_rank", &mut config.dangerzone_rank);
+        read(vdf, "ranks/dangerzone_wins", &mut config.dangerzone_wins);
+        read_bool(vdf, "vac_banned", &mut config.vac_banned);
+        read(vdf, "cmd_friendly", &mut config.cmd_friendly);
+        read(vdf, "cmd_teaching", &mut config.cmd_teaching);
+        read(vdf, "cmd_leader", &mut config.cmd_leader);
+        read(vdf, "player_level", &mut config.player_level);
+        read(vdf, "player_cur_xp", &mut config.player_cur_xp);
+        read_bool(vdf, "destroy_used_items", &mut config.destroy_used_items);
+        read_bool(
+            vdf,
+            "show_csgo_gc_servers_only",
+            &mut config.show_csgo_gc_servers_only,
+        );
+        read_bool(vdf, "rcon/enabled", &mut config.rcon_enabled);
+        read(vdf, "rcon/bind_address", &mut config.rcon_bind_address);
+        read(vdf, "rcon/port", &mut config.rcon_port);
+        read(vdf, "rcon/password", &mut config.rcon_password);
+        read(vdf, "log_output", &mut config.log_output);
+        if let Some(weights) = vdf.get("rarity_weights").and_then(|v| v.as_object()) {
+            let weights: BTreeMap<u32, u32> = weights
+                .iter()
+                .filter_map(|(rarity, weight)| {
+                    Some((rarity.parse().ok()?, weight.as_string()?.parse().ok()?))
+                })
+                .collect();
+            if !weights.is_empty() {
+                config.rarity_weights = weights;
             }
         }
 
-        if let Some(VdfValue::String(s)) = vdf.get("vac_banned") {
-            config.vac_banned = s == "1";
-        }
-        if let Some(VdfValue::String(s)) = vdf.get("cmd_friendly") {
-            config.cmd_friendly = s.parse().unwrap_or(config.cmd_friendly);
-        }
-        if let Some(VdfValue::String(s)) = vdf.get("cmd_teaching") {
-            config.cmd_teaching = s.parse().unwrap_or(config.cmd_teaching);
-        }
-        if let Some(VdfValue::String(s)) = vdf.get("cmd_leader") {
-            config.cmd_leader = s.parse().unwrap_or(config.cmd_leader);
-        }
-        if let Some(VdfValue::String(s)) = vdf.get("player_level") {
-            config.player_level = s.parse().unwrap_or(config.player_level);
-        }
-        if let Some(VdfValue::String(s)) = vdf.get("player_cur_xp") {
-            config.player_cur_xp = s.parse().unwrap_or(config.player_cur_xp);
-        }
-        if let Some(VdfValue::String(s)) = vdf.get("destroy_used_items") {
-            config.destroy_used_items = s == "1";
-        }
-        if let Some(VdfValue::String(s)) = vdf.get("show_csgo_gc_servers_only") {
-            config.show_csgo_gc_servers_only = s == "1";
-        }
-        if let Some(VdfValue::Object(rcon)) = vdf.get("rcon") {
-            if let Some(VdfValue::String(s)) = rcon.get("enabled") {
-                config.rcon_enabled = s == "1";
-            }
-            if let Some(VdfValue::String(s)) = rcon.get("bind_address") {
-                config.rcon_bind_address = s.clone();
-            }
-            if let Some(VdfValue::String(s)) = rcon.get("port") {
-                config.rcon_port = s.parse().unwrap_or(config.rcon_port);
-            }
-            if let Some(VdfValue::String(s)) = rcon.get("password") {
-                config.rcon_password = s.clone();
-            }
-        }
-        if let Some(VdfValue::String(s)) = vdf.get("log_output") {
-            config.log_output = s.parse().unwrap_or(config.log_output);
+        config
+    }
+
+    /// Three-way merge of the editor's copy of the config (`ours`) with the
+    /// file as it is now (`theirs`), given the file as last read or written
+    /// (`base`). Values changed on one side only are taken from that side.
+    pub fn merge(base: &Config, ours: &Config, theirs: &Config) -> ConfigMerge {
+        let (base, ours, theirs) = (Self::to_vdf(base), Self::to_vdf(ours), Self::to_vdf(theirs));
+
+        let our_changes = VdfPatch::diff(&base, &ours);
+        let mut keep_theirs = theirs.clone();
+        let conflicts = our_changes
+            .apply_each(&mut keep_theirs)
+            .into_iter()
+            .map(|error| format!("{} ({})", our_changes.changes[error.change], error.message))
+            .collect();
+
+        let mut keep_ours = ours;
+        VdfPatch::diff(&base, &theirs).apply_each(&mut keep_ours);
+
+        ConfigMerge {
+            keep_ours: Self::from_vdf(&keep_ours),
+            keep_theirs: Self::from_vdf(&keep_theirs),
+            conflicts,
         }
+    }
 
-        Ok(config)
+    /// What [`save`](Self::save) would change in the file at `path`. A file
+    /// that is missing counts as empty.
+    pub fn preview_save(config: &Config, path: &Path) -> Result<VdfPatch, String> {
+        let original =
+            read_existing(path).map_err(|e| format!("Failed to read config file: {}", e))?;
+        let content = Self::render(config, original.as_deref())?;
+
+        let before = original
+            .and_then(|original| VdfParser::parse(&original).ok())
+            .unwrap_or_default();
+        let after = VdfParser::parse(&content)
+            .map_err(|e| format!("Failed to render config file: {}", e))?;
+        Ok(VdfPatch::diff(&before, &after))
     }
 
     pub fn save(config: &Config, path: &Path) -> Result<(), String> {
-        let mut ranks = std::collections::HashMap::new();
+        let original =
+            read_existing(path).map_err(|e| format!("Failed to read config file: {}", e))?;
+        let content = Self::render(config, original.as_deref())?;
+
+        write_atomic(path, content).map_err(|e| format!("Failed to write config file: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Like [`save`](Self::save), but first copies the current file into
+    /// `backups`.
+    pub fn save_with_backup(
+        config: &Config,
+        path: &Path,
+        backups: &BackupStore,
+    ) -> Result<(), String> {
+        backups
+            .backup(path)
+            .map_err(|e| format!("Failed to back up config file: {}", e))?;
+        Self::save(config, path)
+    }
+
+    fn render(config: &Config, original: Option<&str>) -> Result<String, String> {
+        let root = Self::to_vdf(config);
+
+        // Update the existing file in place so comments and keys this editor
+        // does not know about survive the save.
+        match original {
+            Some(original) => {
+                let mut document = VdfDocument::parse(original).map_err(|e| {
+                    format!(
+                        "config.txt cannot be updated in place, as it does not parse: {}",
+                        e
+                    )
+                })?;
+                document.update(&root, &mut |_| false);
+                Ok(document.to_string())
+            }
+            None => Ok(VdfParser::to_string(&VdfValue::Object(root))),
+        }
+    }
+
+    fn to_vdf(config: &Config) -> VdfObject {
+        let mut ranks = VdfObject::new();
         ranks.insert(
             "competitive_rank".to_string(),
             VdfValue::String(config.competitive_rank.to_string()),
@@ -162,16 +255,12 @@ impl ConfigLoader {
             VdfValue::String(config.dangerzone_wins.to_string()),
         );
 
-        let mut rarity_weights = std::collections::HashMap::new();
-        rarity_weights.insert("1".to_string(), VdfValue::String("10000000".to_string()));
-        rarity_weights.insert("2".to_string(), VdfValue::String("2000000".to_string()));
-        rarity_weights.insert("3".to_string(), VdfValue::String("400000".to_string()));
-        rarity_weights.insert("4".to_string(), VdfValue::String("80000".to_string()));
-        rarity_weights.insert("5".to_string(), VdfValue::String("16000".to_string()));
-        rarity_weights.insert("6".to_string(), VdfValue::String("3200".to_string()));
-        rarity_weights.insert("99".to_string(), VdfValue::String("1280".to_string()));
+        let mut rarity_weights = VdfObject::new();
+        for (rarity, weight) in &config.rarity_weights {
+            rarity_weights.insert(rarity.to_string(), VdfValue::String(weight.to_string()));
+        }
 
-        let mut root = std::collections::HashMap::new();
+        let mut root = VdfObject::new();
         root.insert(
             "appid_override".to_string(),
             VdfValue::String(config.appid_override.to_string()),
@@ -226,7 +315,7 @@ impl ConfigLoader {
             }),
         );
 
-        let mut rcon = std::collections::HashMap::new();
+        let mut rcon = VdfObject::new();
         rcon.insert(
             "enabled".to_string(),
             VdfValue::String(if config.rcon_enabled {
@@ -252,11 +341,19 @@ impl ConfigLoader {
             "log_output".to_string(),
             VdfValue::String(config.log_output.to_string()),
         );
+        root
+    }
+}
 
-        let content = VdfParser::to_string(&VdfValue::Object(root));
-
-        fs::write(path, content).map_err(|e| format!("Failed to write config file: {}", e))?;
+/// Overwrites `field` with the value at `path` if it is present and parses.
+fn read<T: std::str::FromStr>(vdf: &VdfObject, path: &str, field: &mut T) {
+    if let Some(value) = vdf.select_str(path).and_then(|s| s.parse().ok()) {
+        *field = value;
+    }
+}
 
-        Ok(())
+fn read_bool(vdf: &VdfObject, path: &str, field: &mut bool) {
+    if let Some(s) = vdf.select_str(path) {
+        *field = s == "1";
     }
 }
@@ -0,0 +1,252 @@
+use crate::core::game_dir::editor_dir;
+use chrono::{Local, NaiveDateTime};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S%.3f";
+
+/// The contents of the file at `path`, or `None` if there is no such file.
+/// Any other error, such as a lack of permission or text that is not UTF-8,
+/// is returned rather than taken as a missing file.
+pub fn read_existing(path: &Path) -> std::io::Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Replaces the file at `path` with `contents` without ever leaving it half
+/// written: the data goes to a temporary file next to it, which is flushed to
+/// disk and then renamed over the original.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let file_name = path.file_name().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "path has no file name")
+    })?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = (|| {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(contents.as_ref())?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// A copy of a game file taken before it was overwritten.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupEntry {
+    pub path: PathBuf,
+    /// Name of the file the backup was taken from, e.g. `inventory.txt`.
+    pub file_name: String,
+    pub created: NaiveDateTime,
+}
+
+/// Timestamped copies of game files, kept in one directory. Only the newest
+/// `keep` backups of each file are retained.
+#[derive(Debug, Clone)]
+pub struct BackupStore {
+    dir: PathBuf,
+    keep: usize,
+}
+
+impl BackupStore {
+    pub fn new(dir: impl Into<PathBuf>, keep: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            keep,
+        }
+    }
+
+    /// The store under `csgo_gc/editor/backups`.
+    pub fn in_editor_dir(keep: usize) -> Self {
+        Self::new(editor_dir().join("backups"), keep)
+    }
+
+    /// Copies the file at `path` into the store, unless it is missing, backups
+    /// are disabled, or the newest backup already holds the same contents.
+    pub fn backup(&self, path: &Path) -> std::io::Result<Option<BackupEntry>> {
+        if self.keep == 0 {
+            return Ok(None);
+        }
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            return Ok(None);
+        };
+        let contents = match fs::read(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        if let Some(latest) = self.list(file_name).first()
+            && fs::read(&latest.path).is_ok_and(|previous| previous == contents)
+        {
+            return Ok(None);
+        }
+
+        fs::create_dir_all(&self.dir)?;
+        let created = Local::now().naive_local();
+        let backup_path = self.dir.join(format!(
+            "{}.{}.bak",
+            file_name,
+            created.format(TIMESTAMP_FORMAT)
+        ));
+        write_atomic(&backup_path, &contents)?;
+
+        for old in self.list(file_name).iter().skip(self.keep) {
+            let _ = fs::remove_file(&old.path);
+        }
+
+        Ok(Some(BackupEntry {
+            path: backup_path,
+            file_name: file_name.to_string(),
+            created,
+        }))
+    }
+
+    /// Backups of `file_name`, newest first.
+    pub fn list(&self, file_name: &str) -> Vec<BackupEntry> {
+        self.list_all()
+            .into_iter()
+            .filter(|entry| entry.file_name == file_name)
+            .collect()
+    }
+
+    /// Every backup in the store, newest first.
+    pub fn list_all(&self) -> Vec<BackupEntry> {
+        let Ok(read_dir) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut entries: Vec<BackupEntry> = read_dir
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| parse_backup_name(entry.path()))
+            .collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.created));
+        entries
+    }
+
+    /// Puts `entry` back in place of the file at `target`, first backing up
+    /// what `target` holds now so the restore can itself be undone.
+    pub fn restore(&self, entry: &BackupEntry, target: &Path) -> std::io::Result<()> {
+        let contents = fs::read(&entry.path)?;
+        self.backup(target)?;
+        write_atomic(target, contents)
+    }
+}
+
+/// Reads `<file name>.<timestamp>.bak`.
+fn parse_backup_name(path: PathBuf) -> Option<BackupEntry> {
+    let name = path.file_name()?.to_str()?.strip_suffix(".bak")?;
+    // The timestamp holds one dot, before its milliseconds.
+    let (rest, millis) = name.rsplit_once('.')?;
+    let (file_name, seconds) = rest.rsplit_once('.')?;
+    let created =
+        NaiveDateTime::parse_from_str(&format!("{}.{}", seconds, millis), TIMESTAMP_FORMAT).ok()?;
+
+    Some(BackupEntry {
+        file_name: file_name.to_string(),
+        path,
+        created,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BackupStore, write_atomic};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    /// An empty directory of its own for each test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "csgo-gc-backup-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Writes each of `versions` to `target` and backs it up, waiting in
+    /// between so that the backups get distinct timestamps.
+    fn back_up_versions(store: &BackupStore, target: &Path, versions: &[&str]) {
+        for version in versions {
+            write_atomic(target, version).unwrap();
+            store.backup(target).unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn writes_atomically_without_leaving_a_temporary_file() {
+        let dir = test_dir("atomic");
+        let target = dir.join("inventory.txt");
+
+        write_atomic(&target, "v1").unwrap();
+        write_atomic(&target, "v2").unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "v2");
+        assert!(!dir.join(".inventory.txt.tmp").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn skips_missing_and_unchanged_files() {
+        let dir = test_dir("skip");
+        let target = dir.join("inventory.txt");
+        let store = BackupStore::new(dir.join("backups"), 2);
+
+        assert!(store.backup(&target).unwrap().is_none());
+        write_atomic(&target, "v1").unwrap();
+        assert!(store.backup(&target).unwrap().is_some());
+        assert!(store.backup(&target).unwrap().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_only_the_newest_backups() {
+        let dir = test_dir("rotate");
+        let target = dir.join("inventory.txt");
+        let store = BackupStore::new(dir.join("backups"), 2);
+
+        back_up_versions(&store, &target, &["v1", "v2", "v3", "v4"]);
+        let backups = store.list("inventory.txt");
+        assert_eq!(backups.len(), 2);
+        assert_eq!(fs::read_to_string(&backups[0].path).unwrap(), "v4");
+        assert_eq!(fs::read_to_string(&backups[1].path).unwrap(), "v3");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn restoring_backs_up_the_current_file_first() {
+        let dir = test_dir("restore");
+        let target = dir.join("inventory.txt");
+        let store = BackupStore::new(dir.join("backups"), 2);
+
+        back_up_versions(&store, &target, &["v1", "v2"]);
+        let backups = store.list("inventory.txt");
+        write_atomic(&target, "v3").unwrap();
+        store.restore(&backups[1], &target).unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "v1");
+        assert_eq!(
+            fs::read_to_string(&store.list("inventory.txt")[0].path).unwrap(),
+            "v3"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// What a file looked like when it was last read or written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fingerprint {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+impl Fingerprint {
+    fn read(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let contents = fs::read(path).ok()?;
+        let mut hasher = DefaultHasher::new();
+        contents.hash(&mut hasher);
+        Some(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+            hash: hasher.finish(),
+        })
+    }
+}
+
+/// Notices when a file is changed by another program. The modification time
+/// and size are checked on every poll; the contents are only hashed when
+/// those differ, so touching a file without changing it is not a change.
+#[derive(Debug, Clone)]
+pub struct FileWatcher {
+    path: PathBuf,
+    seen: Option<Fingerprint>,
+}
+
+impl FileWatcher {
+    /// Watches `path`, taking what it holds now as already seen.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let seen = Fingerprint::read(&path);
+        Self { path, seen }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file differs from what was last seen. A file that was
+    /// deleted is not reported.
+    pub fn has_changed(&mut self) -> bool {
+        let Ok(metadata) = fs::metadata(&self.path) else {
+            return false;
+        };
+        if let Some(seen) = self.seen
+            && seen.modified == metadata.modified().ok()
+            && seen.len == metadata.len()
+        {
+            return false;
+        }
+
+        match Fingerprint::read(&self.path) {
+            Some(current) if Some(current.hash) == self.seen.map(|seen| seen.hash) => {
+                // Same contents with a new timestamp.
+                self.seen = Some(current);
+                false
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// Takes what the file holds now as seen, e.g. after the editor wrote it.
+    pub fn mark_seen(&mut self) {
+        self.seen = Fingerprint::read(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileWatcher;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// A path to inventory.txt in an empty directory of the test's own.
+    fn test_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "csgo-gc-watch-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("inventory.txt")
+    }
+
+    /// A watcher of the file at `path`, created holding `contents`.
+    fn watching(path: &Path, contents: &str) -> FileWatcher {
+        fs::write(path, contents).unwrap();
+        FileWatcher::new(path)
+    }
+
+    #[test]
+    fn notices_a_missing_file_being_created() {
+        let path = test_file("create");
+
+        let mut watcher = FileWatcher::new(&path);
+        assert!(!watcher.has_changed());
+        fs::write(&path, "one").unwrap();
+        assert!(watcher.has_changed());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn marking_seen_clears_the_change() {
+        let path = test_file("seen");
+
+        let mut watcher = watching(&path, "one");
+        fs::write(&path, "two!").unwrap();
+        assert!(watcher.has_changed());
+        watcher.mark_seen();
+        assert!(!watcher.has_changed());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn ignores_writes_of_the_same_contents() {
+        let path = test_file("same");
+
+        let mut watcher = watching(&path, "one");
+        fs::write(&path, "one").unwrap();
+        assert!(!watcher.has_changed());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn notices_changed_contents() {
+        let path = test_file("changed");
+
+        let mut watcher = watching(&path, "one");
+        fs::write(&path, "two!").unwrap();
+        assert!(watcher.has_changed());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -1,3 +1,7 @@
+pub mod backup;
+pub mod file_watch;
 pub mod game_dir;
 
+pub use backup::{BackupEntry, BackupStore, read_existing, write_atomic};
+pub use file_watch::FileWatcher;
 pub use game_dir::GameDir;
@@ -0,0 +1,374 @@
+//! Simulated case openings.
+//!
+//! Drops are picked the way csgo_gc picks them: first a rarity, weighted by
+//! the `rarity_weights` of config.txt among the rarities the case holds,
+//! then one of the case's skins of that rarity. Everything random comes
+//! from a seedable [`CaseRng`], so the same seed opens the same items.
+
+use crate::inventory::item_attribute::{AttributeValue, ItemAttribute};
+use crate::inventory::items_game::{IGLootEntry, ItemsGame};
+use crate::inventory::models::Item;
+use std::collections::{BTreeMap, HashMap};
+
+/// `origin` of items found in a case.
+const ORIGIN_FOUND_IN_CRATE: u32 = 8;
+
+/// A small SplitMix64 generator; good enough for drops and reproducible
+/// across platforms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseRng {
+    state: u64,
+}
+
+impl CaseRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0.0..1.0`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A number in `0..bound`; `bound` must not be 0.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        ((u128::from(self.next_u64()) * u128::from(bound)) >> 64) as u64
+    }
+}
+
+/// Rarities dropped by a run of [`CaseOpener::simulate`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CaseStats {
+    pub openings: u32,
+    pub stattrak: u32,
+    /// How many drops each rarity had.
+    pub by_rarity: BTreeMap<u32, u32>,
+}
+
+impl CaseStats {
+    /// The share of drops of `rarity`, from 0 to 1.
+    pub fn share(&self, rarity: u32) -> f64 {
+        if self.openings == 0 {
+            return 0.0;
+        }
+        f64::from(self.by_rarity.get(&rarity).copied().unwrap_or(0)) / f64::from(self.openings)
+    }
+}
+
+pub struct CaseOpener<'a> {
+    items_game: &'a ItemsGame,
+    rarity_weights: &'a BTreeMap<u32, u32>,
+    /// Chance of a drop being StatTrak, from 0 to 1.
+    pub stattrak_chance: f64,
+}
+
+impl<'a> CaseOpener<'a> {
+    /// StatTrak drops are one in ten, as in the game.
+    pub const DEFAULT_STATTRAK_CHANCE: f64 = 0.1;
+
+    pub fn new(items_game: &'a ItemsGame, rarity_weights: &'a BTreeMap<u32, u32>) -> Self {
+        Self {
+            items_game,
+            rarity_weights,
+            stattrak_chance: Self::DEFAULT_STATTRAK_CHANCE,
+        }
+    }
+
+    /// The chance of each rarity the case holds, from 0 to 1.
+    pub fn odds(&self, case_def_index: u32) -> Result<BTreeMap<u32, f64>, String> {
+        let weights = self.case_weights(case_def_index)?;
+        let total: u64 = weights.values().sum();
+        Ok(weights
+            .into_iter()
+            .map(|(rarity, weight)| (rarity, weight as f64 / total as f64))
+            .collect())
+    }
+
+    /// Opens the case once. The item has id 0 and no inventory position; the
+    /// caller gives it both before adding it.
+    pub fn open(&self, case_def_index: u32, rng: &mut CaseRng) -> Result<Item, String> {
+        let weights = self.case_weights(case_def_index)?;
+        let total: u64 = weights.values().sum();
+
+        let mut roll = rng.below(total);
+        let rarity = weights
+            .iter()
+            .find(|(_, weight)| {
+                let hit = roll < **weight;
+                roll = roll.saturating_sub(**weight);
+                hit
+            })
+            .map(|(rarity, _)| *rarity)
+            .expect("roll is below the total weight");
+
+        let skins: Vec<&IGLootEntry> = self
+            .items_game
+            .case_contents(case_def_index)
+            .iter()
+            .filter(|entry| entry.rarity == rarity)
+            .collect();
+        let skin = skins[rng.below(skins.len() as u64) as usize];
+
+        let stattrak = rng.next_f64() < self.stattrak_chance;
+        Ok(self.create_drop(skin, stattrak, rng))
+    }
+
+    /// Opens the case `count` times and counts the rarities dropped.
+    pub fn simulate(
+        &self,
+        case_def_index: u32,
+        count: u32,
+        rng: &mut CaseRng,
+    ) -> Result<CaseStats, String> {
+        let mut stats = CaseStats::default();
+        let strange = self.quality("strange", 9);
+        for _ in 0..count {
+            let item = self.open(case_def_index, rng)?;
+            stats.openings += 1;
+            if item.quality == strange {
+                stats.stattrak += 1;
+            }
+            *stats.by_rarity.entry(item.rarity).or_default() += 1;
+        }
+        Ok(stats)
+    }
+
+    /// The weight of each rarity the case holds; rarities without a weight
+    /// never drop.
+    fn case_weights(&self, case_def_index: u32) -> Result<BTreeMap<u32, u64>, String> {
+        let contents = self.items_game.case_contents(case_def_index);
+        if contents.is_empty() {
+            return Err(format!(
+                "The contents of case {} are unknown",
+                case_def_index
+            ));
+        }
+
+        let mut weights: HashMap<u32, u64> = HashMap::new();
+        for entry in contents {
+            if let Some(&weight) = self.rarity_weights.get(&entry.rarity)
+                && weight > 0
+            {
+                weights.insert(entry.rarity, u64::from(weight));
+            }
+        }
+        if weights.is_empty() {
+            return Err(format!(
+                "None of the rarities in case {} has a drop weight",
+                case_def_index
+            ));
+        }
+        Ok(weights.into_iter().collect())
+    }
+
+    fn create_drop(&self, skin: &IGLootEntry, stattrak: bool, rng: &mut CaseRng) -> Item {
+        let (min_wear, max_wear) = self.items_game.get_paint_kit_wear_range(skin.paint_index);
+        let wear = min_wear + (max_wear - min_wear) * rng.next_f64() as f32;
+        let seed = rng.below(1001) as f32;
+
+        let mut attributes = HashMap::new();
+        attributes.insert(
+            ItemAttribute::SkinPaintIndex.id(),
+            AttributeValue::float(skin.paint_index as f32),
+        );
+        attributes.insert(
+            ItemAttribute::SkinPaintSeed.id(),
+            AttributeValue::float(seed),
+        );
+        attributes.insert(
+            ItemAttribute::SkinPaintWear.id(),
+            AttributeValue::float(wear),
+        );
+        let quality = if stattrak {
+            attributes.insert(ItemAttribute::StatTrakCount.id(), AttributeValue::uint32(0));
+            attributes.insert(ItemAttribute::StatTrakType.id(), AttributeValue::uint32(0));
+            self.quality("strange", 9)
+        } else {
+            self.quality("unique", 4)
+        };
+
+        Item {
+            def_index: skin.def_index,
+            level: 1,
+            quality,
+            origin: ORIGIN_FOUND_IN_CRATE,
+            rarity: skin.rarity,
+            attributes,
+            ..Default::default()
+        }
+    }
+
+    fn quality(&self, name: &str, fallback: u32) -> u32 {
+        self.items_game
+            .qualities
+            .get(name)
+            .map_or(fallback, |quality| quality.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CaseOpener, CaseRng};
+    use crate::config::{Config, ConfigLoader};
+    use crate::inventory::item_attribute::AttributeValue;
+    use crate::inventory::items_game::ItemsGame;
+    use crate::inventory::items_game_loader::ItemsGameLoader;
+    use std::collections::BTreeMap;
+
+    /// A case with two rare skins, a mythical one and a knife.
+    fn items_game() -> ItemsGame {
+        ItemsGameLoader::parse_from_str(
+            r#"
+            "items_game"
+            {
+                "rarities"
+                {
+                    "rare" { "value" "3" }
+                    "mythical" { "value" "4" }
+                    "ancient" { "value" "6" }
+                }
+                "qualities" { "unique" { "value" "4" } "strange" { "value" "9" } }
+                "items"
+                {
+                    "7" { "name" "weapon_ak47" }
+                    "507" { "name" "weapon_knife_karambit" }
+                    "4001" { "name" "crate_community_1"
+                        "attributes" { "set supply crate series" { "attribute_class" "supply_crate_series" "value" "10" } } }
+                }
+                "paint_kits"
+                {
+                    "0" { "name" "default" "wear_remap_min" "0.06" "wear_remap_max" "0.8" }
+                    "44" { "name" "cu_ak47_case_hardened" }
+                    "45" { "name" "cu_ak47_redline" "wear_remap_min" "0.1" "wear_remap_max" "0.7" }
+                    "180" { "name" "cu_ak47_fire_serpent" }
+                    "38" { "name" "aa_fade" }
+                }
+                "revolving_loot_lists" { "10" "crate_community_1" }
+                "client_loot_lists"
+                {
+                    "crate_community_1_rare" { "[cu_ak47_case_hardened]weapon_ak47" "1" "[cu_ak47_redline]weapon_ak47" "1" }
+                    "crate_community_1_mythical" { "[cu_ak47_fire_serpent]weapon_ak47" "1" }
+                    "crate_community_1_ancient" { "[aa_fade]weapon_knife_karambit" "1" }
+                    "crate_community_1"
+                    {
+                        "crate_community_1_rare" "1"
+                        "crate_community_1_mythical" "1"
+                        "crate_community_1_ancient" "1"
+                    }
+                }
+            }
+            "#,
+        )
+        .unwrap()
+    }
+
+    /// Weights for rare, mythical and legendary drops only, so the knife
+    /// (rarity 6) never drops.
+    fn config() -> Config {
+        ConfigLoader::parse_from_str(
+            r#"
+            "rarity_weights"
+            {
+                "3" "750"
+                "4" "250"
+                "5" "100"
+            }
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn odds_come_from_the_weights_of_the_rarities_the_case_holds() {
+        let items_game = items_game();
+        let config = config();
+        let opener = CaseOpener::new(&items_game, &config.rarity_weights);
+
+        assert_eq!(
+            opener.odds(4001).unwrap(),
+            BTreeMap::from([(3, 0.75), (4, 0.25)])
+        );
+    }
+
+    #[test]
+    fn cases_without_contents_or_weights_cannot_be_opened() {
+        let items_game = items_game();
+        let config = config();
+        let opener = CaseOpener::new(&items_game, &config.rarity_weights);
+        assert!(opener.open(7, &mut CaseRng::new(1)).is_err());
+
+        let knives_only = BTreeMap::from([(6, 1)]);
+        let weights = BTreeMap::from([(1, 100)]);
+        assert!(
+            CaseOpener::new(&items_game, &weights)
+                .open(4001, &mut CaseRng::new(1))
+                .is_err()
+        );
+        assert!(
+            CaseOpener::new(&items_game, &knives_only)
+                .open(4001, &mut CaseRng::new(1))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn the_same_seed_opens_the_same_item() {
+        let items_game = items_game();
+        let config = config();
+        let opener = CaseOpener::new(&items_game, &config.rarity_weights);
+
+        let first = opener.open(4001, &mut CaseRng::new(42)).unwrap();
+        assert_eq!(first, opener.open(4001, &mut CaseRng::new(42)).unwrap());
+    }
+
+    #[test]
+    fn drops_are_skins_of_the_case_worn_within_their_range() {
+        let items_game = items_game();
+        let config = config();
+        let opener = CaseOpener::new(&items_game, &config.rarity_weights);
+
+        let mut rng = CaseRng::new(7);
+        for _ in 0..200 {
+            let item = opener.open(4001, &mut rng).unwrap();
+            assert_eq!((item.def_index, item.origin), (7, 8));
+            let paint_index = item.attributes[&6].as_u32().unwrap();
+            assert!([44, 45, 180].contains(&paint_index));
+            let (min, max) = items_game.get_paint_kit_wear_range(paint_index);
+            let wear = item.attributes[&8].as_f32().unwrap();
+            assert!((min..=max).contains(&wear));
+        }
+    }
+
+    #[test]
+    fn simulated_drops_follow_the_odds() {
+        let items_game = items_game();
+        let config = config();
+        let opener = CaseOpener::new(&items_game, &config.rarity_weights);
+
+        let stats = opener.simulate(4001, 10_000, &mut CaseRng::new(7)).unwrap();
+        assert_eq!(stats.openings, 10_000);
+        assert_eq!(stats.by_rarity.keys().copied().collect::<Vec<_>>(), [3, 4]);
+        assert!((stats.share(3) - 0.75).abs() < 0.02);
+        assert!((f64::from(stats.stattrak) / 10_000.0 - 0.1).abs() < 0.02);
+    }
+
+    #[test]
+    fn stattrak_drops_start_counting_at_zero() {
+        let items_game = items_game();
+        let config = config();
+        let mut opener = CaseOpener::new(&items_game, &config.rarity_weights);
+        opener.stattrak_chance = 1.0;
+
+        let item = opener.open(4001, &mut CaseRng::new(3)).unwrap();
+        assert_eq!(item.quality, 9);
+        assert_eq!(item.attributes.get(&80), Some(&AttributeValue::uint32(0)));
+    }
+}
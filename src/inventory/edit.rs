@@ -0,0 +1,486 @@
+//! Reversible edits to an [`Inventory`].
+//!
+//! Applying an [`InventoryEdit`] returns the edit that reverses it, which is
+//! what [`EditHistory`] keeps on its undo stack; undoing in turn returns the
+//! edit that redoes. Edits find items by id rather than position, except when
+//! a deleted item is put back where it was.
+
+use crate::inventory::item_attribute::AttributeValue;
+use crate::inventory::models::{DefaultEquip, Inventory, Item};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InventoryEdit {
+    /// Appends an item.
+    AddItem(Item),
+    /// Removes the item with this id.
+    DeleteItem(u64),
+    /// Inserts an item at `index`; the reverse of a delete.
+    RestoreItem {
+        index: usize,
+        item: Item,
+    },
+    /// Sets an attribute, or removes it when `value` is `None`.
+    SetAttribute {
+        item_id: u64,
+        attribute: u32,
+        value: Option<AttributeValue>,
+    },
+    SetRarity {
+        item_id: u64,
+        rarity: u32,
+    },
+    /// Replaces the item with the same id.
+    ReplaceItem(Item),
+    /// Equips the stock item `def_index` by default, or stops doing so when
+    /// `equip` is `None`.
+    SetDefaultEquip {
+        def_index: u32,
+        equip: Option<DefaultEquip>,
+    },
+    /// Replaces every item at once, e.g. after renumbering.
+    SetItems {
+        description: String,
+        items: Vec<Item>,
+    },
+    /// Several edits applied together and undone together.
+    Batch {
+        description: String,
+        edits: Vec<InventoryEdit>,
+    },
+}
+
+impl InventoryEdit {
+    /// The edit that turns `old` into `new`, or `None` if they are equal.
+    /// Changes to nothing but the rarity and attributes become one edit each;
+    /// anything else replaces the whole item.
+    pub fn update_item(old: &Item, new: &Item) -> Option<Self> {
+        if old == new {
+            return None;
+        }
+        let only_rarity_and_attributes = Item {
+            rarity: new.rarity,
+            attributes: new.attributes.clone(),
+            ..old.clone()
+        } == *new;
+        if !only_rarity_and_attributes {
+            return Some(InventoryEdit::ReplaceItem(new.clone()));
+        }
+
+        let mut edits = Vec::new();
+        if old.rarity != new.rarity {
+            edits.push(InventoryEdit::SetRarity {
+                item_id: new.id,
+                rarity: new.rarity,
+            });
+        }
+        let mut attribute_ids: Vec<u32> = old
+            .attributes
+            .keys()
+            .chain(new.attributes.keys())
+            .copied()
+            .collect();
+        attribute_ids.sort_unstable();
+        attribute_ids.dedup();
+        for attribute in attribute_ids {
+            let value = new.attributes.get(&attribute);
+            if old.attributes.get(&attribute) != value {
+                edits.push(InventoryEdit::SetAttribute {
+                    item_id: new.id,
+                    attribute,
+                    value: value.cloned(),
+                });
+            }
+        }
+
+        if edits.len() == 1 {
+            edits.pop()
+        } else {
+            Some(InventoryEdit::Batch {
+                description: format!("Edit item {}", new.id),
+                edits,
+            })
+        }
+    }
+
+    /// A short English summary, used for snapshot messages and tooltips.
+    pub fn description(&self) -> String {
+        match self {
+            InventoryEdit::AddItem(item) => format!("Add item {}", item.id),
+            InventoryEdit::DeleteItem(item_id) => format!("Delete item {}", item_id),
+            InventoryEdit::RestoreItem { item, .. } => format!("Restore item {}", item.id),
+            InventoryEdit::SetAttribute {
+                item_id,
+                attribute,
+                value: Some(_),
+            } => format!("Set attribute {} of item {}", attribute, item_id),
+            InventoryEdit::SetAttribute {
+                item_id,
+                attribute,
+                value: None,
+            } => format!("Remove attribute {} of item {}", attribute, item_id),
+            InventoryEdit::SetRarity { item_id, .. } => format!("Set rarity of item {}", item_id),
+            InventoryEdit::ReplaceItem(item) => format!("Edit item {}", item.id),
+            InventoryEdit::SetDefaultEquip {
+                def_index,
+                equip: Some(_),
+            } => format!("Equip stock item {}", def_index),
+            InventoryEdit::SetDefaultEquip {
+                def_index,
+                equip: None,
+            } => format!("Unequip stock item {}", def_index),
+            InventoryEdit::SetItems { description, .. }
+            | InventoryEdit::Batch { description, .. } => description.clone(),
+        }
+    }
+
+    /// Ids of the items the edit touches.
+    pub fn item_ids(&self) -> Vec<u64> {
+        match self {
+            InventoryEdit::AddItem(item)
+            | InventoryEdit::RestoreItem { item, .. }
+            | InventoryEdit::ReplaceItem(item) => vec![item.id],
+            InventoryEdit::DeleteItem(item_id)
+            | InventoryEdit::SetAttribute { item_id, .. }
+            | InventoryEdit::SetRarity { item_id, .. } => vec![*item_id],
+            InventoryEdit::SetDefaultEquip { .. } => Vec::new(),
+            InventoryEdit::SetItems { items, .. } => items.iter().map(|item| item.id).collect(),
+            InventoryEdit::Batch { edits, .. } => {
+                edits.iter().flat_map(InventoryEdit::item_ids).collect()
+            }
+        }
+    }
+
+    /// Applies the edit and returns the one that reverses it. On error
+    /// `inventory` is left unchanged.
+    pub fn apply(&self, inventory: &mut Inventory) -> Result<InventoryEdit, String> {
+        let mut edited = inventory.clone();
+        let inverse = self.apply_in_place(&mut edited)?;
+        *inventory = edited;
+        Ok(inverse)
+    }
+
+    fn apply_in_place(&self, inventory: &mut Inventory) -> Result<InventoryEdit, String> {
+        match self {
+            InventoryEdit::AddItem(item) => {
+                if inventory.has_item_id(item.id) {
+                    return Err(format!("Item with id {} already exists", item.id));
+                }
+                inventory.items.push(item.clone());
+                Ok(InventoryEdit::DeleteItem(item.id))
+            }
+            InventoryEdit::DeleteItem(item_id) => {
+                let index = find_item(inventory, *item_id)?;
+                let item = inventory.items.remove(index);
+                Ok(InventoryEdit::RestoreItem { index, item })
+            }
+            InventoryEdit::RestoreItem { index, item } => {
+                if inventory.has_item_id(item.id) {
+                    return Err(format!("Item with id {} already exists", item.id));
+                }
+                let index = (*index).min(inventory.items.len());
+                inventory.items.insert(index, item.clone());
+                Ok(InventoryEdit::DeleteItem(item.id))
+            }
+            InventoryEdit::SetAttribute {
+                item_id,
+                attribute,
+                value,
+            } => {
+                let index = find_item(inventory, *item_id)?;
+                let attributes = &mut inventory.items[index].attributes;
+                let old = match value {
+                    Some(value) => attributes.insert(*attribute, value.clone()),
+                    None => attributes.remove(attribute),
+                };
+                Ok(InventoryEdit::SetAttribute {
+                    item_id: *item_id,
+                    attribute: *attribute,
+                    value: old,
+                })
+            }
+            InventoryEdit::SetRarity { item_id, rarity } => {
+                let index = find_item(inventory, *item_id)?;
+                let old = std::mem::replace(&mut inventory.items[index].rarity, *rarity);
+                Ok(InventoryEdit::SetRarity {
+                    item_id: *item_id,
+                    rarity: old,
+                })
+            }
+            InventoryEdit::ReplaceItem(item) => {
+                let index = find_item(inventory, item.id)?;
+                let old = std::mem::replace(&mut inventory.items[index], item.clone());
+                Ok(InventoryEdit::ReplaceItem(old))
+            }
+            InventoryEdit::SetDefaultEquip { def_index, equip } => {
+                let old = match equip {
+                    Some(equip) => inventory.default_equips.insert(*def_index, equip.clone()),
+                    None => inventory.default_equips.remove(def_index),
+                };
+                Ok(InventoryEdit::SetDefaultEquip {
+                    def_index: *def_index,
+                    equip: old,
+                })
+            }
+            InventoryEdit::SetItems { description, items } => {
+                let old = std::mem::replace(&mut inventory.items, items.clone());
+                Ok(InventoryEdit::SetItems {
+                    description: description.clone(),
+                    items: old,
+                })
+            }
+            InventoryEdit::Batch { description, edits } => {
+                let mut inverses = Vec::with_capacity(edits.len());
+                for edit in edits {
+                    inverses.push(edit.apply_in_place(inventory)?);
+                }
+                inverses.reverse();
+                Ok(InventoryEdit::Batch {
+                    description: description.clone(),
+                    edits: inverses,
+                })
+            }
+        }
+    }
+}
+
+fn find_item(inventory: &Inventory, item_id: u64) -> Result<usize, String> {
+    inventory
+        .items
+        .iter()
+        .position(|item| item.id == item_id)
+        .ok_or_else(|| format!("Item with id {} not found", item_id))
+}
+
+/// What an applied, undone or redone edit was about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedEdit {
+    pub description: String,
+    pub item_ids: Vec<u64>,
+}
+
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    description: String,
+    /// The edit that moves the inventory to the other side of this entry.
+    edit: InventoryEdit,
+}
+
+/// Unlimited undo and redo stacks of [`InventoryEdit`]s.
+#[derive(Debug, Clone, Default)]
+pub struct EditHistory {
+    undo: Vec<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+}
+
+impl EditHistory {
+    /// Applies `edit` and makes it the next one to undo. Anything that could
+    /// be redone is dropped.
+    pub fn apply(
+        &mut self,
+        inventory: &mut Inventory,
+        edit: InventoryEdit,
+    ) -> Result<AppliedEdit, String> {
+        let inverse = edit.apply(inventory)?;
+        let applied = AppliedEdit {
+            description: edit.description(),
+            item_ids: edit.item_ids(),
+        };
+        self.undo.push(HistoryEntry {
+            description: applied.description.clone(),
+            edit: inverse,
+        });
+        self.redo.clear();
+        Ok(applied)
+    }
+
+    /// Reverses the last edit; `None` if there is nothing to undo.
+    pub fn undo(&mut self, inventory: &mut Inventory) -> Result<Option<AppliedEdit>, String> {
+        Self::step(&mut self.undo, &mut self.redo, inventory)
+    }
+
+    /// Applies the last undone edit again; `None` if there is nothing to redo.
+    pub fn redo(&mut self, inventory: &mut Inventory) -> Result<Option<AppliedEdit>, String> {
+        Self::step(&mut self.redo, &mut self.undo, inventory)
+    }
+
+    fn step(
+        from: &mut Vec<HistoryEntry>,
+        to: &mut Vec<HistoryEntry>,
+        inventory: &mut Inventory,
+    ) -> Result<Option<AppliedEdit>, String> {
+        let Some(entry) = from.last() else {
+            return Ok(None);
+        };
+        let reverse = entry.edit.apply(inventory)?;
+        let entry = from.pop().expect("entry was just read");
+        let applied = AppliedEdit {
+            description: entry.description.clone(),
+            item_ids: entry.edit.item_ids(),
+        };
+        to.push(HistoryEntry {
+            description: entry.description,
+            edit: reverse,
+        });
+        Ok(Some(applied))
+    }
+
+    pub fn undo_description(&self) -> Option<&str> {
+        self.undo.last().map(|entry| entry.description.as_str())
+    }
+
+    pub fn redo_description(&self) -> Option<&str> {
+        self.redo.last().map(|entry| entry.description.as_str())
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EditHistory, InventoryEdit};
+    use crate::inventory::item_attribute::AttributeValue;
+    use crate::inventory::models::Inventory;
+    use crate::inventory::test_fixtures::{self, item};
+
+    /// A knife, a skin and a sticker.
+    fn inventory() -> Inventory {
+        test_fixtures::inventory(&[
+            item(1, 507, 6, &[(6, "44"), (7, "651"), (8, "0.0712")]),
+            item(2, 7, 3, &[(6, "180"), (7, "12"), (8, "0.25")]),
+            item(3, 1209, 1, &[(113, "76")]),
+        ])
+    }
+
+    #[test]
+    fn updating_rarity_and_attributes_makes_one_edit_each() {
+        let inventory = inventory();
+        let old = &inventory.items[1];
+        let mut new = old.clone();
+        new.rarity = 4;
+        new.attributes.insert(8, AttributeValue::float(0.01));
+        new.attributes.remove(&7);
+
+        let Some(InventoryEdit::Batch { edits, .. }) = InventoryEdit::update_item(old, &new) else {
+            panic!("expected a batch");
+        };
+        assert_eq!(
+            edits,
+            [
+                InventoryEdit::SetRarity {
+                    item_id: 2,
+                    rarity: 4
+                },
+                InventoryEdit::SetAttribute {
+                    item_id: 2,
+                    attribute: 7,
+                    value: None
+                },
+                InventoryEdit::SetAttribute {
+                    item_id: 2,
+                    attribute: 8,
+                    value: Some(AttributeValue::float(0.01))
+                },
+            ]
+        );
+
+        new.level = 2;
+        assert!(matches!(
+            InventoryEdit::update_item(old, &new),
+            Some(InventoryEdit::ReplaceItem(_))
+        ));
+        assert!(InventoryEdit::update_item(old, old).is_none());
+    }
+
+    #[test]
+    fn undo_and_redo_step_through_every_edit() {
+        let mut inventory = inventory();
+        let original = inventory.clone();
+        let mut history = EditHistory::default();
+
+        history
+            .apply(&mut inventory, InventoryEdit::DeleteItem(2))
+            .unwrap();
+        history
+            .apply(
+                &mut inventory,
+                InventoryEdit::SetAttribute {
+                    item_id: 1,
+                    attribute: 8,
+                    value: Some(AttributeValue::float(0.5)),
+                },
+            )
+            .unwrap();
+        let mut added = original.items[2].clone();
+        added.id = 4;
+        history
+            .apply(
+                &mut inventory,
+                InventoryEdit::Batch {
+                    description: "Add stickers".to_string(),
+                    edits: vec![InventoryEdit::AddItem(added)],
+                },
+            )
+            .unwrap();
+        let after = inventory.clone();
+        assert_eq!(history.undo_description(), Some("Add stickers"));
+
+        for _ in 0..3 {
+            history.undo(&mut inventory).unwrap().unwrap();
+        }
+        // The deleted item is back where it was, with its text untouched.
+        assert_eq!(inventory, original);
+        assert_eq!(inventory.items[1].attributes[&8].as_str(), "0.25");
+        assert!(history.undo(&mut inventory).unwrap().is_none());
+        assert_eq!(history.redo_description(), Some("Delete item 2"));
+
+        for _ in 0..3 {
+            history.redo(&mut inventory).unwrap().unwrap();
+        }
+        assert_eq!(inventory, after);
+    }
+
+    #[test]
+    fn a_failed_edit_changes_nothing() {
+        let mut inventory = inventory();
+        let original = inventory.clone();
+        let mut history = EditHistory::default();
+
+        let duplicate = InventoryEdit::Batch {
+            description: "Add items".to_string(),
+            edits: vec![
+                InventoryEdit::DeleteItem(3),
+                InventoryEdit::AddItem(original.items[0].clone()),
+            ],
+        };
+        assert!(history.apply(&mut inventory, duplicate).is_err());
+        assert_eq!(inventory, original);
+        assert!(history.undo_description().is_none());
+    }
+
+    #[test]
+    fn a_new_edit_drops_what_could_be_redone() {
+        let mut inventory = inventory();
+        let mut history = EditHistory::default();
+
+        history
+            .apply(&mut inventory, InventoryEdit::DeleteItem(3))
+            .unwrap();
+        history.undo(&mut inventory).unwrap();
+        assert!(history.redo_description().is_some());
+
+        history
+            .apply(
+                &mut inventory,
+                InventoryEdit::SetRarity {
+                    item_id: 1,
+                    rarity: 5,
+                },
+            )
+            .unwrap();
+        assert!(history.redo_description().is_none());
+        assert_eq!(inventory.items.len(), 3);
+    }
+}
@@ -38,6 +38,251 @@ impl ItemAttribute {
     pub fn id(&self) -> u32 {
         *self as u32
     }
+
+    pub fn from_id(id: u32) -> Option<Self> {
+        ALL_ATTRIBUTES.iter().copied().find(|a| a.id() == id)
+    }
+
+    /// The type the game stores this attribute as.
+    pub fn value_type(&self) -> AttributeType {
+        match self {
+            ItemAttribute::SkinPaintIndex
+            | ItemAttribute::SkinPaintSeed
+            | ItemAttribute::SkinPaintWear
+            | ItemAttribute::Sticker0Wear
+            | ItemAttribute::Sticker0Scale
+            | ItemAttribute::Sticker0Rotation
+            | ItemAttribute::Sticker1Wear
+            | ItemAttribute::Sticker1Scale
+            | ItemAttribute::Sticker1Rotation
+            | ItemAttribute::Sticker2Wear
+            | ItemAttribute::Sticker2Scale
+            | ItemAttribute::Sticker2Rotation
+            | ItemAttribute::Sticker3Wear
+            | ItemAttribute::Sticker3Scale
+            | ItemAttribute::Sticker3Rotation
+            | ItemAttribute::Sticker4Wear
+            | ItemAttribute::Sticker4Scale
+            | ItemAttribute::Sticker4Rotation
+            | ItemAttribute::Sticker5Wear
+            | ItemAttribute::Sticker5Scale
+            | ItemAttribute::Sticker5Rotation => AttributeType::Float,
+            _ => AttributeType::Uint32,
+        }
+    }
+}
+
+const ALL_ATTRIBUTES: [ItemAttribute; 32] = [
+    ItemAttribute::SkinPaintIndex,
+    ItemAttribute::SkinPaintSeed,
+    ItemAttribute::SkinPaintWear,
+    ItemAttribute::StatTrakCount,
+    ItemAttribute::StatTrakType,
+    ItemAttribute::Sticker0ID,
+    ItemAttribute::Sticker0Wear,
+    ItemAttribute::Sticker0Scale,
+    ItemAttribute::Sticker0Rotation,
+    ItemAttribute::Sticker1ID,
+    ItemAttribute::Sticker1Wear,
+    ItemAttribute::Sticker1Scale,
+    ItemAttribute::Sticker1Rotation,
+    ItemAttribute::Sticker2ID,
+    ItemAttribute::Sticker2Wear,
+    ItemAttribute::Sticker2Scale,
+    ItemAttribute::Sticker2Rotation,
+    ItemAttribute::Sticker3ID,
+    ItemAttribute::Sticker3Wear,
+    ItemAttribute::Sticker3Scale,
+    ItemAttribute::Sticker3Rotation,
+    ItemAttribute::Sticker4ID,
+    ItemAttribute::Sticker4Wear,
+    ItemAttribute::Sticker4Scale,
+    ItemAttribute::Sticker4Rotation,
+    ItemAttribute::Sticker5ID,
+    ItemAttribute::Sticker5Wear,
+    ItemAttribute::Sticker5Scale,
+    ItemAttribute::Sticker5Rotation,
+    ItemAttribute::MusicID,
+    ItemAttribute::SprayRemain,
+    ItemAttribute::SprayColor,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttributeType {
+    Uint32,
+    Float,
+    String,
+}
+
+impl std::fmt::Display for AttributeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttributeType::Uint32 => write!(f, "an unsigned integer"),
+            AttributeType::Float => write!(f, "a number"),
+            AttributeType::String => write!(f, "a string"),
+        }
+    }
+}
+
+/// The type of attribute `attr_id`. Attributes the editor does not know are
+/// kept as strings.
+pub fn attribute_value_type(attr_id: u32) -> AttributeType {
+    ItemAttribute::from_id(attr_id)
+        .map(|a| a.value_type())
+        .unwrap_or(AttributeType::String)
+}
+
+/// An attribute value checked against its type. The text is kept exactly as
+/// read, so a value that is not edited is written back unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AttributeValue {
+    value_type: AttributeType,
+    text: String,
+}
+
+impl AttributeValue {
+    pub fn parse(value_type: AttributeType, text: &str) -> Result<Self, AttributeValueError> {
+        let valid = match value_type {
+            AttributeType::Uint32 => parse_u32(text).is_some(),
+            AttributeType::Float => text.parse::<f32>().is_ok_and(f32::is_finite),
+            AttributeType::String => true,
+        };
+        if !valid {
+            return Err(AttributeValueError {
+                value: text.to_string(),
+                expected: value_type,
+            });
+        }
+        Ok(Self {
+            value_type,
+            text: text.to_string(),
+        })
+    }
+
+    /// Parses `text` as the type of attribute `attr_id`.
+    pub fn for_attribute(attr_id: u32, text: &str) -> Result<Self, AttributeValueError> {
+        Self::parse(attribute_value_type(attr_id), text)
+    }
+
+    pub fn uint32(value: u32) -> Self {
+        Self {
+            value_type: AttributeType::Uint32,
+            text: value.to_string(),
+        }
+    }
+
+    pub fn float(value: f32) -> Self {
+        Self {
+            value_type: AttributeType::Float,
+            text: value.to_string(),
+        }
+    }
+
+    pub fn string(value: impl Into<String>) -> Self {
+        Self {
+            value_type: AttributeType::String,
+            text: value.into(),
+        }
+    }
+
+    pub fn value_type(&self) -> AttributeType {
+        self.value_type
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// The value as an unsigned integer. Floats qualify when they are whole
+    /// and in range, since the game stores indices such as the paint kit as
+    /// floats.
+    pub fn as_u32(&self) -> Option<u32> {
+        match self.value_type {
+            AttributeType::String => None,
+            _ => parse_u32(&self.text),
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        match self.value_type {
+            AttributeType::String => None,
+            _ => self.text.parse().ok(),
+        }
+    }
+}
+
+impl std::fmt::Display for AttributeValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+fn parse_u32(text: &str) -> Option<u32> {
+    if let Ok(parsed) = text.parse::<u32>() {
+        return Some(parsed);
+    }
+
+    let parsed = text.parse::<f64>().ok()?;
+    if parsed < 0.0 || parsed.fract() != 0.0 || parsed > u32::MAX as f64 {
+        return None;
+    }
+    Some(parsed as u32)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeValueError {
+    pub value: String,
+    pub expected: AttributeType,
+}
+
+impl std::fmt::Display for AttributeValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' is not {}", self.value, self.expected)
+    }
+}
+
+impl std::error::Error for AttributeValueError {}
+
+/// The warning for an attribute whose text does not fit its type. The value
+/// is kept as text and written back as it was read.
+pub fn attribute_kept_as_text(item_id: u64, attr_id: u32, error: &AttributeValueError) -> String {
+    format!(
+        "Item {} attribute {}: {}; kept as text",
+        item_id, attr_id, error
+    )
+}
+
+/// Serde adapter for `Item::attributes`: values are written as their text
+/// and typed by attribute id when read back; text that does not fit the type
+/// is kept as a string, as when reading inventory.txt.
+pub(crate) mod attribute_map {
+    use super::AttributeValue;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S: Serializer>(
+        attributes: &HashMap<u32, AttributeValue>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        attributes
+            .iter()
+            .map(|(id, value)| (*id, value.as_str()))
+            .collect::<HashMap<u32, &str>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<u32, AttributeValue>, D::Error> {
+        Ok(HashMap::<u32, String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(id, text)| {
+                let value = AttributeValue::for_attribute(id, &text)
+                    .unwrap_or_else(|_| AttributeValue::string(text));
+                (id, value)
+            })
+            .collect())
+    }
 }
 
 pub static AVAILABLE_ATTRIBUTES: [u32; 32] = [
@@ -81,19 +326,17 @@ pub fn get_attribute_fluent_key(attr_id: u32) -> String {
 
 pub fn get_attribute_value_display_name(
     attr_id: u32,
-    value: &str,
+    value: &AttributeValue,
     items_game: &crate::inventory::ItemsGame,
     translations: &crate::inventory::GameTranslation,
 ) -> String {
     match attr_id {
         id if id == ItemAttribute::SkinPaintIndex.id() => {
-            if let Ok(paint_id_f32) = value.parse::<f32>() {
-                let paint_id = paint_id_f32 as u32;
-                if let Some(paint_name) =
+            if let Some(paint_id) = value.as_u32()
+                && let Some(paint_name) =
                     items_game.get_paint_kit_display_name(paint_id, translations)
-                {
-                    return format!("{} ({})", paint_name, paint_id);
-                }
+            {
+                return format!("{} ({})", paint_name, paint_id);
             }
             value.to_string()
         }
@@ -104,7 +347,7 @@ pub fn get_attribute_value_display_name(
             || id == ItemAttribute::Sticker4ID.id()
             || id == ItemAttribute::Sticker5ID.id() =>
         {
-            if let Ok(sticker_id) = value.parse::<u32>()
+            if let Some(sticker_id) = value.as_u32()
                 && let Some(sticker_name) =
                     items_game.get_sticker_kit_display_name(sticker_id, translations)
             {
@@ -113,7 +356,7 @@ pub fn get_attribute_value_display_name(
             value.to_string()
         }
         id if id == ItemAttribute::MusicID.id() => {
-            if let Ok(music_id) = value.parse::<u32>()
+            if let Some(music_id) = value.as_u32()
                 && let Some(music_name) =
                     items_game.get_music_def_display_name(music_id, translations)
             {
@@ -122,7 +365,7 @@ pub fn get_attribute_value_display_name(
             value.to_string()
         }
         id if id == ItemAttribute::SprayColor.id() => {
-            if let Ok(tint_id) = value.parse::<u32>() {
+            if let Some(tint_id) = value.as_u32() {
                 for tint in items_game.graffiti_tints.values() {
                     if tint.id == tint_id {
                         return tint.name.clone();
@@ -131,7 +374,11 @@ pub fn get_attribute_value_display_name(
             }
             value.to_string()
         }
-        _ => value.to_string(),
+        _ => items_game
+            .attributes
+            .get(&attr_id)
+            .map(|attribute| attribute.format_value(value))
+            .unwrap_or_else(|| value.to_string()),
     }
 }
 
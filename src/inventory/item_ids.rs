@@ -0,0 +1,319 @@
+//! Item id allocation.
+//!
+//! csgo_gc hands out ids of its own when cases are opened or items are
+//! crafted, so ids the editor picks must not only be free now but also stay
+//! out of csgo_gc's way later. [`IdStrategy`] picks where new ids come from;
+//! [`Inventory::renumber_items`] tidies up ids that ended up scattered.
+
+use crate::inventory::item_attribute::AttributeValue;
+use crate::inventory::models::Inventory;
+use crate::inventory::vdf::VdfValue;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// The first id of the range [`IdStrategy::ReservedRange`] allocates from,
+/// far above the ids the game and csgo_gc have ever used.
+pub const RESERVED_ID_START: u64 = 1 << 40;
+
+/// Attributes of an item stored in a storage unit, holding the low and high
+/// 32 bits of the storage unit's id.
+const CASKET_ITEM_ID_LOW: u32 = 272;
+const CASKET_ITEM_ID_HIGH: u32 = 273;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum IdStrategy {
+    /// One past the highest id in use.
+    #[default]
+    AfterHighest,
+    /// The lowest ids not in use.
+    FillGaps,
+    /// One past the highest id at or above [`RESERVED_ID_START`], so that
+    /// csgo_gc allocating after the highest id can collide with the editor
+    /// only once the editor has used the range.
+    ReservedRange,
+}
+
+impl IdStrategy {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            IdStrategy::AfterHighest => "After highest id",
+            IdStrategy::FillGaps => "Fill gaps",
+            IdStrategy::ReservedRange => "Reserved range",
+        }
+    }
+
+    pub fn all() -> &'static [IdStrategy] {
+        &[
+            IdStrategy::AfterHighest,
+            IdStrategy::FillGaps,
+            IdStrategy::ReservedRange,
+        ]
+    }
+
+    /// The id [`Inventory::renumber_items`] starts from.
+    pub fn first_id(&self) -> u64 {
+        match self {
+            IdStrategy::AfterHighest | IdStrategy::FillGaps => 1,
+            IdStrategy::ReservedRange => RESERVED_ID_START,
+        }
+    }
+}
+
+impl Inventory {
+    pub fn has_item_id(&self, item_id: u64) -> bool {
+        self.items.iter().any(|item| item.id == item_id)
+            || self.unreadable_item_ids().any(|id| id == item_id)
+    }
+
+    /// Ids of items a lenient load could not read. They are still in the
+    /// file, so new items must not take them.
+    fn unreadable_item_ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.unreadable
+            .get("items")
+            .and_then(VdfValue::as_object)
+            .into_iter()
+            .flat_map(|items| items.keys())
+            .filter_map(|key| key.parse().ok())
+    }
+
+    /// Ids used by more than one item, in ascending order.
+    pub fn duplicate_item_ids(&self) -> Vec<u64> {
+        let mut seen = HashSet::new();
+        let mut duplicates: Vec<u64> = self
+            .items
+            .iter()
+            .map(|item| item.id)
+            .filter(|item_id| !seen.insert(*item_id))
+            .collect();
+        duplicates.sort_unstable();
+        duplicates.dedup();
+        duplicates
+    }
+
+    /// `count` unused ids picked by `strategy`, in ascending order.
+    pub fn allocate_item_ids(&self, strategy: IdStrategy, count: usize) -> Vec<u64> {
+        let used: HashSet<u64> = self
+            .items
+            .iter()
+            .map(|item| item.id)
+            .chain(self.unreadable_item_ids())
+            .collect();
+        let first = match strategy {
+            IdStrategy::AfterHighest => used.iter().max().map_or(1, |max| max + 1),
+            IdStrategy::FillGaps => 1,
+            IdStrategy::ReservedRange => used
+                .iter()
+                .filter(|item_id| **item_id >= RESERVED_ID_START)
+                .max()
+                .map_or(RESERVED_ID_START, |max| max + 1),
+        };
+        (first..)
+            .filter(|item_id| !used.contains(item_id))
+            .take(count)
+            .collect()
+    }
+
+    pub fn allocate_item_id(&self, strategy: IdStrategy) -> u64 {
+        self.allocate_item_ids(strategy, 1)[0]
+    }
+
+    /// The inventory position after the last item's.
+    pub fn next_inventory_position(&self) -> u64 {
+        self.items
+            .iter()
+            .map(|item| item.inventory)
+            .max()
+            .unwrap_or(0)
+            + 1
+    }
+
+    /// Gives the items consecutive ids from `first_id` on, in their current
+    /// order, and returns the new id of each old one. Ids of items a lenient
+    /// load could not read are skipped, as those items keep theirs.
+    ///
+    /// References to renumbered items are rewritten: items stored in a
+    /// storage unit are pointed at its new id. Loadouts stay as they were,
+    /// as `equipped_state` is stored on the equipped item itself and moves
+    /// with it, and `default_equips` is keyed by item definition, not by id.
+    pub fn renumber_items(&mut self, first_id: u64) -> HashMap<u64, u64> {
+        let kept: HashSet<u64> = self.unreadable_item_ids().collect();
+        let free_ids = (first_id..).filter(|item_id| !kept.contains(item_id));
+        let mut new_ids = HashMap::new();
+        for (item, new_id) in self.items.iter_mut().zip(free_ids) {
+            // With duplicate ids, references go to the first item.
+            new_ids.entry(item.id).or_insert(new_id);
+            item.id = new_id;
+        }
+
+        for item in &mut self.items {
+            let (Some(low), Some(high)) = (
+                item.attributes.get(&CASKET_ITEM_ID_LOW),
+                item.attributes.get(&CASKET_ITEM_ID_HIGH),
+            ) else {
+                continue;
+            };
+            // Read from the text, as the attributes load untyped unless
+            // items_game gives them a type.
+            let (Ok(low_id), Ok(high_id)) =
+                (low.as_str().parse::<u32>(), high.as_str().parse::<u32>())
+            else {
+                continue;
+            };
+            let casket_id = (u64::from(high_id) << 32) | u64::from(low_id);
+            if let Some(&new_id) = new_ids.get(&casket_id) {
+                let low = casket_id_part(low, new_id as u32);
+                let high = casket_id_part(high, (new_id >> 32) as u32);
+                item.attributes.insert(CASKET_ITEM_ID_LOW, low);
+                item.attributes.insert(CASKET_ITEM_ID_HIGH, high);
+            }
+        }
+
+        new_ids
+    }
+}
+
+/// `part` of a storage unit id, typed like the value it replaces.
+fn casket_id_part(old: &AttributeValue, part: u32) -> AttributeValue {
+    AttributeValue::parse(old.value_type(), &part.to_string())
+        .unwrap_or_else(|_| AttributeValue::uint32(part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IdStrategy, RESERVED_ID_START};
+    use crate::inventory::loader::InventoryLoader;
+    use crate::inventory::models::Inventory;
+    use crate::inventory::parser::{InventoryParser, VdfInventoryParser};
+    use crate::inventory::test_fixtures::{self, inventory_text, item};
+
+    /// A sticker under each of `ids`.
+    fn stickers(ids: &[u64]) -> Vec<String> {
+        ids.iter()
+            .map(|id| item(*id, 1209, 1, &[(113, "76")]))
+            .collect()
+    }
+
+    fn inventory(ids: &[u64]) -> Inventory {
+        test_fixtures::inventory(&stickers(ids))
+    }
+
+    fn ids(inventory: &Inventory) -> Vec<u64> {
+        inventory.items.iter().map(|item| item.id).collect()
+    }
+
+    #[test]
+    fn after_highest_allocates_past_every_id() {
+        let inventory = inventory(&[3, 7, RESERVED_ID_START + 1]);
+        assert_eq!(
+            inventory.allocate_item_ids(IdStrategy::AfterHighest, 2),
+            [RESERVED_ID_START + 2, RESERVED_ID_START + 3]
+        );
+        assert_eq!(
+            Inventory::default().allocate_item_id(IdStrategy::AfterHighest),
+            1
+        );
+    }
+
+    #[test]
+    fn fill_gaps_takes_the_lowest_free_ids() {
+        let inventory = inventory(&[3, 7, RESERVED_ID_START + 1]);
+        assert_eq!(
+            inventory.allocate_item_ids(IdStrategy::FillGaps, 3),
+            [1, 2, 4]
+        );
+    }
+
+    #[test]
+    fn reserved_range_stays_above_the_ids_csgo_gc_uses() {
+        assert_eq!(
+            inventory(&[3, 7]).allocate_item_id(IdStrategy::ReservedRange),
+            RESERVED_ID_START
+        );
+        assert_eq!(
+            inventory(&[3, RESERVED_ID_START + 1]).allocate_item_id(IdStrategy::ReservedRange),
+            RESERVED_ID_START + 2
+        );
+    }
+
+    #[test]
+    fn renumbering_gives_duplicates_ids_of_their_own() {
+        // InventoryLoader rejects duplicate ids, so parse the text directly.
+        let mut inventory = VdfInventoryParser
+            .parse(&inventory_text(&stickers(&[3, 7, 3])))
+            .unwrap();
+        assert_eq!(inventory.duplicate_item_ids(), [3]);
+
+        let new_ids = inventory.renumber_items(1);
+        assert_eq!(ids(&inventory), [1, 2, 3]);
+        assert!(inventory.duplicate_item_ids().is_empty());
+        assert_eq!(new_ids[&3], 1);
+    }
+
+    #[test]
+    fn renumbering_points_stored_items_at_the_new_storage_unit_id() {
+        // A storage unit (1201) with id 2^32 + 7 holding a sticker, and an
+        // equipped knife.
+        let mut inventory = InventoryLoader::parse_from_str(
+            r#"
+            "items"
+            {
+                "4294967303"
+                {
+                    "inventory" "1" "def_index" "1201" "level" "1" "quality" "4"
+                    "flags" "0" "origin" "0" "in_use" "0" "rarity" "1"
+                }
+                "9"
+                {
+                    "inventory" "2" "def_index" "1209" "level" "1" "quality" "4"
+                    "flags" "0" "origin" "0" "in_use" "0" "rarity" "1"
+                    "attributes" { "113" "76" "272" "7" "273" "1" }
+                }
+                "12"
+                {
+                    "inventory" "3" "def_index" "507" "level" "1" "quality" "3"
+                    "flags" "0" "origin" "0" "in_use" "0" "rarity" "6"
+                    "equipped_state" { "3" "0" }
+                }
+            }
+            "default_equips"
+            {
+                "507" { "class_id" "3" "slot_id" "0" }
+            }
+            "#,
+        )
+        .unwrap();
+        let loadout = inventory.default_equips.clone();
+
+        let new_ids = inventory.renumber_items(RESERVED_ID_START);
+        assert_eq!(new_ids[&((1 << 32) + 7)], RESERVED_ID_START);
+
+        let stored = &inventory.items[1].attributes;
+        assert_eq!(stored[&272].as_str(), "0");
+        assert_eq!(stored[&273].as_str(), "256");
+        let knife = &inventory.items[2];
+        assert_eq!(knife.id, RESERVED_ID_START + 2);
+        assert_eq!(knife.equipped_state[&3], "0");
+        assert_eq!(inventory.default_equips, loadout);
+    }
+
+    #[test]
+    fn renumbering_skips_ids_of_unreadable_items() {
+        let (mut inventory, warnings) = VdfInventoryParser.parse_lenient(
+            r#"
+            "items"
+            {
+                "1" { "inventory" "x" }
+                "5"
+                {
+                    "inventory" "1" "def_index" "7" "level" "1" "quality" "4"
+                    "flags" "0" "origin" "0" "in_use" "0" "rarity" "1"
+                }
+            }
+            "#,
+        );
+        assert_eq!(warnings.len(), 1);
+
+        inventory.renumber_items(1);
+        assert_eq!(inventory.items[0].id, 2);
+    }
+}
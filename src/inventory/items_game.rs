@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
-use crate::inventory::item_attribute::ItemAttribute;
+use crate::inventory::item_attribute::{
+    AttributeType, AttributeValue, ItemAttribute, attribute_kept_as_text,
+    get_attribute_default_value,
+};
+use crate::inventory::models::Inventory;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct IGItem {
@@ -12,6 +16,17 @@ pub struct IGItem {
     pub item_type_name: Option<String>,
     pub inv_container_and_tools: Option<String>,
     pub associated_items: Vec<u32>,
+    /// The loadout slot group, e.g. `rifle` or `melee`.
+    #[serde(default)]
+    pub item_slot: Option<String>,
+    /// Class ids of the teams that can equip the item; empty if the schema
+    /// does not say.
+    #[serde(default)]
+    pub used_by_classes: Vec<u32>,
+    /// The `set supply crate series` attribute of cases, naming their entry
+    /// in `revolving_loot_lists`.
+    #[serde(default)]
+    pub supply_crate_series: Option<u32>,
 }
 
 impl IGItem {
@@ -32,11 +47,22 @@ impl IGItem {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct IGPaintKit {
     pub name: String,
     pub description_string: String,
     pub description_tag: String,
+    /// The wear range skins with this paint kit are created in. Paint kits
+    /// without one use the range of the `default` paint kit.
+    #[serde(default)]
+    pub wear_remap_min: Option<f32>,
+    #[serde(default)]
+    pub wear_remap_max: Option<f32>,
+    /// How the paint is applied: 1 solid color, 2 hydrographic, 3 spray
+    /// paint, 4 anodized, 5 anodized multicolored, 6 anodized airbrushed,
+    /// 7 custom paint job, 8 patina, 9 gunsmith.
+    #[serde(default)]
+    pub style: Option<u32>,
 }
 
 impl IGPaintKit {
@@ -46,6 +72,76 @@ impl IGPaintKit {
             .unwrap_or(&self.description_string)
             .clone()
     }
+
+    /// Whether `SkinPaintSeed` changes how the skin looks. Solid colors and
+    /// plain anodized paints have no pattern for the seed to place.
+    pub fn seed_affects_pattern(&self) -> bool {
+        !matches!(self.style, Some(1) | Some(4))
+    }
+}
+
+/// The highest `SkinPaintSeed` the game gives out.
+pub const MAX_PAINT_SEED: u32 = 1000;
+
+/// The exterior a skin's wear falls into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WearTier {
+    FactoryNew,
+    MinimalWear,
+    FieldTested,
+    WellWorn,
+    BattleScarred,
+}
+
+impl WearTier {
+    pub fn all() -> &'static [WearTier] {
+        &[
+            WearTier::FactoryNew,
+            WearTier::MinimalWear,
+            WearTier::FieldTested,
+            WearTier::WellWorn,
+            WearTier::BattleScarred,
+        ]
+    }
+
+    pub fn from_wear(wear: f32) -> Self {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|tier| wear < tier.max_wear())
+            .unwrap_or(WearTier::BattleScarred)
+    }
+
+    /// The wear the tier goes up to, not included.
+    pub fn max_wear(&self) -> f32 {
+        match self {
+            WearTier::FactoryNew => 0.07,
+            WearTier::MinimalWear => 0.15,
+            WearTier::FieldTested => 0.38,
+            WearTier::WellWorn => 0.45,
+            WearTier::BattleScarred => 1.0,
+        }
+    }
+
+    pub fn loc_key(&self) -> String {
+        format!("SFUI_InvTooltip_Wear_Amount_{}", *self as u32)
+    }
+
+    pub fn get_display_name(&self, translations: &GameTranslation) -> String {
+        translations
+            .get(&self.loc_key())
+            .cloned()
+            .unwrap_or_else(|| {
+                match self {
+                    WearTier::FactoryNew => "Factory New",
+                    WearTier::MinimalWear => "Minimal Wear",
+                    WearTier::FieldTested => "Field-Tested",
+                    WearTier::WellWorn => "Well-Worn",
+                    WearTier::BattleScarred => "Battle-Scarred",
+                }
+                .to_string()
+            })
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -94,6 +190,30 @@ pub struct IGQuality {
     pub value: u32,
 }
 
+/// An entry of `item_sets`: a collection such as "The Dust Collection", or
+/// a set of items that belong together.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IGItemSet {
+    pub name: String,
+    pub is_collection: bool,
+    /// The skins of the set as `(def_index, paint_index)`.
+    pub items: Vec<(u32, u32)>,
+}
+
+impl IGItemSet {
+    pub fn get_display_name(&self, translations: &GameTranslation) -> String {
+        translations.get(&self.name).unwrap_or(&self.name).clone()
+    }
+}
+
+/// A skin a case can drop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IGLootEntry {
+    pub def_index: u32,
+    pub paint_index: u32,
+    pub rarity: u32,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct IGGraffitiTint {
     pub name: String,
@@ -101,6 +221,62 @@ pub struct IGGraffitiTint {
     pub hex_color: String,
 }
 
+/// An entry of the items_game `attributes` section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IGAttribute {
+    pub name: String,
+    pub attribute_class: Option<String>,
+    /// Explicit storage type such as `string` or `uint64`; most attributes
+    /// only set `stored_as_integer`.
+    pub attribute_type: Option<String>,
+    pub stored_as_integer: bool,
+    pub description_string: Option<String>,
+    pub description_format: Option<String>,
+    pub hidden: bool,
+}
+
+impl IGAttribute {
+    pub fn value_type(&self) -> AttributeType {
+        match self.attribute_type.as_deref() {
+            Some("float") => AttributeType::Float,
+            Some("uint32") => AttributeType::Uint32,
+            Some(_) => AttributeType::String,
+            None if self.stored_as_integer => AttributeType::Uint32,
+            None => AttributeType::Float,
+        }
+    }
+
+    pub fn get_display_name(&self, translations: &GameTranslation) -> String {
+        self.description_string
+            .as_deref()
+            .and_then(|key| translations.get(key))
+            .cloned()
+            .unwrap_or_else(|| self.name.clone())
+    }
+
+    /// Formats `value` the way its `description_format` asks for, or returns
+    /// the text unchanged. The text is read as this attribute's type, whatever
+    /// type the value was loaded with.
+    pub fn format_value(&self, value: &AttributeValue) -> String {
+        let Ok(value) = AttributeValue::parse(self.value_type(), value.as_str()) else {
+            return value.to_string();
+        };
+        match (self.description_format.as_deref(), value.as_f32()) {
+            (Some("value_is_percentage"), Some(f)) => format!("{:+.0}%", (f - 1.0) * 100.0),
+            (Some("value_is_inverted_percentage"), Some(f)) => {
+                format!("{:+.0}%", (1.0 - f) * 100.0)
+            }
+            (Some("value_is_additive_percentage"), Some(f)) => format!("{:+.0}%", f * 100.0),
+            (Some("value_is_date"), _) => value
+                .as_u32()
+                .and_then(|t| chrono::DateTime::from_timestamp(t as i64, 0))
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| value.to_string()),
+            _ => value.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct GameTranslation {
     pub map: HashMap<String, String>,
@@ -126,6 +302,14 @@ pub struct ItemsGame {
     pub qualities: HashMap<String, IGQuality>,
     pub graffiti_tints: HashMap<String, IGGraffitiTint>,
     pub paint_kits_rarity: HashMap<String, String>,
+    pub attributes: HashMap<u32, IGAttribute>,
+    /// The skins each case drops, by the case's def_index.
+    pub case_loot: HashMap<u32, Vec<IGLootEntry>>,
+    /// `item_sets` by key, e.g. `set_dust`.
+    pub item_sets: HashMap<String, IGItemSet>,
+    /// The paint kits each weapon exists with, from `alternate_icons2` and
+    /// `item_sets`.
+    pub weapon_paint_kits: HashMap<u32, BTreeSet<u32>>,
 }
 
 impl ItemsGame {
@@ -149,6 +333,62 @@ impl ItemsGame {
         qualities
     }
 
+    /// The type of attribute `attr_id`, from its items_game definition, the
+    /// editor's built-in list, or `String` when neither knows it.
+    pub fn get_attribute_value_type(&self, attr_id: u32) -> AttributeType {
+        match self.attributes.get(&attr_id) {
+            Some(attribute) => attribute.value_type(),
+            None => crate::inventory::attribute_value_type(attr_id),
+        }
+    }
+
+    /// Types the attributes of an inventory read from disk the way edits are
+    /// typed, by [`Self::get_attribute_value_type`]; inventory.txt alone only
+    /// knows the editor's built-in list. Values that do not fit their type
+    /// are kept as text and described in the returned warnings.
+    pub fn apply_attribute_types(&self, inventory: &mut Inventory) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for item in &mut inventory.items {
+            let mut ids: Vec<u32> = item.attributes.keys().copied().collect();
+            ids.sort_unstable();
+            for attr_id in ids {
+                let value_type = self.get_attribute_value_type(attr_id);
+                let value = &item.attributes[&attr_id];
+                if value.value_type() == value_type {
+                    continue;
+                }
+                let typed = AttributeValue::parse(value_type, value.as_str()).unwrap_or_else(|e| {
+                    warnings.push(attribute_kept_as_text(item.id, attr_id, &e));
+                    AttributeValue::string(value.as_str())
+                });
+                item.attributes.insert(attr_id, typed);
+            }
+        }
+        warnings
+    }
+
+    pub fn get_attribute_default_value(&self, attr_id: u32) -> String {
+        if ItemAttribute::from_id(attr_id).is_some() {
+            return get_attribute_default_value(attr_id).to_string();
+        }
+        match self.get_attribute_value_type(attr_id) {
+            AttributeType::Uint32 | AttributeType::Float => "0".to_string(),
+            AttributeType::String => String::new(),
+        }
+    }
+
+    /// Ids of every attribute defined in items_game, or the editor's built-in
+    /// list if items_game defines none, in ascending order.
+    pub fn get_all_attribute_ids(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = if self.attributes.is_empty() {
+            crate::inventory::AVAILABLE_ATTRIBUTES.to_vec()
+        } else {
+            self.attributes.keys().copied().collect()
+        };
+        ids.sort_unstable();
+        ids
+    }
+
     pub fn get_item_display_name(&self, def_index: u32, translations: &GameTranslation) -> String {
         if let Some(item) = self.items.get(&def_index) {
             item.get_display_name(translations)
@@ -167,6 +407,62 @@ impl ItemsGame {
             .map(|pk| pk.get_display_name(translations))
     }
 
+    /// The wear range of a paint kit, falling back to the range of paint kit
+    /// 0 (`default`) and then to the game's own default of 0.06 to 0.8.
+    pub fn get_paint_kit_wear_range(&self, paint_index: u32) -> (f32, f32) {
+        let default = self.paint_kits.get(&0);
+        let min = self
+            .paint_kits
+            .get(&paint_index)
+            .and_then(|pk| pk.wear_remap_min)
+            .or_else(|| default.and_then(|pk| pk.wear_remap_min))
+            .unwrap_or(0.06);
+        let max = self
+            .paint_kits
+            .get(&paint_index)
+            .and_then(|pk| pk.wear_remap_max)
+            .or_else(|| default.and_then(|pk| pk.wear_remap_max))
+            .unwrap_or(0.8);
+        (min, max)
+    }
+
+    /// The tiers a skin with `paint_index` can be found in.
+    pub fn get_paint_kit_wear_tiers(&self, paint_index: u32) -> Vec<WearTier> {
+        let (min, max) = self.get_paint_kit_wear_range(paint_index);
+        let (lowest, highest) = (WearTier::from_wear(min), WearTier::from_wear(max));
+        WearTier::all()
+            .iter()
+            .copied()
+            .filter(|tier| (lowest..=highest).contains(tier))
+            .collect()
+    }
+
+    /// The tier of an item's `SkinPaintWear`, if it has one.
+    pub fn get_item_wear_tier(&self, item: &crate::inventory::models::Item) -> Option<WearTier> {
+        item.attributes
+            .get(&ItemAttribute::SkinPaintWear.id())
+            .and_then(|wear| wear.as_f32())
+            .map(WearTier::from_wear)
+    }
+
+    /// The skins the case `def_index` drops, from most common to rarest;
+    /// empty if it is not a case or its contents are unknown.
+    pub fn case_contents(&self, def_index: u32) -> &[IGLootEntry] {
+        self.case_loot
+            .get(&def_index)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The key of the collection a skin belongs to.
+    pub fn collection_of(&self, def_index: u32, paint_index: u32) -> Option<&str> {
+        self.item_sets
+            .iter()
+            .filter(|(_, set)| set.is_collection && set.items.contains(&(def_index, paint_index)))
+            .map(|(key, _)| key.as_str())
+            .min()
+    }
+
     pub fn get_paint_kit_rarity(&self, paint_index: u32) -> Option<u32> {
         self.paint_kits.get(&paint_index).and_then(|pk| {
             self.paint_kits_rarity
@@ -218,23 +514,25 @@ impl ItemsGame {
         let item_name = self.get_item_display_name(item.def_index, translations);
 
         if let Some(paint_index) = item.attributes.get(&ItemAttribute::SkinPaintIndex.id())
-            && let Ok(paint_id_f32) = paint_index.parse::<f32>()
+            && let Some(paint_id) = paint_index.as_u32()
+            && let Some(paint_name) = self.get_paint_kit_display_name(paint_id, translations)
         {
-            let paint_id = paint_id_f32 as u32;
-            if let Some(paint_name) = self.get_paint_kit_display_name(paint_id, translations) {
-                return format!("{} | {}", item_name, paint_name);
-            }
+            let name = format!("{} | {}", item_name, paint_name);
+            return match self.get_item_wear_tier(item) {
+                Some(tier) => format!("{} ({})", name, tier.get_display_name(translations)),
+                None => name,
+            };
         }
 
         if let Some(music_index) = item.attributes.get(&ItemAttribute::MusicID.id())
-            && let Ok(music_id) = music_index.parse::<u32>()
+            && let Some(music_id) = music_index.as_u32()
             && let Some(music_name) = self.get_music_def_display_name(music_id, translations)
         {
             return format!("{} | {}", item_name, music_name);
         }
 
         if let Some(sticker_index) = item.attributes.get(&ItemAttribute::Sticker0ID.id())
-            && let Ok(sticker_id) = sticker_index.parse::<u32>()
+            && let Some(sticker_id) = sticker_index.as_u32()
             && let Some(sticker_name) = self.get_sticker_kit_display_name(sticker_id, translations)
         {
             return format!("{} | {}", item_name, sticker_name);
@@ -272,6 +570,30 @@ impl ItemsGame {
         build_select_list(&self.paint_kits, translations)
     }
 
+    /// The paint kits `def_index` exists with, or `None` for weapons the
+    /// schema lists no skins for.
+    pub fn get_weapon_paint_kits(&self, def_index: u32) -> Option<&BTreeSet<u32>> {
+        self.weapon_paint_kits.get(&def_index)
+    }
+
+    /// Like [`Self::create_paint_kit_select_list`], but only with the paint
+    /// kits `def_index` exists with, when those are known.
+    pub fn create_paint_kit_select_list_for_weapon(
+        &self,
+        def_index: u32,
+        translations: &GameTranslation,
+    ) -> Vec<(String, String)> {
+        match self.get_weapon_paint_kits(def_index) {
+            Some(paint_indexes) => build_select_list(
+                self.paint_kits
+                    .iter()
+                    .filter(|(paint_index, _)| paint_indexes.contains(paint_index)),
+                translations,
+            ),
+            None => self.create_paint_kit_select_list(translations),
+        }
+    }
+
     pub fn create_music_def_select_list(
         &self,
         translations: &GameTranslation,
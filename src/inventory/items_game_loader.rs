@@ -1,40 +1,116 @@
 use crate::inventory::items_game::{
-    IGGraffitiTint, IGItem, IGMusicDef, IGPaintKit, IGQuality, IGRarity, IGStickerKit, ItemsGame,
+    IGAttribute, IGGraffitiTint, IGItem, IGItemSet, IGLootEntry, IGMusicDef, IGPaintKit, IGQuality,
+    IGRarity, IGStickerKit, ItemsGame,
 };
-use crate::inventory::vdf::{VdfParser, VdfValue, get_string_from_obj};
-use std::collections::HashMap;
+use crate::inventory::vdf::{
+    VdfEvent, VdfFileResolver, VdfObject, VdfParseError, VdfParseOptions, VdfParser, VdfReader,
+    VdfValue, get_string_from_obj,
+};
+use std::collections::{BTreeSet, HashMap};
 use std::path::Path;
 
+/// The `items_game` sections read by [`ItemsGameLoader`].
+const SECTIONS: &[&str] = &[
+    "rarities",
+    "qualities",
+    "prefabs",
+    "items",
+    "music_definitions",
+    "paint_kits",
+    "sticker_kits",
+    "paint_kits_rarity",
+    "graffiti_tints",
+    "attributes",
+    "revolving_loot_lists",
+    "client_loot_lists",
+    "item_sets",
+    "alternate_icons2",
+];
+
 pub struct ItemsGameLoader;
 
 impl ItemsGameLoader {
+    /// Loads an items_game file, resolving `#base`/`#include` directives
+    /// relative to the directory it lives in.
     pub fn load<P: AsRef<Path>>(path: P) -> Result<ItemsGame, ItemsGameLoadError> {
+        let path = path.as_ref();
         let content = std::fs::read_to_string(path).map_err(ItemsGameLoadError::Io)?;
-        Self::parse_from_str(&content)
+
+        if let Some(sections) = Self::extract_sections(&content)? {
+            return Ok(Self::from_sections(&sections));
+        }
+
+        let resolver = VdfFileResolver::new(path.parent().unwrap_or(Path::new("")));
+        let vdf = VdfParser::parse_with(&content, &VdfParseOptions::with_resolver(&resolver))
+            .map_err(|e| ItemsGameLoadError::Parse(e.to_string()))?;
+        Ok(Self::from_vdf(&vdf))
     }
 
     pub fn parse_from_str(content: &str) -> Result<ItemsGame, ItemsGameLoadError> {
+        if let Some(sections) = Self::extract_sections(content)? {
+            return Ok(Self::from_sections(&sections));
+        }
+
         let vdf =
             VdfParser::parse(content).map_err(|e| ItemsGameLoadError::Parse(e.to_string()))?;
+        Ok(Self::from_vdf(&vdf))
+    }
 
-        let mut items_game = ItemsGame::default();
+    /// Streams `content` and builds only the `items_game` sections listed in
+    /// [`SECTIONS`], skipping the rest without allocating. Returns `None` if the
+    /// file uses `#base`/`#include`, which need the full parser.
+    fn extract_sections(content: &str) -> Result<Option<VdfObject>, ItemsGameLoadError> {
+        let parse_error = |e: VdfParseError| ItemsGameLoadError::Parse(e.to_string());
+        let mut reader = VdfReader::new(content);
+        let mut sections = VdfObject::new();
 
-        if let Some(root_obj) = vdf.get("items_game").and_then(|v| v.as_object()) {
-            Self::parse_rarities(root_obj, &mut items_game);
-            Self::parse_qualities(root_obj, &mut items_game);
-            Self::parse_items(root_obj, &mut items_game);
-            Self::parse_music_definitions(root_obj, &mut items_game);
-            Self::parse_paint_kits(root_obj, &mut items_game);
-            Self::parse_sticker_kits(root_obj, &mut items_game);
-            Self::parse_paint_kits_rarity(root_obj, &mut items_game);
-            Self::parse_graffiti_tints(root_obj, &mut items_game);
+        while let Some(event) = reader.next_event().map_err(parse_error)? {
+            match event {
+                VdfEvent::ObjectStart { key } if reader.depth() == 1 && key == "items_game" => {}
+                VdfEvent::ObjectStart { key }
+                    if reader.depth() == 2 && SECTIONS.contains(&key.as_ref()) =>
+                {
+                    let section = reader.read_object().map_err(parse_error)?;
+                    sections.push(key, VdfValue::Object(section));
+                }
+                VdfEvent::ObjectStart { .. } => reader.skip_object().map_err(parse_error)?,
+                VdfEvent::String { key, .. } if reader.depth() == 0 && key.starts_with('#') => {
+                    return Ok(None);
+                }
+                _ => {}
+            }
         }
 
-        Ok(items_game)
+        Ok(Some(sections))
+    }
+
+    /// Reads the sections of every `items_game` block, as
+    /// [`extract_sections`](Self::extract_sections) does.
+    fn from_vdf(vdf: &VdfObject) -> ItemsGame {
+        Self::from_sections(&vdf.merged_object("items_game"))
     }
 
-    fn parse_rarities(root_obj: &HashMap<String, VdfValue>, items_game: &mut ItemsGame) {
-        if let Some(rarities_obj) = root_obj.get("rarities").and_then(|v| v.as_object()) {
+    fn from_sections(root_obj: &VdfObject) -> ItemsGame {
+        let mut items_game = ItemsGame::default();
+
+        Self::parse_rarities(root_obj, &mut items_game);
+        Self::parse_qualities(root_obj, &mut items_game);
+        Self::parse_items(root_obj, &mut items_game);
+        Self::parse_music_definitions(root_obj, &mut items_game);
+        Self::parse_paint_kits(root_obj, &mut items_game);
+        Self::parse_sticker_kits(root_obj, &mut items_game);
+        Self::parse_paint_kits_rarity(root_obj, &mut items_game);
+        Self::parse_graffiti_tints(root_obj, &mut items_game);
+        Self::parse_attributes(root_obj, &mut items_game);
+        Self::parse_case_loot(root_obj, &mut items_game);
+        Self::parse_item_sets(root_obj, &mut items_game);
+        Self::parse_weapon_paint_kits(root_obj, &mut items_game);
+
+        items_game
+    }
+
+    fn parse_rarities(root_obj: &VdfObject, items_game: &mut ItemsGame) {
+        for rarities_obj in root_obj.objects("rarities") {
             for (key, value) in rarities_obj {
                 if let Some(obj) = value.as_object() {
                     let rarity = IGRarity {
@@ -48,8 +124,8 @@ impl ItemsGameLoader {
         }
     }
 
-    fn parse_qualities(root_obj: &HashMap<String, VdfValue>, items_game: &mut ItemsGame) {
-        if let Some(qualities_obj) = root_obj.get("qualities").and_then(|v| v.as_object()) {
+    fn parse_qualities(root_obj: &VdfObject, items_game: &mut ItemsGame) {
+        for qualities_obj in root_obj.objects("qualities") {
             for (key, value) in qualities_obj {
                 if let Some(obj) = value.as_object() {
                     let quality = IGQuality {
@@ -62,10 +138,11 @@ impl ItemsGameLoader {
         }
     }
 
-    fn parse_items(root_obj: &HashMap<String, VdfValue>, items_game: &mut ItemsGame) {
-        let prefabs_obj = root_obj.get("prefabs").and_then(|v| v.as_object());
+    fn parse_items(root_obj: &VdfObject, items_game: &mut ItemsGame) {
+        let prefabs = root_obj.merged_object("prefabs");
+        let prefabs_obj = Some(&prefabs);
 
-        if let Some(items_obj) = root_obj.get("items").and_then(|v| v.as_object()) {
+        for items_obj in root_obj.objects("items") {
             for (key, value) in items_obj {
                 if let Some(obj) = value.as_object() {
                     let name = get_string_from_obj(obj, "name").unwrap_or_else(|| key.clone());
@@ -101,6 +178,20 @@ impl ItemsGameLoader {
                             "inv_container_and_tools",
                         ),
                         associated_items: get_associated_items(obj, prefabs_obj, prefab.as_deref()),
+                        item_slot: get_inherited_string(
+                            obj,
+                            prefabs_obj,
+                            prefab.as_deref(),
+                            "item_slot",
+                        ),
+                        used_by_classes: get_used_by_classes(obj, prefabs_obj, prefab.as_deref()),
+                        supply_crate_series: get_inherited_attribute(
+                            obj,
+                            prefabs_obj,
+                            prefab.as_deref(),
+                            "set supply crate series",
+                        )
+                        .and_then(|series| get_u32_from_obj(series, "value")),
                         prefab,
                     };
 
@@ -116,11 +207,8 @@ impl ItemsGameLoader {
         }
     }
 
-    fn parse_music_definitions(root_obj: &HashMap<String, VdfValue>, items_game: &mut ItemsGame) {
-        if let Some(music_obj) = root_obj
-            .get("music_definitions")
-            .and_then(|v| v.as_object())
-        {
+    fn parse_music_definitions(root_obj: &VdfObject, items_game: &mut ItemsGame) {
+        for music_obj in root_obj.objects("music_definitions") {
             for (key, value) in music_obj {
                 if let Some(obj) = value.as_object() {
                     let loc_name = get_string_from_obj(obj, "loc_name")
@@ -145,8 +233,8 @@ impl ItemsGameLoader {
         }
     }
 
-    fn parse_paint_kits(root_obj: &HashMap<String, VdfValue>, items_game: &mut ItemsGame) {
-        if let Some(paint_kits_obj) = root_obj.get("paint_kits").and_then(|v| v.as_object()) {
+    fn parse_paint_kits(root_obj: &VdfObject, items_game: &mut ItemsGame) {
+        for paint_kits_obj in root_obj.objects("paint_kits") {
             for (key, value) in paint_kits_obj {
                 if let Some(obj) = value.as_object() {
                     let name = get_string_from_obj(obj, "name").unwrap_or_else(|| key.clone());
@@ -167,6 +255,11 @@ impl ItemsGameLoader {
                             .strip_prefix('#')
                             .unwrap_or(&description_tag)
                             .to_string(),
+                        wear_remap_min: get_string_from_obj(obj, "wear_remap_min")
+                            .and_then(|s| s.parse().ok()),
+                        wear_remap_max: get_string_from_obj(obj, "wear_remap_max")
+                            .and_then(|s| s.parse().ok()),
+                        style: get_u32_from_obj(obj, "style"),
                     };
 
                     if let Ok(index) = key.parse::<u32>() {
@@ -177,8 +270,8 @@ impl ItemsGameLoader {
         }
     }
 
-    fn parse_sticker_kits(root_obj: &HashMap<String, VdfValue>, items_game: &mut ItemsGame) {
-        if let Some(sticker_kits_obj) = root_obj.get("sticker_kits").and_then(|v| v.as_object()) {
+    fn parse_sticker_kits(root_obj: &VdfObject, items_game: &mut ItemsGame) {
+        for sticker_kits_obj in root_obj.objects("sticker_kits") {
             for (key, value) in sticker_kits_obj {
                 if let Some(obj) = value.as_object() {
                     let name = get_string_from_obj(obj, "name").unwrap_or_else(|| key.clone());
@@ -210,11 +303,8 @@ impl ItemsGameLoader {
         }
     }
 
-    fn parse_paint_kits_rarity(root_obj: &HashMap<String, VdfValue>, items_game: &mut ItemsGame) {
-        if let Some(rarity_obj) = root_obj
-            .get("paint_kits_rarity")
-            .and_then(|v| v.as_object())
-        {
+    fn parse_paint_kits_rarity(root_obj: &VdfObject, items_game: &mut ItemsGame) {
+        for rarity_obj in root_obj.objects("paint_kits_rarity") {
             for (key, value) in rarity_obj {
                 if let Some(s) = value.as_string() {
                     items_game
@@ -225,8 +315,8 @@ impl ItemsGameLoader {
         }
     }
 
-    fn parse_graffiti_tints(root_obj: &HashMap<String, VdfValue>, items_game: &mut ItemsGame) {
-        if let Some(graffiti_obj) = root_obj.get("graffiti_tints").and_then(|v| v.as_object()) {
+    fn parse_graffiti_tints(root_obj: &VdfObject, items_game: &mut ItemsGame) {
+        for graffiti_obj in root_obj.objects("graffiti_tints") {
             for (key, value) in graffiti_obj {
                 if let Some(obj) = value.as_object() {
                     let tint = IGGraffitiTint {
@@ -239,11 +329,196 @@ impl ItemsGameLoader {
             }
         }
     }
+
+    fn parse_attributes(root_obj: &VdfObject, items_game: &mut ItemsGame) {
+        for attributes_obj in root_obj.objects("attributes") {
+            for (key, value) in attributes_obj {
+                if let Some(obj) = value.as_object()
+                    && let Ok(index) = key.parse::<u32>()
+                {
+                    let attribute = IGAttribute {
+                        name: get_string_from_obj(obj, "name").unwrap_or_else(|| key.clone()),
+                        attribute_class: get_string_from_obj(obj, "attribute_class"),
+                        attribute_type: get_string_from_obj(obj, "attribute_type"),
+                        stored_as_integer: get_u32_from_obj(obj, "stored_as_integer") == Some(1),
+                        description_string: get_string_from_obj(obj, "description_string")
+                            .map(|s| s.strip_prefix('#').unwrap_or(&s).to_string()),
+                        description_format: get_string_from_obj(obj, "description_format"),
+                        hidden: get_u32_from_obj(obj, "hidden") == Some(1),
+                    };
+                    items_game.attributes.insert(index, attribute);
+                }
+            }
+        }
+    }
+
+    /// Resolves each case's `set supply crate series` through
+    /// `revolving_loot_lists` to its `client_loot_lists` entry, whose lists
+    /// nest down to `[paint_kit]weapon` entries. A list's rarity comes from
+    /// its name (`crate_valve_1_rare`); skins in lists without one take the
+    /// rarity of their paint kit.
+    fn parse_case_loot(root_obj: &VdfObject, items_game: &mut ItemsGame) {
+        let series: HashMap<u32, &str> = root_obj
+            .objects("revolving_loot_lists")
+            .flat_map(|lists| lists.iter())
+            .filter_map(|(key, value)| Some((key.parse().ok()?, value.as_string()?)))
+            .collect();
+        let loot_lists: HashMap<&str, &VdfObject> = root_obj
+            .objects("client_loot_lists")
+            .flat_map(|lists| lists.iter())
+            .filter_map(|(key, value)| Some((key.as_str(), value.as_object()?)))
+            .collect();
+        if series.is_empty() || loot_lists.is_empty() {
+            return;
+        }
+
+        let skins = SkinNames::new(items_game);
+        let resolver = LootResolver {
+            items_game,
+            loot_lists: &loot_lists,
+            skins: &skins,
+        };
+
+        let mut case_loot = HashMap::new();
+        for (def_index, item) in &items_game.items {
+            let Some(list) = item
+                .supply_crate_series
+                .and_then(|series_id| series.get(&series_id))
+            else {
+                continue;
+            };
+            let mut entries = Vec::new();
+            resolver.collect(list, None, 0, &mut entries);
+            if !entries.is_empty() {
+                entries.sort_by_key(|entry| (entry.rarity, entry.def_index, entry.paint_index));
+                entries.dedup();
+                case_loot.insert(*def_index, entries);
+            }
+        }
+        items_game.case_loot = case_loot;
+    }
+
+    /// Reads `item_sets`, whose `items` are `[paint_kit]weapon` entries for
+    /// skins and plain item names for anything else.
+    fn parse_item_sets(root_obj: &VdfObject, items_game: &mut ItemsGame) {
+        let skins = SkinNames::new(items_game);
+        let mut item_sets = HashMap::new();
+        for sets_obj in root_obj.objects("item_sets") {
+            for (key, value) in sets_obj {
+                let Some(obj) = value.as_object() else {
+                    continue;
+                };
+                let name = get_string_from_obj(obj, "name").unwrap_or_else(|| key.clone());
+                let items = obj
+                    .get("items")
+                    .and_then(|v| v.as_object())
+                    .map(|items| {
+                        items
+                            .keys()
+                            .filter_map(|entry| skins.resolve(entry))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let set = IGItemSet {
+                    name: name.strip_prefix('#').unwrap_or(&name).to_string(),
+                    is_collection: get_string_from_obj(obj, "is_collection").as_deref()
+                        == Some("1"),
+                    items,
+                };
+                item_sets.insert(key.clone(), set);
+            }
+        }
+        items_game.item_sets = item_sets;
+    }
+
+    /// Indexes the paint kits each weapon exists with. Every skin has an
+    /// icon in `alternate_icons2 > weapon_icons` per wear level, with a path
+    /// like `econ/default_generated/weapon_ak47_cu_ak47_cobra_light`; skins
+    /// in item sets are added as well.
+    fn parse_weapon_paint_kits(root_obj: &VdfObject, items_game: &mut ItemsGame) {
+        let skins = SkinNames::new(items_game);
+        let mut weapon_paint_kits: HashMap<u32, BTreeSet<u32>> = HashMap::new();
+
+        for icons_obj in root_obj.objects("alternate_icons2") {
+            let Some(weapon_icons) = icons_obj.get("weapon_icons").and_then(|v| v.as_object())
+            else {
+                continue;
+            };
+            for value in weapon_icons.values() {
+                let Some(icon_path) = value
+                    .as_object()
+                    .and_then(|obj| get_string_from_obj(obj, "icon_path"))
+                else {
+                    continue;
+                };
+                if let Some((def_index, paint_index)) = skins.resolve_icon(&icon_path) {
+                    weapon_paint_kits
+                        .entry(def_index)
+                        .or_default()
+                        .insert(paint_index);
+                }
+            }
+        }
+
+        for set in items_game.item_sets.values() {
+            for (def_index, paint_index) in &set.items {
+                weapon_paint_kits
+                    .entry(*def_index)
+                    .or_default()
+                    .insert(*paint_index);
+            }
+        }
+
+        items_game.weapon_paint_kits = weapon_paint_kits;
+    }
+}
+
+/// Looks up the def_index and paint kit of `[paint_kit]weapon` entries.
+struct SkinNames<'a> {
+    items: HashMap<&'a str, u32>,
+    paint_kits: HashMap<&'a str, u32>,
+}
+
+impl<'a> SkinNames<'a> {
+    fn new(items_game: &'a ItemsGame) -> Self {
+        Self {
+            items: items_game
+                .items
+                .iter()
+                .map(|(def_index, item)| (item.name.as_str(), *def_index))
+                .collect(),
+            paint_kits: items_game
+                .paint_kits
+                .iter()
+                .map(|(paint_index, paint_kit)| (paint_kit.name.as_str(), *paint_index))
+                .collect(),
+        }
+    }
+
+    /// `(def_index, paint_index)` of an entry naming a known skin.
+    fn resolve(&self, entry: &str) -> Option<(u32, u32)> {
+        let (paint_kit, item) = entry.strip_prefix('[')?.split_once(']')?;
+        Some((*self.items.get(item)?, *self.paint_kits.get(paint_kit)?))
+    }
+
+    /// `(def_index, paint_index)` of a weapon icon path. Item and paint kit
+    /// names both contain underscores, so every split is tried, longest item
+    /// name first: `weapon_m4a1_silencer_...` is an M4A1-S skin.
+    fn resolve_icon(&self, icon_path: &str) -> Option<(u32, u32)> {
+        let name = icon_path.rsplit('/').next()?;
+        let name = ["_light", "_medium", "_heavy"]
+            .iter()
+            .find_map(|suffix| name.strip_suffix(suffix))?;
+        name.rmatch_indices('_').find_map(|(at, _)| {
+            let (item, paint_kit) = (&name[..at], &name[at + 1..]);
+            Some((*self.items.get(item)?, *self.paint_kits.get(paint_kit)?))
+        })
+    }
 }
 
 fn get_inherited_string(
-    obj: &HashMap<String, VdfValue>,
-    prefabs_obj: Option<&HashMap<String, VdfValue>>,
+    obj: &VdfObject,
+    prefabs_obj: Option<&VdfObject>,
     prefab_name: Option<&str>,
     key: &str,
 ) -> Option<String> {
@@ -259,8 +534,8 @@ fn get_inherited_string(
 }
 
 fn get_inherited_string_with_depth(
-    obj: &HashMap<String, VdfValue>,
-    prefabs_obj: Option<&HashMap<String, VdfValue>>,
+    obj: &VdfObject,
+    prefabs_obj: Option<&VdfObject>,
     prefab_name: Option<&str>,
     key: &str,
     remaining_depth: usize,
@@ -284,9 +559,74 @@ fn get_inherited_string_with_depth(
     })
 }
 
+/// The object under `key` in the item or, failing that, in its nearest
+/// prefab that has one.
+fn get_inherited_object<'a>(
+    obj: &'a VdfObject,
+    prefabs_obj: Option<&'a VdfObject>,
+    prefab_name: Option<&str>,
+    key: &str,
+) -> Option<&'a VdfObject> {
+    let mut obj = obj;
+    let mut prefab_name = prefab_name.map(str::to_string);
+    for _ in 0..16 {
+        if let Some(value) = obj.get(key).and_then(|v| v.as_object()) {
+            return Some(value);
+        }
+        obj = prefabs_obj?.get(prefab_name.as_deref()?)?.as_object()?;
+        prefab_name = get_string_from_obj(obj, "prefab");
+    }
+    None
+}
+
+/// Attribute `name` of the item or, failing that, of its nearest prefab that
+/// sets it. Prefabs along the way may have `attributes` without it.
+fn get_inherited_attribute<'a>(
+    obj: &'a VdfObject,
+    prefabs_obj: Option<&'a VdfObject>,
+    prefab_name: Option<&str>,
+    name: &str,
+) -> Option<&'a VdfObject> {
+    let mut obj = obj;
+    let mut prefab_name = prefab_name.map(str::to_string);
+    for _ in 0..16 {
+        if let Some(attribute) = obj
+            .get("attributes")
+            .and_then(|v| v.as_object())
+            .and_then(|attributes| attributes.get(name))
+            .and_then(|v| v.as_object())
+        {
+            return Some(attribute);
+        }
+        obj = prefabs_obj?.get(prefab_name.as_deref()?)?.as_object()?;
+        prefab_name = get_string_from_obj(obj, "prefab");
+    }
+    None
+}
+
+fn get_used_by_classes(
+    obj: &VdfObject,
+    prefabs_obj: Option<&VdfObject>,
+    prefab_name: Option<&str>,
+) -> Vec<u32> {
+    let Some(classes) = get_inherited_object(obj, prefabs_obj, prefab_name, "used_by_classes")
+    else {
+        return Vec::new();
+    };
+    classes
+        .iter()
+        .filter(|(_, value)| value.as_string() != Some("0"))
+        .filter_map(|(class, _)| match class.as_str() {
+            "terrorists" => Some(2),
+            "counter-terrorists" => Some(3),
+            _ => None,
+        })
+        .collect()
+}
+
 fn get_associated_items(
-    obj: &HashMap<String, VdfValue>,
-    prefabs_obj: Option<&HashMap<String, VdfValue>>,
+    obj: &VdfObject,
+    prefabs_obj: Option<&VdfObject>,
     prefab_name: Option<&str>,
 ) -> Vec<u32> {
     let mut associated_items = Vec::new();
@@ -314,7 +654,57 @@ fn get_associated_items(
     associated_items
 }
 
-fn get_u32_from_obj(obj: &HashMap<String, VdfValue>, key: &str) -> Option<u32> {
+struct LootResolver<'a> {
+    items_game: &'a ItemsGame,
+    loot_lists: &'a HashMap<&'a str, &'a VdfObject>,
+    skins: &'a SkinNames<'a>,
+}
+
+impl LootResolver<'_> {
+    const MAX_DEPTH: usize = 8;
+
+    fn collect(&self, list: &str, rarity: Option<u32>, depth: usize, out: &mut Vec<IGLootEntry>) {
+        let Some(entries) = self.loot_lists.get(list) else {
+            return;
+        };
+        let rarity = self.list_rarity(list).or(rarity);
+
+        for (key, _) in entries.iter() {
+            if self.loot_lists.contains_key(key.as_str()) {
+                if depth < Self::MAX_DEPTH {
+                    self.collect(key, rarity, depth + 1, out);
+                }
+                continue;
+            }
+            let Some((def_index, paint_index)) = self.skins.resolve(key) else {
+                continue;
+            };
+            let Some(rarity) = rarity.or_else(|| self.items_game.get_paint_kit_rarity(paint_index))
+            else {
+                continue;
+            };
+            out.push(IGLootEntry {
+                def_index,
+                paint_index,
+                rarity,
+            });
+        }
+    }
+
+    /// The rarity a list's name ends in, e.g. `rare` in `crate_valve_1_rare`.
+    fn list_rarity(&self, list: &str) -> Option<u32> {
+        self.items_game
+            .rarities
+            .iter()
+            .find(|(name, _)| {
+                list.strip_suffix(name.as_str())
+                    .is_some_and(|rest| rest.ends_with('_'))
+            })
+            .map(|(_, rarity)| rarity.value)
+    }
+}
+
+fn get_u32_from_obj(obj: &VdfObject, key: &str) -> Option<u32> {
     obj.get(key)
         .and_then(|v| v.as_string())
         .and_then(|s| s.parse::<u32>().ok())
@@ -336,3 +726,438 @@ impl std::fmt::Display for ItemsGameLoadError {
 }
 
 impl std::error::Error for ItemsGameLoadError {}
+
+#[cfg(test)]
+mod tests {
+    use super::ItemsGameLoader;
+    use crate::inventory::item_attribute::{AttributeType, AttributeValue};
+    use crate::inventory::items_game::{GameTranslation, WearTier};
+    use crate::inventory::loader::InventoryLoader;
+    use crate::inventory::models::Item;
+    use crate::inventory::vdf::VdfParser;
+
+    /// Sections the loader does not read, some named like ones it does.
+    const UNREAD_SECTIONS: &[&str] = &[
+        "game_info",
+        "prefabs_unused",
+        "sticker_kits_v2",
+        "paint_kits_legacy",
+        "recipes",
+        "quest_definitions",
+    ];
+
+    /// An items_game shaped file with `entries` entries in each of a few read
+    /// sections and as many in each of [`UNREAD_SECTIONS`].
+    fn synthetic_items_game(entries: usize) -> String {
+        let mut content = String::from("\"items_game\"\n{\n");
+        let sections = ["items", "paint_kits", "client_loot_lists", "item_sets"];
+        for section in sections.iter().chain(UNREAD_SECTIONS) {
+            content.push_str(&format!("\t\"{}\"\n\t{{\n", section));
+            for i in 1..=entries {
+                content.push_str(&format!(
+                    concat!(
+                        "\t\t\"{i}\"\n\t\t{{\n",
+                        "\t\t\t\"name\"\t\t\"entry_{i}\"\n",
+                        "\t\t\t\"item_name\"\t\t\"#SFUI_Entry_{i}\"\n",
+                        "\t\t\t\"description_tag\"\t\t\"#PaintKit_{i}\"\n",
+                        "\t\t\t\"attributes\"\n\t\t\t{{\n\t\t\t\t\"a\"\t\t\"1\"\n\t\t\t}}\n",
+                        "\t\t}}\n",
+                    ),
+                    i = i
+                ));
+            }
+            content.push_str("\t}\n");
+        }
+        content.push_str("}\n");
+        content
+    }
+
+    #[test]
+    fn streaming_matches_tree_parser() {
+        let content = synthetic_items_game(20);
+
+        let streamed = ItemsGameLoader::parse_from_str(&content).unwrap();
+        let parsed = ItemsGameLoader::from_vdf(&VdfParser::parse(&content).unwrap());
+
+        assert_eq!(streamed.items.len(), 20);
+        assert_eq!(streamed.items, parsed.items);
+        assert_eq!(streamed.paint_kits, parsed.paint_kits);
+    }
+
+    #[test]
+    fn reads_every_items_game_block() {
+        let content = r#"
+            "items_game"
+            {
+                "items" { "7" { "name" "weapon_ak47" } }
+                "rarities" { "rare" { "value" "3" } }
+            }
+            "items_game"
+            {
+                "items" { "9" { "name" "weapon_awp" } }
+            }
+        "#;
+
+        let streamed = ItemsGameLoader::parse_from_str(content).unwrap();
+        let parsed = ItemsGameLoader::from_vdf(&VdfParser::parse(content).unwrap());
+
+        let mut def_indices: Vec<u32> = streamed.items.keys().copied().collect();
+        def_indices.sort_unstable();
+        assert_eq!(def_indices, [7, 9]);
+        assert_eq!(streamed.items, parsed.items);
+        assert_eq!(streamed.rarities, parsed.rarities);
+        assert_eq!(parsed.rarities["rare"].value, 3);
+    }
+
+    #[test]
+    fn streaming_skips_sections_the_loader_does_not_read() {
+        let content = synthetic_items_game(20);
+
+        let sections = ItemsGameLoader::extract_sections(&content)
+            .unwrap()
+            .unwrap();
+        let keys: Vec<&str> = sections.keys().map(String::as_str).collect();
+        assert_eq!(
+            keys,
+            ["items", "paint_kits", "client_loot_lists", "item_sets"]
+        );
+        for section in UNREAD_SECTIONS {
+            assert!(sections.get(section).is_none());
+        }
+    }
+
+    #[test]
+    fn reads_attribute_definitions() {
+        let items_game = ItemsGameLoader::parse_from_str(
+            r#"
+            "items_game"
+            {
+                "items" { "1" { "name" "weapon_deagle" "attributes" { "x" "1" } } }
+                "attributes"
+                {
+                    "8" { "name" "set item texture wear" "stored_as_integer" "0" }
+                    "113" { "name" "sticker slot 0 id" "stored_as_integer" "1" }
+                    "222" { "name" "tradable after date" "description_format" "value_is_date" "stored_as_integer" "1" }
+                    "232" { "name" "spray remaining uses" "description_format" "value_is_additive_percentage" "stored_as_integer" "0" }
+                    "500" { "name" "custom name attr" "attribute_type" "string" }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(items_game.attributes.len(), 5);
+        assert_eq!(items_game.get_attribute_value_type(8), AttributeType::Float);
+        assert_eq!(
+            items_game.get_attribute_value_type(113),
+            AttributeType::Uint32
+        );
+        assert_eq!(
+            items_game.get_attribute_value_type(500),
+            AttributeType::String
+        );
+        assert_eq!(
+            items_game.get_attribute_value_type(9999),
+            AttributeType::String
+        );
+        assert_eq!(items_game.get_attribute_default_value(8), "0.001");
+        assert_eq!(items_game.get_attribute_default_value(500), "");
+        assert_eq!(items_game.get_all_attribute_ids(), [8, 113, 222, 232, 500]);
+    }
+
+    #[test]
+    fn formats_loaded_values_by_their_definitions() {
+        let items_game = ItemsGameLoader::parse_from_str(
+            r#"
+            "items_game"
+            {
+                "attributes"
+                {
+                    "222" { "name" "tradable after date" "description_format" "value_is_date" "stored_as_integer" "1" }
+                    "232" { "name" "spray remaining uses" "description_format" "value_is_additive_percentage" "stored_as_integer" "0" }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        // 222 is not in the built-in attribute table, so it loads as text.
+        let inventory = InventoryLoader::parse_from_str(
+            r#"
+            "items"
+            {
+                "42"
+                {
+                    "inventory" "7" "def_index" "1348" "level" "1" "quality" "4"
+                    "flags" "0" "origin" "0" "in_use" "0" "rarity" "1"
+                    "attributes" { "222" "1500000000" "232" "0.5" }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        let attributes = &inventory.items[0].attributes;
+        assert_eq!(attributes[&222].value_type(), AttributeType::String);
+
+        assert_eq!(
+            items_game.attributes[&222].format_value(&attributes[&222]),
+            "2017-07-14 02:40"
+        );
+        assert_eq!(
+            items_game.attributes[&232].format_value(&attributes[&232]),
+            "+50%"
+        );
+        let unreadable = AttributeValue::string("soon");
+        assert_eq!(
+            items_game.attributes[&222].format_value(&unreadable),
+            "soon"
+        );
+    }
+
+    #[test]
+    fn reads_paint_kit_wear_and_style() {
+        let items_game = ItemsGameLoader::parse_from_str(
+            r#"
+            "items_game"
+            {
+                "items" { "7" { "name" "weapon_ak47" "item_name" "AK-47" } }
+                "paint_kits"
+                {
+                    "0" { "name" "default" "wear_remap_min" "0.06" "wear_remap_max" "0.8" }
+                    "44" { "name" "cu_ak47_case_hardened" "description_string" "Case Hardened" "style" "4" "wear_remap_min" "0.00" "wear_remap_max" "1.00" }
+                    "180" { "name" "cu_ak47_fire_serpent" "style" "7" "wear_remap_max" "0.40" }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(items_game.get_paint_kit_wear_range(180), (0.06, 0.4));
+        assert_eq!(
+            items_game.get_paint_kit_wear_tiers(180),
+            [
+                WearTier::FactoryNew,
+                WearTier::MinimalWear,
+                WearTier::FieldTested,
+                WearTier::WellWorn
+            ]
+        );
+        assert_eq!(WearTier::from_wear(0.07), WearTier::MinimalWear);
+        assert_eq!(WearTier::from_wear(0.45), WearTier::BattleScarred);
+        assert!(!items_game.paint_kits[&44].seed_affects_pattern());
+        assert!(items_game.paint_kits[&180].seed_affects_pattern());
+
+        let mut item = Item {
+            def_index: 7,
+            ..Default::default()
+        };
+        item.attributes.insert(6, AttributeValue::float(44.0));
+        item.attributes.insert(8, AttributeValue::float(0.2));
+        assert_eq!(
+            items_game.get_item_full_name(&item, &GameTranslation::default()),
+            "AK-47 | Case Hardened (Field-Tested)"
+        );
+    }
+
+    #[test]
+    fn types_loaded_attributes_by_their_definitions() {
+        let items_game = ItemsGameLoader::parse_from_str(
+            r#"
+            "items_game"
+            {
+                "attributes"
+                {
+                    "8" { "name" "set item texture wear" "stored_as_integer" "0" }
+                    "272" { "name" "casket item id low" "attribute_type" "uint32" }
+                    "500" { "name" "custom name attr" "attribute_type" "string" }
+                    "501" { "name" "kill eater score" "stored_as_integer" "1" }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        let mut inventory = InventoryLoader::parse_from_str(
+            r#"
+            "items"
+            {
+                "42"
+                {
+                    "inventory" "7" "def_index" "7" "level" "1" "quality" "4"
+                    "flags" "0" "origin" "0" "in_use" "0" "rarity" "1"
+                    "attributes" { "8" "nan" "272" "9" "500" "12" "501" "x" }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        let attributes = &inventory.items[0].attributes;
+        assert_eq!(attributes[&272].value_type(), AttributeType::String);
+        assert_eq!(attributes[&272].as_u32(), None);
+
+        let warnings = items_game.apply_attribute_types(&mut inventory);
+        let attributes = &inventory.items[0].attributes;
+        assert_eq!(attributes[&272].as_u32(), Some(9));
+        assert_eq!(attributes[&500].value_type(), AttributeType::String);
+        assert_eq!(attributes[&501].as_str(), "x");
+        assert_eq!(
+            warnings,
+            [
+                "Item 42 attribute 8: 'nan' is not a number; kept as text",
+                "Item 42 attribute 501: 'x' is not an unsigned integer; kept as text",
+            ]
+        );
+    }
+
+    #[test]
+    fn indexes_weapon_paint_kits() {
+        let items_game = ItemsGameLoader::parse_from_str(
+            r#"
+            "items_game"
+            {
+                "items"
+                {
+                    "7" { "name" "weapon_ak47" }
+                    "16" { "name" "weapon_m4a1" }
+                    "60" { "name" "weapon_m4a1_silencer" }
+                }
+                "paint_kits"
+                {
+                    "44" { "name" "cu_ak47_case_hardened" }
+                    "254" { "name" "silencer_cyrex" }
+                    "309" { "name" "cu_m4a1_howling" }
+                    "360" { "name" "cyrex" }
+                }
+                "alternate_icons2"
+                {
+                    "weapon_icons"
+                    {
+                        "1" { "icon_path" "econ/default_generated/weapon_m4a1_silencer_cyrex_light" }
+                        "2" { "icon_path" "econ/default_generated/weapon_m4a1_silencer_cyrex_heavy" }
+                        "3" { "icon_path" "econ/default_generated/weapon_m4a1_cu_m4a1_howling_medium" }
+                        "4" { "icon_path" "econ/default_generated/weapon_unknown_cyrex_light" }
+                    }
+                }
+                "item_sets"
+                {
+                    "set_community_1" { "items" { "[cu_ak47_case_hardened]weapon_ak47" "1" } }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let paint_kits = |def_index| {
+            items_game
+                .get_weapon_paint_kits(def_index)
+                .map(|kits| kits.iter().copied().collect::<Vec<_>>())
+        };
+        assert_eq!(paint_kits(7), Some(vec![44]));
+        assert_eq!(paint_kits(16), Some(vec![309]));
+        assert_eq!(paint_kits(60), Some(vec![360]));
+        assert_eq!(paint_kits(1), None);
+        assert_eq!(
+            items_game
+                .create_paint_kit_select_list_for_weapon(7, &GameTranslation::default())
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn resolves_case_contents() {
+        let items_game = ItemsGameLoader::parse_from_str(
+            r#"
+            "items_game"
+            {
+                "rarities"
+                {
+                    "rare" { "value" "3" }
+                    "mythical" { "value" "4" }
+                    "legendary" { "value" "5" }
+                }
+                "prefabs"
+                {
+                    "weapon_case" { "item_class" "supply_crate"
+                        "attributes" { "cannot trade" { "attribute_class" "cannot_trade" "value" "0" } } }
+                    "weapon_case_community_1" { "prefab" "weapon_case"
+                        "attributes" { "set supply crate series" { "attribute_class" "supply_crate_series" "value" "10" } } }
+                }
+                "items"
+                {
+                    "7" { "name" "weapon_ak47" }
+                    "9" { "name" "weapon_awp" }
+                    "4001" { "name" "crate_community_1" "prefab" "weapon_case"
+                        "attributes" { "set supply crate series" { "attribute_class" "supply_crate_series" "value" "10" } } }
+                    "4002" { "name" "crate_community_1_key_free" "prefab" "weapon_case_community_1"
+                        "attributes" { "tournament event id" { "attribute_class" "tournament_event_id" "value" "1" } } }
+                }
+                "paint_kits"
+                {
+                    "44" { "name" "cu_ak47_case_hardened" }
+                    "180" { "name" "cu_ak47_fire_serpent" }
+                    "51" { "name" "cu_awp_lightning" }
+                }
+                "paint_kits_rarity" { "cu_awp_lightning" "legendary" }
+                "revolving_loot_lists" { "10" "crate_community_1" }
+                "client_loot_lists"
+                {
+                    "crate_community_1_rare" { "[cu_ak47_case_hardened]weapon_ak47" "1" }
+                    "crate_community_1_mythical" { "[cu_ak47_fire_serpent]weapon_ak47" "1" "[missing]weapon_ak47" "1" }
+                    "crate_community_1_extra" { "[cu_awp_lightning]weapon_awp" "1" }
+                    "crate_community_1"
+                    {
+                        "crate_community_1_mythical" "1"
+                        "crate_community_1_rare" "1"
+                        "crate_community_1_extra" "1"
+                    }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let contents: Vec<(u32, u32, u32)> = items_game
+            .case_contents(4001)
+            .iter()
+            .map(|entry| (entry.def_index, entry.paint_index, entry.rarity))
+            .collect();
+        assert_eq!(contents, [(7, 44, 3), (7, 180, 4), (9, 51, 5)]);
+        assert!(items_game.case_contents(7).is_empty());
+        // The series comes from a prefab, past `attributes` that lack it.
+        assert_eq!(items_game.items[&4002].supply_crate_series, Some(10));
+        assert_eq!(
+            items_game.case_contents(4002),
+            items_game.case_contents(4001)
+        );
+    }
+
+    /// Reports the time of the full tree parse and of section streaming on
+    /// an items_game-sized file, most of which the loader does not read. Run
+    /// with `cargo test --release parse_speed -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn parse_speed() {
+        use std::time::Instant;
+
+        let content = synthetic_items_game(10_000);
+
+        let start = Instant::now();
+        let tree = VdfParser::parse(&content).unwrap();
+        let tree_time = start.elapsed();
+
+        let start = Instant::now();
+        let sections = ItemsGameLoader::extract_sections(&content)
+            .unwrap()
+            .unwrap();
+        let stream_time = start.elapsed();
+
+        let parsed = ItemsGameLoader::from_vdf(&tree);
+        let streamed = ItemsGameLoader::from_sections(&sections);
+        assert_eq!(streamed.items, parsed.items);
+        println!(
+            "{} KiB: VdfParser::parse {:?}, extract_sections {:?}",
+            content.len() / 1024,
+            tree_time,
+            stream_time
+        );
+    }
+}
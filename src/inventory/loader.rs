@@ -1,5 +1,8 @@
+use crate::core::{BackupStore, read_existing, write_atomic};
 use crate::inventory::models::Inventory;
 use crate::inventory::parser::{InventoryParser, VdfInventoryParser};
+use crate::inventory::vdf::{VdfDocument, VdfParser, VdfPatch};
+use crate::inventory::vdf_convert::{JsonLayout, json_to_vdf, vdf_to_json};
 use std::path::Path;
 
 static DEFAULT_PARSER: VdfInventoryParser = VdfInventoryParser;
@@ -20,29 +23,184 @@ impl InventoryLoader {
         Self::load(&inventory_path)
     }
 
+    /// Like [`load`](Self::load), but a damaged file still yields the items
+    /// that could be read, along with a warning for everything that was
+    /// skipped or kept as text. The warnings are empty when the file parsed
+    /// cleanly.
+    pub fn load_lenient<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<(Inventory, Vec<String>), InventoryLoadError> {
+        let content = std::fs::read_to_string(path).map_err(InventoryLoadError::Io)?;
+
+        Ok(DEFAULT_PARSER.parse_lenient(&content))
+    }
+
+    pub fn load_lenient_from_game_dir<P: AsRef<Path>>(
+        game_dir: P,
+    ) -> Result<(Inventory, Vec<String>), InventoryLoadError> {
+        let inventory_path = game_dir.as_ref().join("csgo_gc").join("inventory.txt");
+        Self::load_lenient(&inventory_path)
+    }
+
+    /// Whether the file at `path` has syntax errors, so that a
+    /// [`save`](Self::save) cannot update it in place and it has to be
+    /// rewritten with [`replace_with_backup`](Self::replace_with_backup).
+    pub fn has_syntax_errors<P: AsRef<Path>>(path: P) -> bool {
+        std::fs::read_to_string(path).is_ok_and(|content| VdfDocument::parse(&content).is_err())
+    }
+
     pub fn parse_from_str(content: &str) -> Result<Inventory, InventoryLoadError> {
         DEFAULT_PARSER
             .parse(content)
             .map_err(InventoryLoadError::Parse)
     }
 
+    /// Converts `inventory` to pretty-printed JSON in the inventory.txt layout;
+    /// see [`vdf_convert`](crate::inventory::vdf_convert) for the mapping.
+    pub fn export_json(
+        inventory: &Inventory,
+        layout: JsonLayout,
+    ) -> Result<String, InventorySaveError> {
+        let value = vdf_to_json(&DEFAULT_PARSER.to_vdf(inventory), layout);
+        serde_json::to_string_pretty(&value).map_err(|e| InventorySaveError::Serialize(e.into()))
+    }
+
+    /// Reads an inventory from JSON written by [`export_json`](Self::export_json)
+    /// with the same layout.
+    pub fn import_json(json: &str, layout: JsonLayout) -> Result<Inventory, InventoryLoadError> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| InventoryLoadError::Parse(e.into()))?;
+        let vdf = json_to_vdf(&value, layout).map_err(|e| InventoryLoadError::Parse(e.into()))?;
+        let inventory = DEFAULT_PARSER
+            .from_vdf(&vdf)
+            .map_err(InventoryLoadError::Parse)?;
+        check_unique_ids(inventory)
+    }
+
+    /// The changes in `changed` relative to `base`, as entries of
+    /// inventory.txt.
+    pub fn diff(base: &Inventory, changed: &Inventory) -> VdfPatch {
+        VdfPatch::diff(
+            &DEFAULT_PARSER.to_vdf(base),
+            &DEFAULT_PARSER.to_vdf(changed),
+        )
+    }
+
+    /// Writes the changes from `base` to `changed` as a JSON patch file that
+    /// [`apply_patch`](Self::apply_patch) can replay on another copy of `base`.
+    pub fn export_patch(
+        base: &Inventory,
+        changed: &Inventory,
+    ) -> Result<String, InventorySaveError> {
+        serde_json::to_string_pretty(&Self::diff(base, changed))
+            .map_err(|e| InventorySaveError::Serialize(e.into()))
+    }
+
+    /// Applies a patch written by [`export_patch`](Self::export_patch). Fails
+    /// without changing anything if `inventory` no longer holds the values the
+    /// patch replaces.
+    pub fn apply_patch(
+        inventory: &Inventory,
+        patch: &str,
+    ) -> Result<Inventory, InventoryLoadError> {
+        let patch: VdfPatch =
+            serde_json::from_str(patch).map_err(|e| InventoryLoadError::Parse(e.into()))?;
+        let mut vdf = DEFAULT_PARSER.to_vdf(inventory);
+        patch
+            .apply(&mut vdf)
+            .map_err(|e| InventoryLoadError::Parse(e.into()))?;
+        let patched = DEFAULT_PARSER
+            .from_vdf(&vdf)
+            .map_err(InventoryLoadError::Parse)?;
+        check_unique_ids(patched)
+    }
+
+    /// What [`save`](Self::save) would change in the file at `path`. A file
+    /// that is missing counts as empty.
+    pub fn preview_save<P: AsRef<Path>>(
+        inventory: &Inventory,
+        path: P,
+    ) -> Result<VdfPatch, InventorySaveError> {
+        let original = read_existing(path.as_ref()).map_err(InventorySaveError::Io)?;
+        let content = Self::render(inventory, original.as_deref())?;
+
+        let before = original
+            .and_then(|original| VdfParser::parse(&original).ok())
+            .unwrap_or_default();
+        let after =
+            VdfParser::parse(&content).map_err(|e| InventorySaveError::Serialize(e.into()))?;
+        Ok(VdfPatch::diff(&before, &after))
+    }
+
+    /// Writes `inventory` to `path`. If the file already exists, only the
+    /// entries that changed are rewritten, so comments, formatting and keys
+    /// the editor does not know about are kept. The file is replaced
+    /// atomically, so an interrupted save leaves the previous version intact.
     pub fn save<P: AsRef<Path>>(inventory: &Inventory, path: P) -> Result<(), InventorySaveError> {
-        let content = DEFAULT_PARSER
-            .serialize(inventory)
-            .map_err(InventorySaveError::Serialize)?;
+        let original = read_existing(path.as_ref()).map_err(InventorySaveError::Io)?;
+        let content = Self::render(inventory, original.as_deref())?;
 
-        std::fs::write(path, content).map_err(InventorySaveError::Io)
+        write_atomic(path.as_ref(), content).map_err(InventorySaveError::Io)
+    }
+
+    /// Like [`save`](Self::save), but first copies the current file into
+    /// `backups`.
+    pub fn save_with_backup<P: AsRef<Path>>(
+        inventory: &Inventory,
+        path: P,
+        backups: &BackupStore,
+    ) -> Result<(), InventorySaveError> {
+        backups
+            .backup(path.as_ref())
+            .map_err(InventorySaveError::Io)?;
+        Self::save(inventory, path)
+    }
+
+    /// Writes `inventory` to `path` from scratch, for when the file there
+    /// does not parse and so cannot be [`save`](Self::save)d over. The
+    /// current file is copied into `backups` first.
+    pub fn replace_with_backup<P: AsRef<Path>>(
+        inventory: &Inventory,
+        path: P,
+        backups: &BackupStore,
+    ) -> Result<(), InventorySaveError> {
+        backups
+            .backup(path.as_ref())
+            .map_err(InventorySaveError::Io)?;
+        let content = Self::render(inventory, None)?;
+        write_atomic(path.as_ref(), content).map_err(InventorySaveError::Io)
+    }
+
+    fn render(inventory: &Inventory, original: Option<&str>) -> Result<String, InventorySaveError> {
+        match original {
+            Some(original) => DEFAULT_PARSER.serialize_preserving(inventory, original),
+            None => DEFAULT_PARSER.serialize(inventory),
+        }
+        .map_err(InventorySaveError::Serialize)
     }
 
     pub fn save_to_game_dir<P: AsRef<Path>>(
         inventory: &Inventory,
         game_dir: P,
+        backups: &BackupStore,
     ) -> Result<(), InventorySaveError> {
         let inventory_path = game_dir.as_ref().join("csgo_gc").join("inventory.txt");
-        Self::save(inventory, &inventory_path)
+        Self::save_with_backup(inventory, &inventory_path, backups)
     }
 }
 
+/// Imported items must not share ids: csgo_gc would only keep one of them.
+fn check_unique_ids(inventory: Inventory) -> Result<Inventory, InventoryLoadError> {
+    let duplicates = inventory.duplicate_item_ids();
+    if duplicates.is_empty() {
+        return Ok(inventory);
+    }
+    let ids: Vec<String> = duplicates.iter().map(ToString::to_string).collect();
+    Err(InventoryLoadError::Parse(
+        format!("Item ids used more than once: {}", ids.join(", ")).into(),
+    ))
+}
+
 #[derive(Debug)]
 pub enum InventoryLoadError {
     Io(std::io::Error),
@@ -76,3 +234,80 @@ impl std::fmt::Display for InventorySaveError {
 }
 
 impl std::error::Error for InventorySaveError {}
+
+#[cfg(test)]
+mod tests {
+    use super::InventoryLoader;
+    use crate::core::BackupStore;
+    use crate::inventory::edit::InventoryEdit;
+    use std::fs;
+
+    #[test]
+    fn rewrites_a_file_with_syntax_errors_after_an_edit() {
+        let dir = std::env::temp_dir().join(format!("csgo-gc-loader-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("inventory.txt");
+        // Item 43's rarity lost its closing quote.
+        let broken = concat!(
+            "\"items\"\n{\n",
+            "\t\"42\"\n\t{\n",
+            "\t\t\"inventory\"\t\t\"1\"\n\t\t\"def_index\"\t\t\"507\"\n\t\t\"level\"\t\t\"1\"\n",
+            "\t\t\"quality\"\t\t\"3\"\n\t\t\"flags\"\t\t\"0\"\n\t\t\"origin\"\t\t\"24\"\n",
+            "\t\t\"in_use\"\t\t\"0\"\n\t\t\"rarity\"\t\t\"6\"\n",
+            "\t}\n",
+            "\t\"43\"\n\t{\n",
+            "\t\t\"inventory\"\t\t\"2\"\n\t\t\"def_index\"\t\t\"7\"\n\t\t\"level\"\t\t\"1\"\n",
+            "\t\t\"quality\"\t\t\"4\"\n\t\t\"flags\"\t\t\"0\"\n\t\t\"origin\"\t\t\"8\"\n",
+            "\t\t\"in_use\"\t\t\"0\"\n\t\t\"rarity\"\t\t\"3\n",
+            "\t}\n",
+            "}\n",
+        );
+        fs::write(&path, broken).unwrap();
+        let backups = BackupStore::new(dir.join("backups"), 2);
+
+        let (mut inventory, warnings) = InventoryLoader::load_lenient(&path).unwrap();
+        assert!(!warnings.is_empty());
+        assert!(InventoryLoader::has_syntax_errors(&path));
+        InventoryEdit::SetRarity {
+            item_id: 42,
+            rarity: 5,
+        }
+        .apply(&mut inventory)
+        .unwrap();
+
+        assert!(InventoryLoader::save_with_backup(&inventory, &path, &backups).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), broken);
+
+        InventoryLoader::replace_with_backup(&inventory, &path, &backups).unwrap();
+        assert!(!InventoryLoader::has_syntax_errors(&path));
+        let saved = InventoryLoader::load(&path).unwrap();
+        assert_eq!(saved.items[0].rarity, 5);
+        let backup = &backups.list("inventory.txt")[0];
+        assert_eq!(fs::read_to_string(&backup.path).unwrap(), broken);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn does_not_save_over_a_file_it_cannot_read() {
+        let dir =
+            std::env::temp_dir().join(format!("csgo-gc-loader-latin1-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("inventory.txt");
+        // A custom name written as Latin-1.
+        let latin1 = b"// keep me\n\"items\"\n{\n\t\"42\"\n\t{\n\t\t\"custom_name\"\t\t\"Caf\xe9\"\n\t}\n}\n";
+        fs::write(&path, latin1).unwrap();
+
+        let inventory = Default::default();
+        assert!(InventoryLoader::save(&inventory, &path).is_err());
+        assert!(InventoryLoader::preview_save(&inventory, &path).is_err());
+        assert_eq!(fs::read(&path).unwrap(), latin1);
+
+        fs::remove_file(&path).unwrap();
+        InventoryLoader::save(&inventory, &path).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,362 @@
+//! Loadouts: which item each team has equipped in each slot.
+//!
+//! An item's `equipped_state` maps a class id (the team) to the loadout slot
+//! it is equipped in. `default_equips` holds the stock items equipped without
+//! an inventory item behind them, such as the M4A1-S chosen over the M4A4;
+//! it is keyed by item definition. A slot holds one item per team, whether
+//! from the inventory or a default equip.
+
+use crate::inventory::edit::InventoryEdit;
+use crate::inventory::items_game::ItemsGame;
+use crate::inventory::models::{Inventory, Item};
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Team {
+    /// Music kits, flair and graffiti, which are not equipped per team.
+    NoTeam,
+    Terrorist,
+    CounterTerrorist,
+}
+
+impl Team {
+    pub fn all() -> &'static [Team] {
+        &[Team::NoTeam, Team::Terrorist, Team::CounterTerrorist]
+    }
+
+    pub fn class_id(&self) -> u32 {
+        match self {
+            Team::NoTeam => 0,
+            Team::Terrorist => 2,
+            Team::CounterTerrorist => 3,
+        }
+    }
+
+    pub fn from_class_id(class_id: u32) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|team| team.class_id() == class_id)
+    }
+
+    pub fn fluent_key(&self) -> &'static str {
+        match self {
+            Team::NoTeam => "team-noteam",
+            Team::Terrorist => "team-t",
+            Team::CounterTerrorist => "team-ct",
+        }
+    }
+}
+
+/// Loadout slots sharing an items_game `item_slot`, e.g. the six rifle slots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotGroup {
+    pub item_slot: &'static str,
+    pub slots: RangeInclusive<u32>,
+    pub no_team: bool,
+}
+
+impl SlotGroup {
+    pub fn fluent_key(&self) -> String {
+        format!("loadout-slot-{}", self.item_slot)
+    }
+}
+
+const fn group(item_slot: &'static str, first: u32, last: u32, no_team: bool) -> SlotGroup {
+    SlotGroup {
+        item_slot,
+        slots: first..=last,
+        no_team,
+    }
+}
+
+/// Every loadout slot the game has, by group.
+pub const SLOT_GROUPS: [SlotGroup; 13] = [
+    group("melee", 0, 0, false),
+    group("c4", 1, 1, false),
+    group("secondary", 2, 7, false),
+    group("smg", 8, 13, false),
+    group("rifle", 14, 19, false),
+    group("heavy", 20, 25, false),
+    group("grenade", 26, 31, false),
+    group("equipment", 32, 37, false),
+    group("customplayer", 38, 38, false),
+    group("clothing", 41, 41, false),
+    group("musickit", 54, 54, true),
+    group("flair0", 55, 55, true),
+    group("spray0", 56, 56, true),
+];
+
+pub fn slot_group(slot: u32) -> Option<&'static SlotGroup> {
+    SLOT_GROUPS.iter().find(|group| group.slots.contains(&slot))
+}
+
+pub fn is_loadout_slot(slot: u32) -> bool {
+    slot_group(slot).is_some()
+}
+
+/// The slot group `def_index` is equipped in, or `None` if it cannot be
+/// equipped or the schema does not know it.
+pub fn item_slot_group(items_game: &ItemsGame, def_index: u32) -> Option<&'static SlotGroup> {
+    let item_slot = items_game.items.get(&def_index)?.item_slot.as_deref()?;
+    SLOT_GROUPS
+        .iter()
+        .find(|group| group.item_slot == item_slot)
+}
+
+/// The teams `def_index` can be equipped for.
+pub fn item_teams(items_game: &ItemsGame, def_index: u32) -> Vec<Team> {
+    match item_slot_group(items_game, def_index) {
+        Some(group) if group.no_team => vec![Team::NoTeam],
+        Some(_) => {
+            let classes = &items_game.items[&def_index].used_by_classes;
+            if classes.is_empty() {
+                vec![Team::Terrorist, Team::CounterTerrorist]
+            } else {
+                classes
+                    .iter()
+                    .filter_map(|class_id| Team::from_class_id(*class_id))
+                    .collect()
+            }
+        }
+        None => Vec::new(),
+    }
+}
+
+/// What fills a loadout slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Equipped {
+    Item(u64),
+    /// A stock item from `default_equips`, by item definition.
+    Default(u32),
+}
+
+/// The equipped items of an inventory by team and slot.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Loadout {
+    slots: BTreeMap<(Team, u32), Vec<Equipped>>,
+}
+
+impl Loadout {
+    /// Reads the loadout from `inventory`. Entries for unknown teams are left
+    /// out; validation reports those.
+    pub fn of(inventory: &Inventory) -> Self {
+        let mut slots: BTreeMap<(Team, u32), Vec<Equipped>> = BTreeMap::new();
+        for item in &inventory.items {
+            let mut equipped: Vec<(&u32, &String)> = item.equipped_state.iter().collect();
+            equipped.sort_unstable();
+            for (class_id, slot) in equipped {
+                if let (Some(team), Ok(slot)) = (Team::from_class_id(*class_id), slot.parse()) {
+                    slots
+                        .entry((team, slot))
+                        .or_default()
+                        .push(Equipped::Item(item.id));
+                }
+            }
+        }
+
+        let mut defaults: Vec<_> = inventory.default_equips.iter().collect();
+        defaults.sort_unstable_by_key(|(def_index, _)| **def_index);
+        for (def_index, equip) in defaults {
+            if let Some(team) = Team::from_class_id(equip.class_id) {
+                slots
+                    .entry((team, equip.slot_id))
+                    .or_default()
+                    .push(Equipped::Default(*def_index));
+            }
+        }
+
+        Self { slots }
+    }
+
+    /// What fills `slot` for `team`; more than one entry is a conflict.
+    pub fn get(&self, team: Team, slot: u32) -> &[Equipped] {
+        self.slots
+            .get(&(team, slot))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Team and slot of every slot filled more than once.
+    pub fn conflicts(&self) -> Vec<(Team, u32, &[Equipped])> {
+        self.slots
+            .iter()
+            .filter(|(_, equipped)| equipped.len() > 1)
+            .map(|((team, slot), equipped)| (*team, *slot, equipped.as_slice()))
+            .collect()
+    }
+}
+
+/// The edit that equips `item_id` for `team` in `slot`, moving it out of any
+/// other slot it has for that team and taking out whatever the slot held.
+pub fn equip(
+    inventory: &Inventory,
+    items_game: &ItemsGame,
+    item_id: u64,
+    team: Team,
+    slot: u32,
+) -> Result<InventoryEdit, String> {
+    let item = find_item(inventory, item_id)?;
+    if !items_game.items.is_empty() {
+        match item_slot_group(items_game, item.def_index) {
+            Some(group) if group.slots.contains(&slot) => {}
+            _ => return Err(format!("Item {} does not fit slot {}", item_id, slot)),
+        }
+        if !item_teams(items_game, item.def_index).contains(&team) {
+            return Err(format!(
+                "Item {} cannot be equipped for team {}",
+                item_id,
+                team.class_id()
+            ));
+        }
+    }
+
+    let mut edits = Vec::new();
+    for equipped in Loadout::of(inventory).get(team, slot) {
+        match *equipped {
+            Equipped::Item(other_id) if other_id != item_id => {
+                edits.push(unequipped(find_item(inventory, other_id)?, team));
+            }
+            Equipped::Default(def_index) => edits.push(InventoryEdit::SetDefaultEquip {
+                def_index,
+                equip: None,
+            }),
+            Equipped::Item(_) => {}
+        }
+    }
+
+    let mut equipped = item.clone();
+    equipped
+        .equipped_state
+        .insert(team.class_id(), slot.to_string());
+    edits.push(InventoryEdit::ReplaceItem(equipped));
+
+    Ok(InventoryEdit::Batch {
+        description: format!("Equip item {}", item_id),
+        edits,
+    })
+}
+
+/// The edit that takes `item_id` out of its slot for `team`.
+pub fn unequip(inventory: &Inventory, item_id: u64, team: Team) -> Result<InventoryEdit, String> {
+    let item = find_item(inventory, item_id)?;
+    if !item.equipped_state.contains_key(&team.class_id()) {
+        return Err(format!(
+            "Item {} is not equipped for team {}",
+            item_id,
+            team.class_id()
+        ));
+    }
+    Ok(unequipped(item, team))
+}
+
+fn unequipped(item: &Item, team: Team) -> InventoryEdit {
+    let mut item = item.clone();
+    item.equipped_state.remove(&team.class_id());
+    InventoryEdit::ReplaceItem(item)
+}
+
+fn find_item(inventory: &Inventory, item_id: u64) -> Result<&Item, String> {
+    inventory
+        .items
+        .iter()
+        .find(|item| item.id == item_id)
+        .ok_or_else(|| format!("Item with id {} not found", item_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Equipped, Loadout, Team, equip, unequip};
+    use crate::inventory::items_game_loader::ItemsGameLoader;
+    use crate::inventory::models::{DefaultEquip, Inventory, Item};
+
+    #[test]
+    fn equips_one_item_per_slot() {
+        let items_game = ItemsGameLoader::parse_from_str(
+            r#"
+            "items_game"
+            {
+                "prefabs"
+                {
+                    "rifle" { "item_slot" "rifle" "used_by_classes" { "terrorists" "1" } }
+                    "m4" { "item_slot" "rifle" "used_by_classes" { "counter-terrorists" "1" } }
+                }
+                "items"
+                {
+                    "7" { "name" "weapon_ak47" "prefab" "rifle" }
+                    "16" { "name" "weapon_m4a1" "prefab" "m4" }
+                    "60" { "name" "weapon_m4a1_silencer" "prefab" "m4" }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let mut old_ak = Item {
+            id: 1,
+            def_index: 7,
+            ..Default::default()
+        };
+        old_ak.equipped_state.insert(2, "14".to_string());
+        let mut inventory = Inventory {
+            items: vec![
+                old_ak,
+                Item {
+                    id: 2,
+                    def_index: 7,
+                    ..Default::default()
+                },
+                Item {
+                    id: 3,
+                    def_index: 16,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        inventory.default_equips.insert(
+            60,
+            DefaultEquip {
+                class_id: 3,
+                slot_id: 15,
+            },
+        );
+
+        assert!(equip(&inventory, &items_game, 2, Team::CounterTerrorist, 14).is_err());
+        assert!(equip(&inventory, &items_game, 2, Team::Terrorist, 2).is_err());
+
+        equip(&inventory, &items_game, 2, Team::Terrorist, 14)
+            .unwrap()
+            .apply(&mut inventory)
+            .unwrap();
+        equip(&inventory, &items_game, 3, Team::CounterTerrorist, 15)
+            .unwrap()
+            .apply(&mut inventory)
+            .unwrap();
+
+        let loadout = Loadout::of(&inventory);
+        assert_eq!(loadout.get(Team::Terrorist, 14), [Equipped::Item(2)]);
+        assert_eq!(loadout.get(Team::CounterTerrorist, 15), [Equipped::Item(3)]);
+        assert!(inventory.items[0].equipped_state.is_empty());
+        assert!(inventory.default_equips.is_empty());
+
+        unequip(&inventory, 2, Team::Terrorist)
+            .unwrap()
+            .apply(&mut inventory)
+            .unwrap();
+        assert!(unequip(&inventory, 2, Team::Terrorist).is_err());
+
+        inventory.items[0]
+            .equipped_state
+            .insert(3, "15".to_string());
+        assert_eq!(
+            Loadout::of(&inventory).conflicts(),
+            [(
+                Team::CounterTerrorist,
+                15,
+                &[Equipped::Item(1), Equipped::Item(3)][..]
+            )]
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! Three-way merges of inventories.
+//!
+//! When inventory.txt is rewritten by another program (csgo_gc itself does so
+//! after case openings and when items are used up), the editor holds three
+//! versions: `base`, the file as last read or written by the editor; `ours`,
+//! the editor's copy; and `theirs`, the file as it is now. Items are matched
+//! by id. An item changed on one side only takes that side's version; an item
+//! changed differently on both sides is a conflict, settled by picking a side.
+
+use crate::inventory::loader::InventoryLoader;
+use crate::inventory::models::{Inventory, Item};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeSide {
+    #[default]
+    Ours,
+    Theirs,
+}
+
+/// An item both sides changed, or one side changed and the other deleted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemConflict {
+    pub item_id: u64,
+    pub base: Option<Item>,
+    pub ours: Option<Item>,
+    pub theirs: Option<Item>,
+    pub resolution: MergeSide,
+}
+
+impl ItemConflict {
+    /// What the editor changed in the item, one line per inventory.txt entry.
+    pub fn our_changes(&self) -> Vec<String> {
+        describe_changes(self.base.as_ref(), self.ours.as_ref())
+    }
+
+    /// What the other program changed in the item.
+    pub fn their_changes(&self) -> Vec<String> {
+        describe_changes(self.base.as_ref(), self.theirs.as_ref())
+    }
+}
+
+fn describe_changes(base: Option<&Item>, changed: Option<&Item>) -> Vec<String> {
+    let wrap = |item: Option<&Item>| Inventory {
+        items: item.into_iter().cloned().collect(),
+        ..Default::default()
+    };
+    InventoryLoader::diff(&wrap(base), &wrap(changed))
+        .changes
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct InventoryMerge {
+    pub theirs: Inventory,
+    /// Ids of the merged items, in the order they are written.
+    order: Vec<u64>,
+    /// Every item that is not in conflict, or `None` where it was deleted.
+    resolved: HashMap<u64, Option<Item>>,
+    /// The merged inventory without its items.
+    rest: Inventory,
+    pub conflicts: Vec<ItemConflict>,
+}
+
+impl InventoryMerge {
+    pub fn new(base: &Inventory, ours: &Inventory, theirs: &Inventory) -> Self {
+        let base_items = items_by_id(base);
+        let our_items = items_by_id(ours);
+        let their_items = items_by_id(theirs);
+
+        let order = merge_order(ours, theirs);
+        let mut resolved = HashMap::new();
+        let mut conflicts = Vec::new();
+        for &item_id in &order {
+            let b = base_items.get(&item_id).copied();
+            let o = our_items.get(&item_id).copied();
+            let t = their_items.get(&item_id).copied();
+
+            let item = if o == t || o == b {
+                t
+            } else if t == b {
+                o
+            } else {
+                conflicts.push(ItemConflict {
+                    item_id,
+                    base: b.cloned(),
+                    ours: o.cloned(),
+                    theirs: t.cloned(),
+                    resolution: MergeSide::Ours,
+                });
+                continue;
+            };
+            resolved.insert(item_id, item.cloned());
+        }
+
+        // Loadouts and unmodelled sections are merged as a whole: the editor's
+        // copy wins only if it changed them.
+        let mut rest = Inventory {
+            items: Vec::new(),
+            ..theirs.clone()
+        };
+        if ours.default_equips != base.default_equips {
+            rest.default_equips = ours.default_equips.clone();
+        }
+        if ours.extra != base.extra {
+            rest.extra = ours.extra.clone();
+        }
+
+        Self {
+            theirs: theirs.clone(),
+            order,
+            resolved,
+            rest,
+            conflicts,
+        }
+    }
+
+    /// The merged inventory, taking each conflict from the side its
+    /// `resolution` picks. Items keep the order they have on disk; items only
+    /// the editor has come last.
+    pub fn merged(&self) -> Inventory {
+        let chosen: HashMap<u64, Option<&Item>> = self
+            .conflicts
+            .iter()
+            .map(|conflict| {
+                let item = match conflict.resolution {
+                    MergeSide::Ours => conflict.ours.as_ref(),
+                    MergeSide::Theirs => conflict.theirs.as_ref(),
+                };
+                (conflict.item_id, item)
+            })
+            .collect();
+
+        let items = self
+            .order
+            .iter()
+            .filter_map(|item_id| match chosen.get(item_id) {
+                Some(item) => item.cloned(),
+                None => self.resolved.get(item_id).cloned().flatten(),
+            })
+            .collect();
+
+        Inventory {
+            items,
+            ..self.rest.clone()
+        }
+    }
+}
+
+fn items_by_id(inventory: &Inventory) -> HashMap<u64, &Item> {
+    inventory.items.iter().map(|item| (item.id, item)).collect()
+}
+
+/// Ids in `theirs` order, followed by those only in `ours`.
+fn merge_order(ours: &Inventory, theirs: &Inventory) -> Vec<u64> {
+    let mut seen = HashSet::new();
+    theirs
+        .items
+        .iter()
+        .chain(&ours.items)
+        .map(|item| item.id)
+        .filter(|item_id| seen.insert(*item_id))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InventoryMerge, MergeSide};
+    use crate::inventory::item_attribute::AttributeValue;
+    use crate::inventory::models::{DefaultEquip, Inventory};
+    use crate::inventory::test_fixtures::{inventory, item};
+
+    /// A StatTrak AK-47, a case and a key.
+    fn base() -> Inventory {
+        inventory(&[
+            item(1, 7, 3, &[(6, "180"), (80, "10"), (81, "0")]),
+            item(2, 4001, 3, &[(68, "10")]),
+            item(3, 1203, 3, &[(75, "0")]),
+        ])
+    }
+
+    fn ids(inventory: &Inventory) -> Vec<u64> {
+        inventory.items.iter().map(|item| item.id).collect()
+    }
+
+    #[test]
+    fn keeps_changes_each_side_made_alone() {
+        let base = base();
+        // The editor renamed the AK-47; the game opened the case with the key.
+        let mut ours = base.clone();
+        ours.items[0].custom_name = Some("Serpent".to_string());
+        let theirs = inventory(&[
+            item(1, 7, 3, &[(6, "180"), (80, "10"), (81, "0")]),
+            item(11, 9, 3, &[(6, "51")]),
+        ]);
+
+        let merge = InventoryMerge::new(&base, &ours, &theirs);
+        assert!(merge.conflicts.is_empty());
+        let merged = merge.merged();
+        assert_eq!(ids(&merged), [1, 11]);
+        assert_eq!(merged.items[0].custom_name.as_deref(), Some("Serpent"));
+    }
+
+    #[test]
+    fn items_only_the_editor_has_come_last() {
+        let base = base();
+        let mut ours = base.clone();
+        let mut added = base.items[2].clone();
+        added.id = 10;
+        ours.items.insert(0, added);
+        let mut theirs = base.clone();
+        theirs.items.reverse();
+
+        let merged = InventoryMerge::new(&base, &ours, &theirs).merged();
+        assert_eq!(ids(&merged), [3, 2, 1, 10]);
+    }
+
+    #[test]
+    fn an_item_both_sides_changed_is_a_conflict() {
+        let base = base();
+        // The editor reset the kill count; the game counted another kill.
+        let mut ours = base.clone();
+        ours.items[0]
+            .attributes
+            .insert(80, AttributeValue::uint32(0));
+        let theirs = inventory(&[
+            item(1, 7, 3, &[(6, "180"), (80, "11"), (81, "0")]),
+            item(2, 4001, 3, &[(68, "10")]),
+            item(3, 1203, 3, &[(75, "0")]),
+        ]);
+
+        let mut merge = InventoryMerge::new(&base, &ours, &theirs);
+        assert_eq!(merge.conflicts.len(), 1);
+        let conflict = &merge.conflicts[0];
+        assert_eq!(conflict.item_id, 1);
+        assert_eq!(
+            conflict.our_changes(),
+            ["~ items > 1 > attributes > 80: 10 -> 0"]
+        );
+        assert_eq!(
+            conflict.their_changes(),
+            ["~ items > 1 > attributes > 80: 10 -> 11"]
+        );
+        assert_eq!(merge.merged().items[0].attributes[&80].as_str(), "0");
+
+        merge.conflicts[0].resolution = MergeSide::Theirs;
+        assert_eq!(merge.merged(), theirs);
+    }
+
+    #[test]
+    fn an_item_changed_on_one_side_and_deleted_on_the_other_is_a_conflict() {
+        let base = base();
+        let mut ours = base.clone();
+        ours.items[1].rarity = 4;
+        let theirs = inventory(&[
+            item(1, 7, 3, &[(6, "180"), (80, "10"), (81, "0")]),
+            item(3, 1203, 3, &[(75, "0")]),
+        ]);
+
+        let mut merge = InventoryMerge::new(&base, &ours, &theirs);
+        assert_eq!(merge.conflicts.len(), 1);
+        assert!(merge.conflicts[0].theirs.is_none());
+        assert_eq!(ids(&merge.merged()), [1, 3, 2]);
+
+        merge.conflicts[0].resolution = MergeSide::Theirs;
+        assert_eq!(ids(&merge.merged()), [1, 3]);
+    }
+
+    #[test]
+    fn loadouts_take_the_side_that_changed_them() {
+        let base = base();
+        let mut ours = base.clone();
+        ours.default_equips.insert(
+            61,
+            DefaultEquip {
+                class_id: 3,
+                slot_id: 2,
+            },
+        );
+        let theirs = inventory(&[item(1, 7, 3, &[(6, "180")])]);
+
+        let merged = InventoryMerge::new(&base, &ours, &theirs).merged();
+        assert_eq!(merged.default_equips, ours.default_equips);
+
+        let unchanged = InventoryMerge::new(&base, &base, &theirs);
+        assert!(unchanged.conflicts.is_empty());
+        assert_eq!(unchanged.merged(), theirs);
+    }
+}
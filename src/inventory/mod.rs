@@ -1,22 +1,43 @@
+pub mod case_opening;
+pub mod edit;
 pub mod item_attribute;
+pub mod item_ids;
 pub mod items_game;
 pub mod items_game_loader;
 pub mod language_file;
 pub mod loader;
+pub mod loadout;
+pub mod merge;
 pub mod models;
 pub mod parser;
+pub mod snapshot;
+#[cfg(test)]
+pub(crate) mod test_fixtures;
+pub mod trade_up;
+pub mod validate;
 pub mod vdf;
+pub mod vdf_convert;
 
+pub use case_opening::{CaseOpener, CaseRng, CaseStats};
+pub use edit::{AppliedEdit, EditHistory, InventoryEdit};
 pub use item_attribute::{
-    AVAILABLE_ATTRIBUTES, ItemAttribute, get_attribute_default_value, get_attribute_fluent_key,
+    AVAILABLE_ATTRIBUTES, AttributeType, AttributeValue, AttributeValueError, ItemAttribute,
+    attribute_value_type, get_attribute_default_value, get_attribute_fluent_key,
     get_attribute_value_display_name,
 };
+pub use item_ids::{IdStrategy, RESERVED_ID_START};
 pub use items_game::{
-    GameTranslation, IGGraffitiTint, IGItem, IGMusicDef, IGPaintKit, IGQuality, IGRarity,
-    IGStickerKit, ItemsGame,
+    GameTranslation, IGAttribute, IGGraffitiTint, IGItem, IGItemSet, IGLootEntry, IGMusicDef,
+    IGPaintKit, IGQuality, IGRarity, IGStickerKit, ItemsGame, MAX_PAINT_SEED, WearTier,
 };
 pub use items_game_loader::{ItemsGameLoadError, ItemsGameLoader};
 pub use language_file::{LanguageFileLoadError, LanguageFileParser};
 pub use loader::{InventoryLoadError, InventoryLoader, InventorySaveError};
+pub use loadout::{Equipped, Loadout, SLOT_GROUPS, SlotGroup, Team};
+pub use merge::{InventoryMerge, ItemConflict, MergeSide};
 pub use models::{DefaultEquip, Inventory, Item};
-pub use vdf::VdfParser;
+pub use snapshot::{Snapshot, SnapshotEntry, SnapshotStore};
+pub use trade_up::{TRADE_UP_SIZE, TradeUp, TradeUpOutcome};
+pub use validate::{Problem, Severity, SkinCatalog, validate};
+pub use vdf::{VdfBinary, VdfDocument, VdfObject, VdfParser, VdfValue};
+pub use vdf_convert::{JsonConvertError, JsonLayout, json_to_vdf, vdf_to_json};
@@ -1,3 +1,5 @@
+use crate::inventory::item_attribute::{AttributeValue, attribute_map};
+use crate::inventory::vdf::VdfObject;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -14,10 +16,15 @@ pub struct Item {
     pub rarity: u32,
     #[serde(default)]
     pub custom_name: Option<String>,
-    #[serde(default)]
-    pub attributes: HashMap<u32, String>,
+    #[serde(default, with = "attribute_map")]
+    pub attributes: HashMap<u32, AttributeValue>,
     #[serde(default)]
     pub equipped_state: HashMap<u32, String>,
+    /// Entries of the item the editor does not model, written back as they
+    /// were read. Unmodeled entries of `attributes` or `equipped_state` are
+    /// kept under the same key here.
+    #[serde(default, skip_serializing_if = "VdfObject::is_empty")]
+    pub extra: VdfObject,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -32,4 +39,12 @@ pub struct Inventory {
     pub items: Vec<Item>,
     #[serde(default)]
     pub default_equips: HashMap<u32, DefaultEquip>,
+    /// Top-level sections the editor does not model.
+    #[serde(default, skip_serializing_if = "VdfObject::is_empty")]
+    pub extra: VdfObject,
+    /// Entries of `items` and `default_equips` that a lenient load could not
+    /// read, shaped like the file. They are written back untouched so that
+    /// saving does not delete them.
+    #[serde(default, skip_serializing_if = "VdfObject::is_empty")]
+    pub unreadable: VdfObject,
 }
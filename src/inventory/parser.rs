@@ -1,5 +1,6 @@
+use crate::inventory::item_attribute::{AttributeValue, attribute_kept_as_text};
 use crate::inventory::models::{DefaultEquip, Inventory, Item};
-use crate::inventory::vdf::{VdfParser, VdfValue, get_string_from_obj};
+use crate::inventory::vdf::{VdfDocument, VdfObject, VdfParser, VdfValue, get_string_from_obj};
 use std::collections::HashMap;
 
 pub trait InventoryParser: Send + Sync {
@@ -8,121 +9,275 @@ pub trait InventoryParser: Send + Sync {
         &self,
         inventory: &Inventory,
     ) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Serializes `inventory` on top of the previous file contents, keeping as
+    /// much of `original` as the format allows. Fails if `original` cannot be
+    /// read, rather than dropping what it holds.
+    fn serialize_preserving(
+        &self,
+        inventory: &Inventory,
+        _original: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        self.serialize(inventory)
+    }
 }
 
 pub struct VdfInventoryParser;
 
-impl InventoryParser for VdfInventoryParser {
-    fn parse(&self, content: &str) -> Result<Inventory, Box<dyn std::error::Error + Send + Sync>> {
-        let vdf = VdfParser::parse(content)?;
-        let items_obj = match vdf.get("items") {
-            Some(VdfValue::Object(items)) => Some(items),
-            Some(_) => {
-                return Err(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "'items' section must be an object",
-                )));
-            }
-            None => None,
+/// Item keys written by `serialize_item`, in output order.
+const ITEM_FIELDS: &[&str] = &[
+    "inventory",
+    "def_index",
+    "level",
+    "quality",
+    "flags",
+    "origin",
+    "custom_name",
+    "in_use",
+    "rarity",
+    "attributes",
+    "equipped_state",
+];
+
+impl VdfInventoryParser {
+    /// Builds the value tree written to inventory.txt, with items and
+    /// attributes in ascending id order and unmodeled entries after the ones
+    /// the editor knows.
+    pub fn to_vdf(&self, inventory: &Inventory) -> VdfObject {
+        let mut items: Vec<&Item> = inventory.items.iter().collect();
+        items.sort_by_key(|item| item.id);
+
+        let unreadable = |section: &str| {
+            inventory
+                .unreadable
+                .get(section)
+                .and_then(VdfValue::as_object)
+                .cloned()
+                .unwrap_or_default()
         };
 
-        let default_equips_obj = vdf.get("default_equips").and_then(|v| v.as_object());
+        let mut items_obj = VdfObject::new();
+        for item in items {
+            items_obj.push(item.id.to_string(), VdfValue::Object(serialize_item(item)));
+        }
+        items_obj.extend(unreadable("items"));
 
-        let mut items = Vec::new();
+        let mut equips: Vec<_> = inventory.default_equips.iter().collect();
+        equips.sort_by_key(|(class_id, _)| **class_id);
 
-        if let Some(items_obj) = items_obj {
-            for (key, item_value) in items_obj {
-                if let Some(item_obj) = item_value.as_object() {
-                    let id: u64 = key.parse().map_err(|_| {
-                        Box::new(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            "Invalid item key",
-                        ))
-                    })?;
-                    items.push(parse_item(id, item_obj)?);
-                }
-            }
+        let mut default_equips_obj = VdfObject::new();
+        for (class_id, equip) in equips {
+            let mut equip_obj = VdfObject::new();
+            equip_obj.push("class_id", VdfValue::String(equip.class_id.to_string()));
+            equip_obj.push("slot_id", VdfValue::String(equip.slot_id.to_string()));
+            default_equips_obj.push(class_id.to_string(), VdfValue::Object(equip_obj));
         }
+        default_equips_obj.extend(unreadable("default_equips"));
 
-        let mut default_equips = HashMap::new();
-
-        if let Some(equips_obj) = default_equips_obj {
-            for (key, equip_value) in equips_obj {
-                if let Some(equip_obj) = equip_value.as_object() {
-                    let class_id: u32 = key.parse().map_err(|_| {
-                        Box::new(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            "Invalid class_id",
-                        ))
-                    })?;
-                    default_equips.insert(
-                        class_id,
-                        DefaultEquip {
-                            class_id: get_u32(equip_obj, "class_id")?,
-                            slot_id: get_u32(equip_obj, "slot_id")?,
-                        },
-                    );
-                }
-            }
+        let mut vdf = VdfObject::new();
+        vdf.push("items", VdfValue::Object(items_obj));
+        if !default_equips_obj.is_empty() {
+            vdf.push("default_equips", VdfValue::Object(default_equips_obj));
         }
+        vdf.extend(inventory.extra.clone());
+        vdf
+    }
 
-        Ok(Inventory {
-            items,
-            default_equips,
-        })
+    /// Reads an inventory from a value tree shaped like inventory.txt.
+    pub fn from_vdf(
+        &self,
+        vdf: &VdfObject,
+    ) -> Result<Inventory, Box<dyn std::error::Error + Send + Sync>> {
+        inventory_from_vdf(vdf, None)
+    }
+
+    /// Reads as much of a damaged inventory.txt as possible. Syntax errors and
+    /// entries that cannot be read are skipped and described in the returned
+    /// warnings instead of failing the whole file.
+    pub fn parse_lenient(&self, content: &str) -> (Inventory, Vec<String>) {
+        let (vdf, diagnostics) = VdfParser::parse_recovering(content);
+        let mut warnings: Vec<String> = diagnostics.iter().map(ToString::to_string).collect();
+
+        let inventory = inventory_from_vdf(&vdf, Some(&mut warnings)).unwrap_or_else(|e| {
+            warnings.push(e.to_string());
+            Inventory::default()
+        });
+        (inventory, warnings)
+    }
+}
+
+/// Whether a key that is missing from [`VdfInventoryParser::to_vdf`] output was
+/// removed by the editor, rather than being a key the editor does not model.
+fn is_managed_path(path: &[&str]) -> bool {
+    match path {
+        ["default_equips"] | ["items", _] | ["default_equips", _] => true,
+        ["items", _, field] => ITEM_FIELDS.contains(field),
+        ["items", _, "attributes" | "equipped_state", _] => true,
+        ["default_equips", _, "class_id" | "slot_id"] => true,
+        _ => false,
+    }
+}
+
+impl InventoryParser for VdfInventoryParser {
+    fn parse(&self, content: &str) -> Result<Inventory, Box<dyn std::error::Error + Send + Sync>> {
+        let vdf = VdfParser::parse(content)?;
+        self.from_vdf(&vdf)
     }
 
     fn serialize(
         &self,
         inventory: &Inventory,
     ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let mut items_obj = std::collections::BTreeMap::new();
+        let result = VdfParser::to_string(&VdfValue::Object(self.to_vdf(inventory)));
+        Ok(result.replace("\r\n", "\n"))
+    }
+
+    fn serialize_preserving(
+        &self,
+        inventory: &Inventory,
+        original: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let mut document = VdfDocument::parse(original).map_err(|e| {
+            format!(
+                "the file cannot be updated in place, as it does not parse: {}",
+                e
+            )
+        })?;
+
+        document.update(&self.to_vdf(inventory), &mut is_managed_path);
+        Ok(document.to_string())
+    }
+}
 
-        for item in inventory.items.iter() {
-            let key = item.id.to_string();
-            items_obj.insert(key, VdfValue::Object(serialize_item(item)));
+/// Builds an [`Inventory`] from a parsed file. With `warnings`, items and
+/// default equips that cannot be read are skipped and described there instead
+/// of failing the whole inventory.
+fn inventory_from_vdf(
+    vdf: &VdfObject,
+    mut warnings: Option<&mut Vec<String>>,
+) -> Result<Inventory, Box<dyn std::error::Error + Send + Sync>> {
+    let items_obj = match vdf.get("items") {
+        Some(VdfValue::Object(items)) => Some(items),
+        Some(_) => {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "'items' section must be an object",
+            )));
         }
+        None => None,
+    };
 
-        let mut default_equips_obj = std::collections::BTreeMap::new();
-
-        for (class_id, equip) in &inventory.default_equips {
-            let mut equip_obj = std::collections::BTreeMap::new();
-            equip_obj.insert(
-                "class_id".to_string(),
-                VdfValue::String(equip.class_id.to_string()),
-            );
-            equip_obj.insert(
-                "slot_id".to_string(),
-                VdfValue::String(equip.slot_id.to_string()),
-            );
-            default_equips_obj.insert(
-                class_id.to_string(),
-                VdfValue::Object(equip_obj.into_iter().collect()),
-            );
+    let default_equips_obj = vdf.get("default_equips").and_then(|v| v.as_object());
+
+    let mut items = Vec::new();
+    let mut unreadable_items = VdfObject::new();
+
+    if let Some(items_obj) = items_obj {
+        for (key, item_value) in items_obj {
+            if let Some(item_obj) = item_value.as_object() {
+                let mut item_warnings = Vec::new();
+                let item = key
+                    .parse()
+                    .map_err(|_| invalid_data("Invalid item key"))
+                    .and_then(|id| parse_item(id, item_obj, &mut item_warnings));
+                match item {
+                    Ok(item) => {
+                        if let Some(warnings) = warnings.as_deref_mut() {
+                            warnings.extend(item_warnings);
+                        }
+                        items.push(item);
+                    }
+                    Err(e) => {
+                        skip(&mut warnings, format!("item {}", key), e)?;
+                        unreadable_items.push(key.clone(), item_value.clone());
+                    }
+                }
+            }
         }
+    }
 
-        let mut vdf = std::collections::BTreeMap::new();
-        vdf.insert(
-            "items".to_string(),
-            VdfValue::Object(items_obj.into_iter().collect()),
-        );
-        if !inventory.default_equips.is_empty() {
-            vdf.insert(
-                "default_equips".to_string(),
-                VdfValue::Object(default_equips_obj.into_iter().collect()),
-            );
+    let mut default_equips = HashMap::new();
+    let mut unreadable_equips = VdfObject::new();
+
+    if let Some(equips_obj) = default_equips_obj {
+        for (key, equip_value) in equips_obj {
+            if let Some(equip_obj) = equip_value.as_object() {
+                let equip = key
+                    .parse::<u32>()
+                    .map_err(|_| invalid_data("Invalid class_id"))
+                    .and_then(|class_id| {
+                        Ok((
+                            class_id,
+                            DefaultEquip {
+                                class_id: get_u32(equip_obj, "class_id")?,
+                                slot_id: get_u32(equip_obj, "slot_id")?,
+                            },
+                        ))
+                    });
+                match equip {
+                    Ok((class_id, equip)) => {
+                        default_equips.insert(class_id, equip);
+                    }
+                    Err(e) => {
+                        skip(&mut warnings, format!("default equip {}", key), e)?;
+                        unreadable_equips.push(key.clone(), equip_value.clone());
+                    }
+                }
+            }
         }
+    }
 
-        let mut result = VdfParser::to_string(&VdfValue::Object(vdf.into_iter().collect()));
-        result = result.replace("\r\n", "\n");
+    let extra = vdf
+        .iter()
+        .filter(|(key, _)| !matches!(key.as_str(), "items" | "default_equips"))
+        .cloned()
+        .collect();
 
-        Ok(result)
+    let mut unreadable = VdfObject::new();
+    if !unreadable_items.is_empty() {
+        unreadable.push("items", VdfValue::Object(unreadable_items));
+    }
+    if !unreadable_equips.is_empty() {
+        unreadable.push("default_equips", VdfValue::Object(unreadable_equips));
+    }
+
+    Ok(Inventory {
+        items,
+        default_equips,
+        extra,
+        unreadable,
+    })
+}
+
+/// Records a skipped entry in `warnings`, or fails with `error` when there is
+/// nowhere to record it.
+fn skip(
+    warnings: &mut Option<&mut Vec<String>>,
+    what: String,
+    error: Box<dyn std::error::Error + Send + Sync>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match warnings {
+        Some(warnings) => {
+            warnings.push(format!("Skipped {}: {}", what, error));
+            Ok(())
+        }
+        None => Err(error),
     }
 }
 
+fn invalid_data(message: &str) -> Box<dyn std::error::Error + Send + Sync> {
+    Box::new(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        message.to_string(),
+    ))
+}
+
+/// Reads one item. Attributes that do not fit their type are kept as text and
+/// described in `warnings`.
 fn parse_item(
     id: u64,
-    obj: &HashMap<String, VdfValue>,
+    obj: &VdfObject,
+    warnings: &mut Vec<String>,
 ) -> Result<Item, Box<dyn std::error::Error + Send + Sync>> {
     let mut item = Item {
         id,
@@ -137,9 +292,20 @@ fn parse_item(
         custom_name: get_string_from_obj(obj, "custom_name"),
         attributes: HashMap::new(),
         equipped_state: HashMap::new(),
+        extra: VdfObject::new(),
     };
 
-    if let Some(attrs_obj) = obj.get("attributes").and_then(|v| v.as_object()) {
+    for (key, value) in obj {
+        let section = matches!(key.as_str(), "attributes" | "equipped_state");
+        if !ITEM_FIELDS.contains(&key.as_str()) || (section && value.as_object().is_none()) {
+            item.extra.push(key.clone(), value.clone());
+        }
+    }
+
+    // Repeated blocks are merged, later values winning, so that saving does
+    // not drop the entries of all but the last.
+    let mut extra_attributes = VdfObject::new();
+    for attrs_obj in obj.objects("attributes") {
         for (key, value) in attrs_obj {
             let id: u32 = key.parse().map_err(|_| {
                 Box::new(std::io::Error::new(
@@ -148,12 +314,23 @@ fn parse_item(
                 ))
             })?;
             if let Some(s) = value.as_string() {
-                item.attributes.insert(id, s.to_string());
+                let value = AttributeValue::for_attribute(id, s).unwrap_or_else(|e| {
+                    warnings.push(attribute_kept_as_text(item.id, id, &e));
+                    AttributeValue::string(s)
+                });
+                item.attributes.insert(id, value);
+            } else {
+                extra_attributes.push(key.clone(), value.clone());
             }
         }
     }
+    if !extra_attributes.is_empty() {
+        item.extra
+            .push("attributes", VdfValue::Object(extra_attributes));
+    }
 
-    if let Some(equips_obj) = obj.get("equipped_state").and_then(|v| v.as_object()) {
+    let mut extra_equips = VdfObject::new();
+    for equips_obj in obj.objects("equipped_state") {
         for (key, value) in equips_obj {
             let id: u32 = key.parse().map_err(|_| {
                 Box::new(std::io::Error::new(
@@ -163,83 +340,72 @@ fn parse_item(
             })?;
             if let Some(s) = value.as_string() {
                 item.equipped_state.insert(id, s.to_string());
+            } else {
+                extra_equips.push(key.clone(), value.clone());
             }
         }
     }
+    if !extra_equips.is_empty() {
+        item.extra
+            .push("equipped_state", VdfValue::Object(extra_equips));
+    }
 
     Ok(item)
 }
 
-fn serialize_item(item: &Item) -> HashMap<String, VdfValue> {
-    let mut obj = std::collections::BTreeMap::new();
-
-    obj.insert(
-        "inventory".to_string(),
-        VdfValue::String(item.inventory.to_string()),
-    );
-    obj.insert(
-        "def_index".to_string(),
-        VdfValue::String(item.def_index.to_string()),
-    );
-    obj.insert(
-        "level".to_string(),
-        VdfValue::String(item.level.to_string()),
-    );
-    obj.insert(
-        "quality".to_string(),
-        VdfValue::String(item.quality.to_string()),
-    );
-    obj.insert(
-        "flags".to_string(),
-        VdfValue::String(item.flags.to_string()),
-    );
-    obj.insert(
-        "origin".to_string(),
-        VdfValue::String(item.origin.to_string()),
-    );
+fn serialize_item(item: &Item) -> VdfObject {
+    let mut obj = VdfObject::new();
+
+    obj.push("inventory", VdfValue::String(item.inventory.to_string()));
+    obj.push("def_index", VdfValue::String(item.def_index.to_string()));
+    obj.push("level", VdfValue::String(item.level.to_string()));
+    obj.push("quality", VdfValue::String(item.quality.to_string()));
+    obj.push("flags", VdfValue::String(item.flags.to_string()));
+    obj.push("origin", VdfValue::String(item.origin.to_string()));
 
     if let Some(name) = &item.custom_name {
-        obj.insert("custom_name".to_string(), VdfValue::String(name.clone()));
-    }
-
-    obj.insert(
-        "in_use".to_string(),
-        VdfValue::String(item.in_use.to_string()),
-    );
-    obj.insert(
-        "rarity".to_string(),
-        VdfValue::String(item.rarity.to_string()),
-    );
-
-    if !item.attributes.is_empty() {
-        let mut attrs = std::collections::BTreeMap::new();
-        for (key, value) in &item.attributes {
-            attrs.insert(key.to_string(), VdfValue::String(value.clone()));
-        }
-        obj.insert(
-            "attributes".to_string(),
-            VdfValue::Object(attrs.into_iter().collect()),
-        );
+        obj.push("custom_name", VdfValue::String(name.clone()));
+    }
+
+    obj.push("in_use", VdfValue::String(item.in_use.to_string()));
+    obj.push("rarity", VdfValue::String(item.rarity.to_string()));
+
+    let extra_section = |key: &str| item.extra.get(key).and_then(VdfValue::as_object);
+
+    if !item.attributes.is_empty() || extra_section("attributes").is_some() {
+        let mut attrs: Vec<_> = item.attributes.iter().collect();
+        attrs.sort_by_key(|(key, _)| **key);
+        let mut attrs: VdfObject = attrs
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), VdfValue::String(value.to_string())))
+            .collect();
+        attrs.extend(extra_section("attributes").cloned().unwrap_or_default());
+        obj.push("attributes", VdfValue::Object(attrs));
     }
 
-    if !item.equipped_state.is_empty() {
-        let mut equips = std::collections::BTreeMap::new();
-        for (key, value) in &item.equipped_state {
-            equips.insert(key.to_string(), VdfValue::String(value.clone()));
+    if !item.equipped_state.is_empty() || extra_section("equipped_state").is_some() {
+        let mut equips: Vec<_> = item.equipped_state.iter().collect();
+        equips.sort_by_key(|(key, _)| **key);
+        let mut equips: VdfObject = equips
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), VdfValue::String(value.to_string())))
+            .collect();
+        equips.extend(extra_section("equipped_state").cloned().unwrap_or_default());
+        obj.push("equipped_state", VdfValue::Object(equips));
+    }
+
+    for (key, value) in &item.extra {
+        let merged =
+            matches!(key.as_str(), "attributes" | "equipped_state") && value.as_object().is_some();
+        if !merged {
+            obj.push(key.clone(), value.clone());
         }
-        obj.insert(
-            "equipped_state".to_string(),
-            VdfValue::Object(equips.into_iter().collect()),
-        );
     }
 
-    obj.into_iter().collect()
+    obj
 }
 
-fn get_u64(
-    obj: &HashMap<String, VdfValue>,
-    key: &str,
-) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+fn get_u64(obj: &VdfObject, key: &str) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
     let value: &str = obj.get(key).and_then(|v| v.as_string()).ok_or_else(
         || -> Box<dyn std::error::Error + Send + Sync> {
             Box::new(std::io::Error::new(
@@ -258,10 +424,7 @@ fn get_u64(
         })
 }
 
-fn get_u32(
-    obj: &HashMap<String, VdfValue>,
-    key: &str,
-) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
+fn get_u32(obj: &VdfObject, key: &str) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
     let value: &str = obj.get(key).and_then(|v| v.as_string()).ok_or_else(
         || -> Box<dyn std::error::Error + Send + Sync> {
             Box::new(std::io::Error::new(
@@ -283,6 +446,8 @@ fn get_u32(
 #[cfg(test)]
 mod tests {
     use super::{InventoryParser, VdfInventoryParser};
+    use crate::inventory::item_attribute::AttributeType;
+    use crate::inventory::vdf::VdfParser;
 
     #[test]
     fn parses_empty_file_as_empty_inventory() {
@@ -350,6 +515,324 @@ mod tests {
         assert_eq!(inventory.items[0].inventory, 7);
     }
 
+    #[test]
+    fn round_trips_multibyte_custom_names() {
+        let content = r#"
+            "items"
+            {
+                "42"
+                {
+                    "inventory" "7"
+                    "def_index" "7"
+                    "level" "1"
+                    "quality" "4"
+                    "flags" "0"
+                    "origin" "0"
+                    "custom_name" "龙狙 \"火神\" 🔥"
+                    "in_use" "0"
+                    "rarity" "6"
+                }
+            }
+        "#;
+
+        let inventory = VdfInventoryParser.parse(content).unwrap();
+        assert_eq!(
+            inventory.items[0].custom_name.as_deref(),
+            Some("龙狙 \"火神\" 🔥")
+        );
+
+        let output = VdfInventoryParser.serialize(&inventory).unwrap();
+        assert_eq!(VdfInventoryParser.parse(&output).unwrap(), inventory);
+    }
+
+    #[test]
+    fn serialize_preserving_keeps_annotations_and_unknown_keys() {
+        let original = concat!(
+            "// team loadout\n",
+            "\"items\"\n",
+            "{\n",
+            "\t\"42\"\n",
+            "\t{\n",
+            "\t\t\"inventory\"\t\t\"7\"\n",
+            "\t\t\"def_index\"\t\t\"507\"\n",
+            "\t\t\"level\"\t\t\"1\"\n",
+            "\t\t\"quality\"\t\t\"3\"\n",
+            "\t\t\"flags\"\t\t\"0\"\n",
+            "\t\t\"origin\"\t\t\"24\"\n",
+            "\t\t\"in_use\"\t\t\"0\"\n",
+            "\t\t\"rarity\"\t\t\"6\" // knife\n",
+            "\t\t\"future_field\"\t\t\"x\"\n",
+            "\t}\n",
+            "\t\"43\"\n",
+            "\t{\n",
+            "\t\t\"inventory\"\t\t\"8\"\n",
+            "\t\t\"def_index\"\t\t\"7\"\n",
+            "\t\t\"level\"\t\t\"1\"\n",
+            "\t\t\"quality\"\t\t\"4\"\n",
+            "\t\t\"flags\"\t\t\"0\"\n",
+            "\t\t\"origin\"\t\t\"0\"\n",
+            "\t\t\"in_use\"\t\t\"0\"\n",
+            "\t\t\"rarity\"\t\t\"1\"\n",
+            "\t}\n",
+            "}\n",
+        );
+
+        let mut inventory = VdfInventoryParser.parse(original).unwrap();
+        assert_eq!(
+            VdfInventoryParser
+                .serialize_preserving(&inventory, original)
+                .unwrap(),
+            original
+        );
+
+        inventory.items.retain(|item| item.id != 43);
+        inventory.items[0].level = 2;
+        let output = VdfInventoryParser
+            .serialize_preserving(&inventory, original)
+            .unwrap();
+
+        assert!(output.starts_with("// team loadout\n"));
+        assert!(output.contains("\"level\"\t\t\"2\""));
+        assert!(output.contains("\"rarity\"\t\t\"6\" // knife"));
+        assert!(output.contains("\"future_field\"\t\t\"x\""));
+        assert!(!output.contains("\"43\""));
+        assert_eq!(VdfInventoryParser.parse(&output).unwrap(), inventory);
+    }
+
+    #[test]
+    fn serialize_preserving_refuses_a_file_that_does_not_parse() {
+        let original = "\"items\"\n{\n\t\"42\"\n\t{\n";
+        let error = VdfInventoryParser
+            .serialize_preserving(&Default::default(), original)
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("the file cannot be updated in place")
+        );
+    }
+
+    #[test]
+    fn parse_lenient_skips_unreadable_items() {
+        let content = r#"
+            "items"
+            {
+                "42"
+                {
+                    "inventory" "7"
+                    "def_index" "507"
+                    "level" "1"
+                    "quality" "3"
+                    "flags" "0"
+                    "origin" "24"
+                    "in_use" "0"
+                    "rarity" "6"
+                }
+                "43"
+                {
+                    "inventory" "8"
+                    "def_index" "seven"
+                }
+            "#;
+
+        assert!(VdfInventoryParser.parse(content).is_err());
+
+        let (inventory, warnings) = VdfInventoryParser.parse_lenient(content);
+
+        assert_eq!(inventory.items.len(), 1);
+        assert_eq!(inventory.items[0].id, 42);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("Expected '}'"));
+        assert!(warnings[1].starts_with("Skipped item 43"));
+    }
+
+    #[test]
+    fn saving_keeps_items_skipped_by_parse_lenient() {
+        let original = concat!(
+            "\"items\"\n",
+            "{\n",
+            "\t\"42\"\n",
+            "\t{\n",
+            "\t\t\"inventory\"\t\t\"7\"\n",
+            "\t\t\"def_index\"\t\t\"507\"\n",
+            "\t\t\"level\"\t\t\"1\"\n",
+            "\t\t\"quality\"\t\t\"3\"\n",
+            "\t\t\"flags\"\t\t\"0\"\n",
+            "\t\t\"origin\"\t\t\"24\"\n",
+            "\t\t\"in_use\"\t\t\"0\"\n",
+            "\t\t\"rarity\"\t\t\"6\"\n",
+            "\t}\n",
+            "\t\"43\"\n",
+            "\t{\n",
+            "\t\t\"inventory\"\t\t\"8\"\n",
+            "\t\t\"def_index\"\t\t\"seven\"\n",
+            "\t}\n",
+            "}\n",
+            "\"default_equips\"\n",
+            "{\n",
+            "\t\"60\"\n",
+            "\t{\n",
+            "\t\t\"class_id\"\t\t\"3\"\n",
+            "\t}\n",
+            "}\n",
+        );
+
+        let (mut inventory, warnings) = VdfInventoryParser.parse_lenient(original);
+        assert_eq!(warnings.len(), 2);
+        assert!(inventory.has_item_id(43));
+
+        inventory.items[0].level = 2;
+        let output = VdfInventoryParser
+            .serialize_preserving(&inventory, original)
+            .unwrap();
+
+        assert!(output.contains("\"level\"\t\t\"2\""));
+        assert!(output.contains("\"43\"\n\t{\n\t\t\"inventory\"\t\t\"8\""));
+        assert!(output.contains("\"def_index\"\t\t\"seven\""));
+        assert!(output.contains("\"60\""));
+        assert_eq!(output.replace("\"2\"", "\"1\""), original);
+        assert!(
+            VdfInventoryParser
+                .serialize(&inventory)
+                .unwrap()
+                .contains("\"seven\"")
+        );
+    }
+
+    #[test]
+    fn types_attributes_and_keeps_their_text() {
+        let item = |wear: &str| {
+            format!(
+                r#""items" {{ "42" {{ "inventory" "7" "def_index" "7" "level" "1" "quality" "4"
+                "flags" "0" "origin" "0" "in_use" "0" "rarity" "1"
+                "attributes" {{ "6" "44.000000" "8" "{}" "9999" "anything" }} }} }}"#,
+                wear
+            )
+        };
+
+        let inventory = VdfInventoryParser.parse(&item("0.100000")).unwrap();
+        let attributes = &inventory.items[0].attributes;
+        assert_eq!(attributes[&6].as_u32(), Some(44));
+        assert_eq!(attributes[&8].as_f32(), Some(0.1));
+        assert_eq!(attributes[&9999].as_str(), "anything");
+
+        let output = VdfInventoryParser.serialize(&inventory).unwrap();
+        assert!(output.contains("\"6\"\t\t\"44.000000\""));
+        assert!(output.contains("\"8\"\t\t\"0.100000\""));
+    }
+
+    #[test]
+    fn merges_repeated_attribute_and_equipped_state_blocks() {
+        let content = r#""items" { "42" { "inventory" "7" "def_index" "7" "level" "1" "quality" "4"
+            "flags" "0" "origin" "0" "in_use" "0" "rarity" "1"
+            "attributes" { "6" "44" "8" "0.2" }
+            "equipped_state" { "2" "0" }
+            "attributes" { "7" "12" "8" "0.3" }
+            "equipped_state" { "3" "0" } } }"#;
+
+        let inventory = VdfInventoryParser.parse(content).unwrap();
+        let item = &inventory.items[0];
+        let mut attributes: Vec<(u32, &str)> = item
+            .attributes
+            .iter()
+            .map(|(id, value)| (*id, value.as_str()))
+            .collect();
+        attributes.sort_unstable();
+        assert_eq!(attributes, [(6, "44"), (7, "12"), (8, "0.3")]);
+        assert_eq!(item.equipped_state.len(), 2);
+        assert!(item.extra.is_empty());
+
+        let output = VdfInventoryParser.serialize(&inventory).unwrap();
+        assert_eq!(VdfInventoryParser.parse(&output).unwrap(), inventory);
+        let preserved = VdfInventoryParser
+            .serialize_preserving(&inventory, content)
+            .unwrap();
+        assert_eq!(VdfInventoryParser.parse(&preserved).unwrap(), inventory);
+    }
+
+    #[test]
+    fn keeps_attributes_that_do_not_fit_their_type_as_text() {
+        let content = r#""items" { "42" { "inventory" "7" "def_index" "7" "level" "1" "quality" "4"
+            "flags" "0" "origin" "0" "in_use" "0" "rarity" "1"
+            "attributes" { "6" "44" "8" "nan" } } }"#;
+
+        let inventory = VdfInventoryParser.parse(content).unwrap();
+        let wear = &inventory.items[0].attributes[&8];
+        assert_eq!(wear.value_type(), AttributeType::String);
+        assert_eq!(wear.as_str(), "nan");
+
+        let (lenient, warnings) = VdfInventoryParser.parse_lenient(content);
+        assert_eq!(lenient, inventory);
+        assert_eq!(
+            warnings,
+            ["Item 42 attribute 8: 'nan' is not a number; kept as text"]
+        );
+        assert!(
+            VdfInventoryParser
+                .serialize(&inventory)
+                .unwrap()
+                .contains("\"8\"\t\t\"nan\"")
+        );
+    }
+
+    #[test]
+    fn keeps_unfamiliar_fields_through_a_fresh_save() {
+        let content = r#"
+            "items"
+            {
+                "42"
+                {
+                    "inventory" "7"
+                    "def_index" "7"
+                    "level" "1"
+                    "quality" "4"
+                    "flags" "0"
+                    "origin" "0"
+                    "in_use" "0"
+                    "rarity" "1"
+                    "attributes"
+                    {
+                        "6" "44"
+                        "300" { "kind" "keychain" "offset" "0 1 2" }
+                    }
+                    "equipped_state" { "2" "1" "3" { "slot" "0" } }
+                    "trade_lock" "1700000000"
+                    "history" { "1" "drop" }
+                }
+            }
+            "loadout_presets" { "1" { "name" "A" } }
+        "#;
+
+        let inventory = VdfInventoryParser.parse(content).unwrap();
+        let item = &inventory.items[0];
+        assert_eq!(item.attributes.len(), 1);
+        assert_eq!(item.equipped_state.len(), 1);
+        let extra_keys: Vec<&str> = item.extra.keys().map(String::as_str).collect();
+        assert_eq!(
+            extra_keys,
+            ["trade_lock", "history", "attributes", "equipped_state"]
+        );
+        assert!(inventory.extra.contains_key("loadout_presets"));
+
+        let output = VdfInventoryParser.serialize(&inventory).unwrap();
+        let written = VdfParser::parse(&output).unwrap();
+        assert_eq!(
+            written.select_str("items/42/attributes/300/offset"),
+            Some("0 1 2")
+        );
+        assert_eq!(
+            written.select_str("items/42/equipped_state/3/slot"),
+            Some("0")
+        );
+        assert_eq!(
+            written.select_str("items/42/trade_lock"),
+            Some("1700000000")
+        );
+        assert_eq!(written.select_str("loadout_presets/1/name"), Some("A"));
+        assert_eq!(written, VdfParser::parse(content).unwrap());
+        assert_eq!(VdfInventoryParser.parse(&output).unwrap(), inventory);
+    }
+
     #[test]
     fn rejects_non_object_items_section() {
         let error = VdfInventoryParser
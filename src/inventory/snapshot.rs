@@ -0,0 +1,253 @@
+//! A history of inventory states.
+//!
+//! Every snapshot is one gzip-compressed JSON file holding the message it was
+//! taken with and the whole [`Inventory`], named after the time it was taken.
+//! Unlike backups, which copy inventory.txt byte for byte, snapshots keep the
+//! editor's model of the inventory, so any two of them (or a snapshot and the
+//! inventory being edited) can be compared with [`InventoryLoader::diff`].
+
+use crate::core::game_dir::editor_dir;
+use crate::core::write_atomic;
+use crate::inventory::loader::InventoryLoader;
+use crate::inventory::models::Inventory;
+use crate::inventory::vdf::VdfPatch;
+use chrono::{Local, NaiveDateTime};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S%.3f";
+const EXTENSION: &str = ".json.gz";
+
+/// The contents of a snapshot file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub message: String,
+    pub inventory: Inventory,
+}
+
+/// A snapshot in the store, without its inventory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotEntry {
+    pub path: PathBuf,
+    pub created: NaiveDateTime,
+    pub message: String,
+    pub item_count: usize,
+}
+
+/// Snapshots of the inventory, kept in one directory. Only the newest `keep`
+/// snapshots are retained.
+#[derive(Debug, Clone)]
+pub struct SnapshotStore {
+    dir: PathBuf,
+    keep: usize,
+}
+
+impl SnapshotStore {
+    pub fn new(dir: impl Into<PathBuf>, keep: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            keep,
+        }
+    }
+
+    /// The store under `csgo_gc/editor/snapshots`.
+    pub fn in_editor_dir(keep: usize) -> Self {
+        Self::new(editor_dir().join("snapshots"), keep)
+    }
+
+    /// Stores `inventory` with `message`, unless snapshots are disabled or the
+    /// newest snapshot already holds the same inventory.
+    pub fn record(
+        &self,
+        inventory: &Inventory,
+        message: &str,
+    ) -> std::io::Result<Option<SnapshotEntry>> {
+        if self.keep == 0 {
+            return Ok(None);
+        }
+        if let Some(latest) = self.list().first()
+            && self
+                .load(latest)
+                .is_ok_and(|snapshot| snapshot.inventory == *inventory)
+        {
+            return Ok(None);
+        }
+
+        let snapshot = Snapshot {
+            message: message.to_string(),
+            inventory: inventory.clone(),
+        };
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        serde_json::to_writer(&mut encoder, &snapshot)?;
+        let compressed = encoder.finish()?;
+
+        fs::create_dir_all(&self.dir)?;
+        let created = Local::now().naive_local();
+        let path = self
+            .dir
+            .join(format!("{}{}", created.format(TIMESTAMP_FORMAT), EXTENSION));
+        write_atomic(&path, compressed)?;
+
+        for old in self.list().iter().skip(self.keep) {
+            let _ = fs::remove_file(&old.path);
+        }
+
+        Ok(Some(SnapshotEntry {
+            path,
+            created,
+            message: snapshot.message,
+            item_count: inventory.items.len(),
+        }))
+    }
+
+    /// Every snapshot in the store, newest first. Files that cannot be read
+    /// are left out.
+    pub fn list(&self) -> Vec<SnapshotEntry> {
+        let Ok(read_dir) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut entries: Vec<SnapshotEntry> = read_dir
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                let created = parse_snapshot_name(&path)?;
+                let snapshot = read_snapshot(&path).ok()?;
+                Some(SnapshotEntry {
+                    path,
+                    created,
+                    message: snapshot.message,
+                    item_count: snapshot.inventory.items.len(),
+                })
+            })
+            .collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.created));
+        entries
+    }
+
+    pub fn load(&self, entry: &SnapshotEntry) -> std::io::Result<Snapshot> {
+        read_snapshot(&entry.path)
+    }
+
+    /// The changes that would turn the inventory in `entry` into `current`.
+    pub fn diff(&self, entry: &SnapshotEntry, current: &Inventory) -> std::io::Result<VdfPatch> {
+        let snapshot = self.load(entry)?;
+        Ok(InventoryLoader::diff(&snapshot.inventory, current))
+    }
+}
+
+fn read_snapshot(path: &Path) -> std::io::Result<Snapshot> {
+    let mut json = String::new();
+    GzDecoder::new(fs::File::open(path)?).read_to_string(&mut json)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Reads `<timestamp>.json.gz`.
+fn parse_snapshot_name(path: &Path) -> Option<NaiveDateTime> {
+    let name = path.file_name()?.to_str()?.strip_suffix(EXTENSION)?;
+    NaiveDateTime::parse_from_str(name, TIMESTAMP_FORMAT).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SnapshotStore;
+    use crate::inventory::models::{Inventory, Item};
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    /// A store keeping two snapshots in an empty directory of the test's own.
+    fn test_store(name: &str) -> (SnapshotStore, PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "csgo-gc-snapshot-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        (SnapshotStore::new(&dir, 2), dir)
+    }
+
+    /// AK-47s with ids 1 to `count`.
+    fn inventory(count: u64) -> Inventory {
+        Inventory {
+            items: (1..=count)
+                .map(|id| Item {
+                    id,
+                    def_index: 7,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Records inventories of one to `count` items, waiting in between so
+    /// that the snapshots get distinct timestamps.
+    fn record_growing(store: &SnapshotStore, count: u64) {
+        for id in 1..=count {
+            store
+                .record(&inventory(id), &format!("add {}", id))
+                .unwrap()
+                .unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn skips_an_unchanged_inventory() {
+        let (store, dir) = test_store("unchanged");
+        assert!(store.list().is_empty());
+
+        assert!(store.record(&inventory(1), "add 1").unwrap().is_some());
+        assert!(store.record(&inventory(1), "again").unwrap().is_none());
+        assert_eq!(store.list().len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_only_the_newest_snapshots() {
+        let (store, dir) = test_store("rotate");
+
+        record_growing(&store, 3);
+        let snapshots = store.list();
+        let messages: Vec<&str> = snapshots.iter().map(|s| s.message.as_str()).collect();
+        assert_eq!(messages, ["add 3", "add 2"]);
+        assert_eq!(snapshots[1].item_count, 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn loads_the_recorded_inventory() {
+        let (store, dir) = test_store("load");
+
+        record_growing(&store, 2);
+        let older = store.load(&store.list()[1]).unwrap().inventory;
+        assert_eq!(older, inventory(1));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn diffs_a_snapshot_against_the_current_inventory() {
+        let (store, dir) = test_store("diff");
+
+        record_growing(&store, 2);
+        let lines: Vec<String> = store
+            .diff(&store.list()[1], &inventory(2))
+            .unwrap()
+            .changes
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(lines, ["+ items > 2: { 8 entries }"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,37 @@
+//! inventory.txt fixtures shared by the inventory tests.
+
+use crate::inventory::loader::InventoryLoader;
+use crate::inventory::models::Inventory;
+
+/// An item as csgo_gc writes it to inventory.txt, with `attributes` as
+/// `(attribute id, value)` pairs.
+pub(crate) fn item(id: u64, def_index: u32, rarity: u32, attributes: &[(u32, &str)]) -> String {
+    let attributes: String = attributes
+        .iter()
+        .map(|(attribute, value)| format!("\t\t\t\"{}\"\t\t\"{}\"\n", attribute, value))
+        .collect();
+    format!(
+        concat!(
+            "\t\"{id}\"\n\t{{\n",
+            "\t\t\"inventory\"\t\t\"{id}\"\n\t\t\"def_index\"\t\t\"{def_index}\"\n",
+            "\t\t\"level\"\t\t\"1\"\n\t\t\"quality\"\t\t\"4\"\n\t\t\"flags\"\t\t\"0\"\n",
+            "\t\t\"origin\"\t\t\"8\"\n\t\t\"in_use\"\t\t\"0\"\n\t\t\"rarity\"\t\t\"{rarity}\"\n",
+            "\t\t\"attributes\"\n\t\t{{\n{attributes}\t\t}}\n",
+            "\t}}\n",
+        ),
+        id = id,
+        def_index = def_index,
+        rarity = rarity,
+        attributes = attributes
+    )
+}
+
+/// inventory.txt text holding `items`.
+pub(crate) fn inventory_text(items: &[String]) -> String {
+    format!("\"items\"\n{{\n{}}}\n", items.concat())
+}
+
+/// `items` as the editor loads them from inventory.txt.
+pub(crate) fn inventory(items: &[String]) -> Inventory {
+    InventoryLoader::parse_from_str(&inventory_text(items)).unwrap()
+}
@@ -0,0 +1,400 @@
+//! Trade-up contracts.
+//!
+//! Ten skins of one rarity are traded for one skin of the next rarity up,
+//! drawn from the collections of the inputs: each input puts a tenth of the
+//! chance on its own collection, shared equally among that collection's
+//! skins of the next rarity. The output's wear is the average wear of the
+//! inputs mapped onto the wear range of the skin it turns out to be.
+
+use crate::inventory::case_opening::CaseRng;
+use crate::inventory::edit::InventoryEdit;
+use crate::inventory::item_attribute::{AttributeValue, ItemAttribute};
+use crate::inventory::items_game::ItemsGame;
+use crate::inventory::models::{Inventory, Item};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// How many items a contract takes.
+pub const TRADE_UP_SIZE: usize = 10;
+
+/// `origin` of items received from a trade-up contract.
+const ORIGIN_CRAFTED: u32 = 4;
+
+/// A skin a contract can give.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeUpOutcome {
+    pub def_index: u32,
+    pub paint_index: u32,
+    pub rarity: u32,
+    /// Key of the collection in `item_sets`.
+    pub collection: String,
+    /// From 0 to 1.
+    pub chance: f64,
+    pub wear: f32,
+}
+
+/// A checked contract and what it can give.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeUp {
+    pub inputs: Vec<u64>,
+    pub rarity: u32,
+    pub stattrak: bool,
+    /// The quality the output gets, that of the inputs.
+    pub quality: u32,
+    /// Sorted by collection, then item definition and paint kit.
+    pub outcomes: Vec<TradeUpOutcome>,
+}
+
+impl TradeUp {
+    /// Checks that `item_ids` make a valid contract and works out its
+    /// outcomes.
+    pub fn plan(
+        inventory: &Inventory,
+        items_game: &ItemsGame,
+        item_ids: &[u64],
+    ) -> Result<Self, String> {
+        if item_ids.len() != TRADE_UP_SIZE {
+            return Err(format!(
+                "A trade-up takes {} items, not {}",
+                TRADE_UP_SIZE,
+                item_ids.len()
+            ));
+        }
+
+        let mut seen = HashSet::new();
+        if let Some(item_id) = item_ids.iter().find(|item_id| !seen.insert(**item_id)) {
+            return Err(format!(
+                "Item {} is in the contract more than once",
+                item_id
+            ));
+        }
+
+        let inputs = item_ids
+            .iter()
+            .map(|item_id| {
+                inventory
+                    .items
+                    .iter()
+                    .find(|item| item.id == *item_id)
+                    .ok_or_else(|| format!("Item with id {} not found", item_id))
+            })
+            .collect::<Result<Vec<&Item>, String>>()?;
+
+        let first = inputs[0];
+        let stattrak = is_stattrak(first);
+        let souvenir = items_game
+            .qualities
+            .get("tournament")
+            .map_or(12, |quality| quality.value);
+        let mut wear_sum = 0.0;
+        let mut collections: Vec<&str> = Vec::new();
+        for item in &inputs {
+            if item.rarity != first.rarity {
+                return Err(format!(
+                    "Item {} has rarity {} instead of {}",
+                    item.id, item.rarity, first.rarity
+                ));
+            }
+            if is_stattrak(item) != stattrak {
+                return Err("StatTrak and normal items cannot be mixed".to_string());
+            }
+            if item.quality == souvenir {
+                return Err(format!("Item {} is a souvenir", item.id));
+            }
+
+            let (Some(paint_index), Some(wear)) = (
+                attribute(item, ItemAttribute::SkinPaintIndex).and_then(AttributeValue::as_u32),
+                attribute(item, ItemAttribute::SkinPaintWear).and_then(AttributeValue::as_f32),
+            ) else {
+                return Err(format!("Item {} is not a skin", item.id));
+            };
+            let collection = items_game
+                .collection_of(item.def_index, paint_index)
+                .ok_or_else(|| format!("Item {} is not in a collection", item.id))?;
+            wear_sum += wear;
+            collections.push(collection);
+        }
+
+        let rarity = first.rarity + 1;
+        let average_wear = wear_sum / TRADE_UP_SIZE as f32;
+        let mut chances: BTreeMap<(&str, u32, u32), f64> = BTreeMap::new();
+        let mut pools: HashMap<&str, Vec<(u32, u32)>> = HashMap::new();
+        for collection in collections {
+            let pool = pools.entry(collection).or_insert_with(|| {
+                items_game.item_sets[collection]
+                    .items
+                    .iter()
+                    .copied()
+                    .filter(|(_, paint_index)| {
+                        items_game.get_paint_kit_rarity(*paint_index) == Some(rarity)
+                    })
+                    .collect()
+            });
+            if pool.is_empty() {
+                return Err(format!(
+                    "Collection {} has no skins of rarity {}",
+                    collection, rarity
+                ));
+            }
+            let chance = 1.0 / (TRADE_UP_SIZE * pool.len()) as f64;
+            for (def_index, paint_index) in pool.iter() {
+                *chances
+                    .entry((collection, *def_index, *paint_index))
+                    .or_default() += chance;
+            }
+        }
+
+        let outcomes = chances
+            .into_iter()
+            .map(|((collection, def_index, paint_index), chance)| {
+                let (min_wear, max_wear) = items_game.get_paint_kit_wear_range(paint_index);
+                TradeUpOutcome {
+                    def_index,
+                    paint_index,
+                    rarity,
+                    collection: collection.to_string(),
+                    chance,
+                    wear: min_wear + average_wear * (max_wear - min_wear),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            inputs: item_ids.to_vec(),
+            rarity: first.rarity,
+            stattrak,
+            quality: first.quality,
+            outcomes,
+        })
+    }
+
+    /// Picks an outcome by its chance.
+    pub fn roll(&self, rng: &mut CaseRng) -> &TradeUpOutcome {
+        let mut roll = rng.next_f64();
+        self.outcomes
+            .iter()
+            .find(|outcome| {
+                let hit = roll < outcome.chance;
+                roll -= outcome.chance;
+                hit
+            })
+            .unwrap_or_else(|| self.outcomes.last().expect("a plan has outcomes"))
+    }
+
+    /// The edit that takes the inputs and adds `outcome` as item `item_id`
+    /// at inventory position `position`.
+    pub fn execute(
+        &self,
+        outcome: &TradeUpOutcome,
+        item_id: u64,
+        position: u64,
+        rng: &mut CaseRng,
+    ) -> InventoryEdit {
+        let mut attributes = HashMap::new();
+        attributes.insert(
+            ItemAttribute::SkinPaintIndex.id(),
+            AttributeValue::float(outcome.paint_index as f32),
+        );
+        attributes.insert(
+            ItemAttribute::SkinPaintSeed.id(),
+            AttributeValue::float(rng.below(1001) as f32),
+        );
+        attributes.insert(
+            ItemAttribute::SkinPaintWear.id(),
+            AttributeValue::float(outcome.wear),
+        );
+        if self.stattrak {
+            attributes.insert(ItemAttribute::StatTrakCount.id(), AttributeValue::uint32(0));
+            attributes.insert(ItemAttribute::StatTrakType.id(), AttributeValue::uint32(0));
+        }
+
+        let mut edits: Vec<InventoryEdit> = self
+            .inputs
+            .iter()
+            .map(|item_id| InventoryEdit::DeleteItem(*item_id))
+            .collect();
+        edits.push(InventoryEdit::AddItem(Item {
+            id: item_id,
+            inventory: position,
+            def_index: outcome.def_index,
+            level: 1,
+            quality: self.quality,
+            origin: ORIGIN_CRAFTED,
+            rarity: outcome.rarity,
+            attributes,
+            ..Default::default()
+        }));
+
+        InventoryEdit::Batch {
+            description: format!("Trade up {} items", self.inputs.len()),
+            edits,
+        }
+    }
+}
+
+fn attribute(item: &Item, attribute: ItemAttribute) -> Option<&AttributeValue> {
+    item.attributes.get(&attribute.id())
+}
+
+fn is_stattrak(item: &Item) -> bool {
+    attribute(item, ItemAttribute::StatTrakCount).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TradeUp;
+    use crate::inventory::case_opening::CaseRng;
+    use crate::inventory::items_game::ItemsGame;
+    use crate::inventory::items_game_loader::ItemsGameLoader;
+    use crate::inventory::models::Inventory;
+    use crate::inventory::test_fixtures::{self, item};
+
+    /// Two collections: Dust with two AK-47 and an AWP skin, Aztec with an
+    /// AWP and an AK-47 skin.
+    fn items_game() -> ItemsGame {
+        ItemsGameLoader::parse_from_str(
+            r##"
+            "items_game"
+            {
+                "rarities" { "rare" { "value" "3" } "mythical" { "value" "4" } }
+                "items"
+                {
+                    "7" { "name" "weapon_ak47" }
+                    "9" { "name" "weapon_awp" }
+                }
+                "paint_kits"
+                {
+                    "0" { "name" "default" "wear_remap_min" "0.06" "wear_remap_max" "0.8" }
+                    "10" { "name" "sp_dust" }
+                    "11" { "name" "sp_dust_rare" "wear_remap_min" "0.0" "wear_remap_max" "0.5" }
+                    "12" { "name" "sp_dust_other" }
+                    "20" { "name" "sp_aztec" }
+                    "21" { "name" "sp_aztec_rare" }
+                }
+                "paint_kits_rarity"
+                {
+                    "sp_dust" "rare" "sp_dust_rare" "mythical" "sp_dust_other" "mythical"
+                    "sp_aztec" "rare" "sp_aztec_rare" "mythical"
+                }
+                "item_sets"
+                {
+                    "set_dust"
+                    {
+                        "name" "#CSGO_set_dust" "is_collection" "1"
+                        "items" { "[sp_dust]weapon_ak47" "1" "[sp_dust_rare]weapon_ak47" "1" "[sp_dust_other]weapon_awp" "1" }
+                    }
+                    "set_aztec"
+                    {
+                        "name" "#CSGO_set_aztec" "is_collection" "1"
+                        "items" { "[sp_aztec]weapon_awp" "1" "[sp_aztec_rare]weapon_ak47" "1" }
+                    }
+                }
+            }
+            "##,
+        )
+        .unwrap()
+    }
+
+    /// A skin with a wear of 0.2, StatTrak with three kills when
+    /// `stattrak` is set.
+    fn skin(id: u64, def_index: u32, paint_index: u32, rarity: u32, stattrak: bool) -> String {
+        let paint_index = paint_index.to_string();
+        let mut attributes = vec![(6, paint_index.as_str()), (7, "431"), (8, "0.2")];
+        if stattrak {
+            attributes.extend([(80, "3"), (81, "0")]);
+        }
+        item(id, def_index, rarity, &attributes)
+    }
+
+    /// Eight Dust and two Aztec rare skins with ids 1 to 10, a mythical
+    /// Dust skin (11) and a StatTrak rare Dust skin (12).
+    fn inventory() -> Inventory {
+        let mut items: Vec<String> = (1..=8).map(|id| skin(id, 7, 10, 3, false)).collect();
+        items.push(skin(9, 9, 20, 3, false));
+        items.push(skin(10, 9, 20, 3, false));
+        items.push(skin(11, 7, 11, 4, false));
+        items.push(skin(12, 7, 10, 3, true));
+        test_fixtures::inventory(&items)
+    }
+
+    #[test]
+    fn contracts_take_ten_items() {
+        let ids: Vec<u64> = (1..=10).collect();
+        let error = TradeUp::plan(&inventory(), &items_game(), &ids[..9]).unwrap_err();
+        assert_eq!(error, "A trade-up takes 10 items, not 9");
+
+        let error = TradeUp::plan(&inventory(), &items_game(), &[1; 10]).unwrap_err();
+        assert_eq!(error, "Item 1 is in the contract more than once");
+    }
+
+    #[test]
+    fn inputs_share_rarity_and_stattrak() {
+        let inventory = inventory();
+        let items_game = items_game();
+        let mut ids: Vec<u64> = (1..=10).collect();
+
+        ids[0] = 11;
+        let error = TradeUp::plan(&inventory, &items_game, &ids).unwrap_err();
+        assert_eq!(error, "Item 2 has rarity 3 instead of 4");
+
+        ids[0] = 12;
+        let error = TradeUp::plan(&inventory, &items_game, &ids).unwrap_err();
+        assert_eq!(error, "StatTrak and normal items cannot be mixed");
+    }
+
+    #[test]
+    fn each_input_gives_its_collection_a_tenth_of_the_chance() {
+        let ids: Vec<u64> = (1..=10).collect();
+        let plan = TradeUp::plan(&inventory(), &items_game(), &ids).unwrap();
+
+        let outcomes: Vec<(&str, u32, f64)> = plan
+            .outcomes
+            .iter()
+            .map(|o| (o.collection.as_str(), o.paint_index, o.chance))
+            .collect();
+        assert_eq!(outcomes.len(), 3);
+        // Aztec's two inputs all go to its one mythical skin; Dust's eight
+        // are shared between two.
+        assert_eq!(outcomes[0].0, "set_aztec");
+        assert_eq!(outcomes[0].1, 21);
+        assert!((outcomes[0].2 - 0.2).abs() < 1e-9);
+        assert!((outcomes[1].2 - 0.4).abs() < 1e-9);
+        assert!((outcomes[2].2 - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn output_wear_maps_the_average_onto_the_skins_range() {
+        let ids: Vec<u64> = (1..=10).collect();
+        let plan = TradeUp::plan(&inventory(), &items_game(), &ids).unwrap();
+
+        let wear = |paint_index| {
+            plan.outcomes
+                .iter()
+                .find(|o| o.paint_index == paint_index)
+                .unwrap()
+                .wear
+        };
+        // 0.2 of 0.0..0.5, and of the default 0.06..0.8.
+        assert!((wear(11) - 0.1).abs() < 1e-6);
+        assert!((wear(12) - 0.208).abs() < 1e-6);
+    }
+
+    #[test]
+    fn executing_replaces_the_inputs_with_the_outcome() {
+        let mut inventory = inventory();
+        let ids: Vec<u64> = (1..=10).collect();
+        let plan = TradeUp::plan(&inventory, &items_game(), &ids).unwrap();
+
+        let mut rng = CaseRng::new(5);
+        let outcome = plan.roll(&mut rng).clone();
+        plan.execute(&outcome, 100, 100, &mut rng)
+            .apply(&mut inventory)
+            .unwrap();
+
+        let ids: Vec<u64> = inventory.items.iter().map(|item| item.id).collect();
+        assert_eq!(ids, [11, 12, 100]);
+        let output = &inventory.items[2];
+        assert_eq!((output.rarity, output.origin, output.quality), (4, 4, 4));
+        assert_eq!(output.attributes[&6].as_u32(), Some(outcome.paint_index));
+        assert!(!output.attributes.contains_key(&80));
+    }
+}
@@ -0,0 +1,465 @@
+//! Checks an inventory against the game's item schema.
+//!
+//! csgo_gc loads almost anything it is given, so mistakes in inventory.txt
+//! usually show up as crashes or wrongly drawn items in game. [`validate`]
+//! lists such mistakes as [`Problem`]s, most with a quick fix in the form of
+//! an [`InventoryEdit`], so that applying it can be undone like any other
+//! edit.
+
+use crate::inventory::edit::InventoryEdit;
+use crate::inventory::item_attribute::{AttributeValue, ItemAttribute};
+use crate::inventory::items_game::{ItemsGame, MAX_PAINT_SEED};
+use crate::inventory::loadout::{Equipped, Loadout, Team, is_loadout_slot};
+use crate::inventory::models::{Inventory, Item};
+use std::collections::HashMap;
+
+/// Skin data that online data answers better than items_game.txt, such as
+/// which paint kits a weapon comes with.
+pub trait SkinCatalog {
+    /// The paint kits `def_index` can be found with, or `None` if unknown.
+    fn paint_kits_for_weapon(&self, def_index: u32) -> Option<Vec<u32>>;
+
+    /// The rarity of `def_index` painted with `paint_index`.
+    fn skin_rarity(&self, def_index: u32, paint_index: u32) -> Option<u32>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Likely to crash the game or lose the item.
+    Error,
+    /// Loads, but looks or behaves wrong.
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub item_id: u64,
+    pub severity: Severity,
+    pub message: String,
+    /// An edit that fixes the problem, if there is an obvious one.
+    pub fix: Option<InventoryEdit>,
+}
+
+const STICKER_ID_ATTRIBUTES: [ItemAttribute; 6] = [
+    ItemAttribute::Sticker0ID,
+    ItemAttribute::Sticker1ID,
+    ItemAttribute::Sticker2ID,
+    ItemAttribute::Sticker3ID,
+    ItemAttribute::Sticker4ID,
+    ItemAttribute::Sticker5ID,
+];
+
+/// Every problem found in `inventory`, in item order. Checks that need the
+/// schema are skipped when `items_game` is empty.
+pub fn validate(
+    inventory: &Inventory,
+    items_game: &ItemsGame,
+    catalog: &dyn SkinCatalog,
+) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut seen_ids: HashMap<u64, usize> = HashMap::new();
+
+    for item in &inventory.items {
+        let seen = seen_ids.entry(item.id).or_default();
+        *seen += 1;
+        if *seen == 2 {
+            problems.push(Problem {
+                item_id: item.id,
+                severity: Severity::Error,
+                message: format!("Item id {} is used by more than one item", item.id),
+                fix: None,
+            });
+        }
+
+        check_equipped_state(item, &mut problems);
+        if !items_game.items.is_empty() {
+            check_item(item, items_game, catalog, &mut problems);
+        }
+    }
+
+    check_loadout(inventory, &mut problems);
+    problems
+}
+
+/// Slots filled more than once; every item but the first is reported.
+fn check_loadout(inventory: &Inventory, problems: &mut Vec<Problem>) {
+    for (team, slot, equipped) in Loadout::of(inventory).conflicts() {
+        for extra in &equipped[1..] {
+            let Equipped::Item(item_id) = *extra else {
+                continue;
+            };
+            let Some(item) = inventory.items.iter().find(|item| item.id == item_id) else {
+                continue;
+            };
+            let mut fixed = item.clone();
+            fixed.equipped_state.remove(&team.class_id());
+            problems.push(Problem {
+                item_id,
+                severity: Severity::Warning,
+                message: format!(
+                    "Slot {} of team {} is already taken by another item",
+                    slot,
+                    team.class_id()
+                ),
+                fix: Some(InventoryEdit::ReplaceItem(fixed)),
+            });
+        }
+    }
+}
+
+fn check_item(
+    item: &Item,
+    items_game: &ItemsGame,
+    catalog: &dyn SkinCatalog,
+    problems: &mut Vec<Problem>,
+) {
+    let mut problem = |severity, message: String, fix| {
+        problems.push(Problem {
+            item_id: item.id,
+            severity,
+            message,
+            fix,
+        })
+    };
+
+    if !items_game.items.contains_key(&item.def_index) {
+        problem(
+            Severity::Error,
+            format!("Unknown item definition {}", item.def_index),
+            Some(InventoryEdit::DeleteItem(item.id)),
+        );
+        return;
+    }
+
+    if let Some(paint_index) = attribute_u32(item, ItemAttribute::SkinPaintIndex) {
+        if !items_game.paint_kits.contains_key(&paint_index) {
+            problem(
+                Severity::Error,
+                format!("Unknown paint kit {}", paint_index),
+                Some(remove_attribute(item, ItemAttribute::SkinPaintIndex)),
+            );
+        } else {
+            if let Some(paint_kits) = catalog.paint_kits_for_weapon(item.def_index)
+                && !paint_kits.contains(&paint_index)
+            {
+                problem(
+                    Severity::Warning,
+                    format!(
+                        "Paint kit {} does not exist for item definition {}",
+                        paint_index, item.def_index
+                    ),
+                    None,
+                );
+            }
+
+            let (min, max) = items_game.get_paint_kit_wear_range(paint_index);
+            if let Some(wear) = item
+                .attributes
+                .get(&ItemAttribute::SkinPaintWear.id())
+                .and_then(AttributeValue::as_f32)
+                && !(min..=max).contains(&wear)
+            {
+                problem(
+                    Severity::Warning,
+                    format!(
+                        "Wear {} is outside the paint kit's range of {} to {}",
+                        wear, min, max
+                    ),
+                    Some(InventoryEdit::SetAttribute {
+                        item_id: item.id,
+                        attribute: ItemAttribute::SkinPaintWear.id(),
+                        value: Some(AttributeValue::float(wear.clamp(min, max))),
+                    }),
+                );
+            }
+
+            if let Some(seed) = item
+                .attributes
+                .get(&ItemAttribute::SkinPaintSeed.id())
+                .and_then(AttributeValue::as_f32)
+                && !(0.0..=MAX_PAINT_SEED as f32).contains(&seed)
+            {
+                problem(
+                    Severity::Warning,
+                    format!(
+                        "Seed {} is outside the game's range of 0 to {}",
+                        seed, MAX_PAINT_SEED
+                    ),
+                    Some(InventoryEdit::SetAttribute {
+                        item_id: item.id,
+                        attribute: ItemAttribute::SkinPaintSeed.id(),
+                        value: Some(AttributeValue::float(
+                            seed.clamp(0.0, MAX_PAINT_SEED as f32),
+                        )),
+                    }),
+                );
+            }
+
+            if let Some(rarity) = catalog.skin_rarity(item.def_index, paint_index)
+                && rarity != item.rarity
+            {
+                problem(
+                    Severity::Warning,
+                    format!(
+                        "Rarity {} does not match the skin's rarity {}",
+                        item.rarity, rarity
+                    ),
+                    Some(InventoryEdit::SetRarity {
+                        item_id: item.id,
+                        rarity,
+                    }),
+                );
+            }
+        }
+    }
+
+    let strange = quality_value(items_game, "strange").unwrap_or(9);
+    // StatTrak knives keep the unusual (★) quality.
+    let unusual = quality_value(items_game, "unusual").unwrap_or(3);
+    if item
+        .attributes
+        .contains_key(&ItemAttribute::StatTrakCount.id())
+        && item.quality != strange
+        && item.quality != unusual
+    {
+        problem(
+            Severity::Warning,
+            format!(
+                "StatTrak counter on an item of quality {} instead of StatTrak",
+                item.quality
+            ),
+            Some(InventoryEdit::ReplaceItem(Item {
+                quality: strange,
+                ..item.clone()
+            })),
+        );
+    }
+
+    for attribute in STICKER_ID_ATTRIBUTES {
+        if let Some(sticker) = attribute_u32(item, attribute)
+            && !items_game.sticker_kits.contains_key(&sticker)
+        {
+            problem(
+                Severity::Error,
+                format!("Unknown sticker {}", sticker),
+                Some(remove_attribute(item, attribute)),
+            );
+        }
+    }
+}
+
+fn check_equipped_state(item: &Item, problems: &mut Vec<Problem>) {
+    let mut bad_teams: Vec<u32> = item
+        .equipped_state
+        .iter()
+        .filter(|(team, slot)| {
+            Team::from_class_id(**team).is_none() || !slot.parse().is_ok_and(is_loadout_slot)
+        })
+        .map(|(team, _)| *team)
+        .collect();
+    bad_teams.sort_unstable();
+
+    for team in bad_teams {
+        let mut fixed = item.clone();
+        fixed.equipped_state.remove(&team);
+        problems.push(Problem {
+            item_id: item.id,
+            severity: Severity::Error,
+            message: format!(
+                "Equipped for team {} in slot {}, which does not exist",
+                team, item.equipped_state[&team]
+            ),
+            fix: Some(InventoryEdit::ReplaceItem(fixed)),
+        });
+    }
+}
+
+fn attribute_u32(item: &Item, attribute: ItemAttribute) -> Option<u32> {
+    item.attributes
+        .get(&attribute.id())
+        .and_then(AttributeValue::as_u32)
+}
+
+fn remove_attribute(item: &Item, attribute: ItemAttribute) -> InventoryEdit {
+    InventoryEdit::SetAttribute {
+        item_id: item.id,
+        attribute: attribute.id(),
+        value: None,
+    }
+}
+
+fn quality_value(items_game: &ItemsGame, name: &str) -> Option<u32> {
+    items_game.qualities.get(name).map(|quality| quality.value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Problem, Severity, SkinCatalog, validate};
+    use crate::inventory::items_game_loader::ItemsGameLoader;
+    use crate::inventory::models::{Inventory, Item};
+    use crate::inventory::{AttributeValue, InventoryEdit};
+
+    struct Catalog;
+
+    impl SkinCatalog for Catalog {
+        fn paint_kits_for_weapon(&self, _def_index: u32) -> Option<Vec<u32>> {
+            Some(vec![44, 180])
+        }
+
+        fn skin_rarity(&self, _def_index: u32, _paint_index: u32) -> Option<u32> {
+            Some(6)
+        }
+    }
+
+    /// The problems [`validate`] finds in `items`.
+    fn problems(items: Vec<Item>) -> Vec<Problem> {
+        let items_game = ItemsGameLoader::parse_from_str(
+            r#"
+            "items_game"
+            {
+                "items" { "7" { "name" "weapon_ak47" } }
+                "qualities" { "strange" { "value" "9" } "unusual" { "value" "3" } }
+                "paint_kits"
+                {
+                    "0" { "name" "default" "wear_remap_min" "0.06" "wear_remap_max" "0.8" }
+                    "44" { "name" "cu_ak47_case_hardened" "wear_remap_min" "0.00" "wear_remap_max" "1.00" }
+                    "45" { "name" "cu_ak47_redline" }
+                    "180" { "name" "cu_ak47_fire_serpent" }
+                }
+                "sticker_kits" { "1" { "name" "dh_gologo" } }
+            }
+            "#,
+        )
+        .unwrap();
+        let inventory = Inventory {
+            items,
+            ..Default::default()
+        };
+        validate(&inventory, &items_game, &Catalog)
+    }
+
+    fn messages(problems: &[Problem]) -> Vec<&str> {
+        problems.iter().map(|p| p.message.as_str()).collect()
+    }
+
+    /// A Case Hardened AK-47 without problems, equipped by the CTs.
+    fn skin() -> Item {
+        let mut skin = Item {
+            id: 1,
+            def_index: 7,
+            quality: 4,
+            rarity: 6,
+            ..Default::default()
+        };
+        skin.attributes.insert(6, AttributeValue::float(44.0));
+        skin.attributes.insert(7, AttributeValue::float(500.0));
+        skin.attributes.insert(8, AttributeValue::float(0.5));
+        skin.attributes.insert(113, AttributeValue::uint32(1));
+        skin.equipped_state.insert(3, "14".to_string());
+        skin
+    }
+
+    #[test]
+    fn a_valid_skin_has_no_problems() {
+        assert!(problems(vec![skin()]).is_empty());
+    }
+
+    #[test]
+    fn reports_slots_that_do_not_exist() {
+        let mut skin = skin();
+        skin.equipped_state.insert(5, "14".to_string());
+
+        let problems = problems(vec![skin]);
+        assert_eq!(
+            messages(&problems),
+            ["Equipped for team 5 in slot 14, which does not exist"]
+        );
+        assert_eq!(problems[0].severity, Severity::Error);
+        assert!(matches!(
+            &problems[0].fix,
+            Some(InventoryEdit::ReplaceItem(fixed)) if !fixed.equipped_state.contains_key(&5)
+        ));
+    }
+
+    #[test]
+    fn reports_paint_kits_the_weapon_does_not_come_with() {
+        let mut skin = skin();
+        skin.attributes.insert(6, AttributeValue::float(45.0));
+
+        assert_eq!(
+            messages(&problems(vec![skin])),
+            ["Paint kit 45 does not exist for item definition 7"]
+        );
+    }
+
+    #[test]
+    fn clamps_wear_to_the_paint_kits_range() {
+        let mut skin = skin();
+        skin.attributes.insert(6, AttributeValue::float(180.0));
+        skin.attributes.insert(8, AttributeValue::float(0.9));
+
+        let problems = problems(vec![skin]);
+        assert_eq!(
+            messages(&problems),
+            ["Wear 0.9 is outside the paint kit's range of 0.06 to 0.8"]
+        );
+        assert!(matches!(
+            &problems[0].fix,
+            Some(InventoryEdit::SetAttribute { value: Some(v), .. }) if v.as_f32() == Some(0.8)
+        ));
+    }
+
+    #[test]
+    fn clamps_seeds_to_the_games_range() {
+        let mut skin = skin();
+        skin.attributes.insert(7, AttributeValue::float(1500.0));
+
+        assert_eq!(
+            messages(&problems(vec![skin])),
+            ["Seed 1500 is outside the game's range of 0 to 1000"]
+        );
+    }
+
+    #[test]
+    fn reports_stattrak_counters_on_normal_items() {
+        let mut skin = skin();
+        skin.attributes.insert(80, AttributeValue::uint32(0));
+
+        assert_eq!(
+            messages(&problems(vec![skin])),
+            ["StatTrak counter on an item of quality 4 instead of StatTrak"]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_stickers() {
+        let mut skin = skin();
+        skin.attributes.insert(117, AttributeValue::uint32(999));
+
+        assert_eq!(messages(&problems(vec![skin])), ["Unknown sticker 999"]);
+    }
+
+    #[test]
+    fn reports_repeated_item_ids() {
+        let mut copy = skin();
+        copy.equipped_state.clear();
+
+        assert_eq!(
+            messages(&problems(vec![skin(), copy])),
+            ["Item id 1 is used by more than one item"]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_item_definitions() {
+        let unknown = Item {
+            id: 2,
+            def_index: 12345,
+            ..Default::default()
+        };
+
+        let problems = problems(vec![unknown]);
+        assert_eq!(messages(&problems), ["Unknown item definition 12345"]);
+        assert_eq!(problems[0].fix, Some(InventoryEdit::DeleteItem(2)));
+    }
+}
@@ -0,0 +1,255 @@
+//! Binary KeyValues.
+//!
+//! Every node is a type byte, a NUL-terminated key and a payload; an object's
+//! children run until an end marker. Typed values (integers, floats, colors,
+//! ...) are read into their text form so the result is the same [`VdfValue`]
+//! tree the text parser produces. When writing, strings are stored as string
+//! nodes, since the tree does not record which values were typed.
+
+use super::{VdfObject, VdfParseError, VdfValue};
+
+const TYPE_NONE: u8 = 0;
+const TYPE_STRING: u8 = 1;
+const TYPE_INT: u8 = 2;
+const TYPE_FLOAT: u8 = 3;
+const TYPE_PTR: u8 = 4;
+const TYPE_WSTRING: u8 = 5;
+const TYPE_COLOR: u8 = 6;
+const TYPE_UINT64: u8 = 7;
+const TYPE_END: u8 = 8;
+
+pub struct VdfBinary;
+
+impl VdfBinary {
+    /// Reads the root entries of a binary KeyValues buffer. The final end
+    /// marker may be missing.
+    pub fn parse(bytes: &[u8]) -> Result<VdfObject, VdfParseError> {
+        let mut reader = BinaryReader { bytes, position: 0 };
+        let obj = reader.read_object(true)?;
+
+        if reader.position < bytes.len() {
+            return Err(VdfParseError::new(
+                "Unexpected data after end marker",
+                reader.position,
+            ));
+        }
+        Ok(obj)
+    }
+
+    /// Writes `obj` as binary KeyValues. Fails on keys or values containing
+    /// NUL, which terminates strings in the binary format.
+    pub fn to_bytes(obj: &VdfObject) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        write_object(obj, &mut out)?;
+        Ok(out)
+    }
+}
+
+struct BinaryReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl BinaryReader<'_> {
+    fn read_object(&mut self, is_root: bool) -> Result<VdfObject, VdfParseError> {
+        let mut obj = VdfObject::new();
+
+        loop {
+            let Some(&node_type) = self.bytes.get(self.position) else {
+                if is_root {
+                    return Ok(obj);
+                }
+                return Err(VdfParseError::new("Expected end marker", self.position));
+            };
+            let node_start = self.position;
+            self.position += 1;
+
+            if node_type == TYPE_END {
+                return Ok(obj);
+            }
+
+            let key = self.read_string()?;
+            let value = match node_type {
+                TYPE_NONE => VdfValue::Object(self.read_object(false)?),
+                TYPE_STRING => VdfValue::String(self.read_string()?),
+                TYPE_INT => VdfValue::String(i32::from_le_bytes(self.read_array()?).to_string()),
+                TYPE_FLOAT => VdfValue::String(f32::from_le_bytes(self.read_array()?).to_string()),
+                TYPE_PTR => VdfValue::String(u32::from_le_bytes(self.read_array()?).to_string()),
+                TYPE_WSTRING => VdfValue::String(self.read_wide_string()?),
+                TYPE_COLOR => {
+                    let [r, g, b, a] = self.read_array()?;
+                    VdfValue::String(format!("{} {} {} {}", r, g, b, a))
+                }
+                TYPE_UINT64 => VdfValue::String(u64::from_le_bytes(self.read_array()?).to_string()),
+                _ => {
+                    return Err(VdfParseError::new(
+                        format!("Unknown node type {}", node_type),
+                        node_start,
+                    ));
+                }
+            };
+
+            obj.push(key, value);
+        }
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], VdfParseError> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + N)
+            .ok_or_else(|| VdfParseError::new("Unexpected end of data", self.bytes.len()))?;
+        self.position += N;
+        Ok(bytes.try_into().expect("slice has length N"))
+    }
+
+    fn read_string(&mut self) -> Result<String, VdfParseError> {
+        let start = self.position;
+        let length = self.bytes[start..]
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| VdfParseError::new("Unterminated string", start))?;
+        self.position += length + 1;
+
+        String::from_utf8(self.bytes[start..start + length].to_vec())
+            .map_err(|_| VdfParseError::new("Invalid UTF-8 in string", start))
+    }
+
+    /// A NUL-terminated UTF-16LE string.
+    fn read_wide_string(&mut self) -> Result<String, VdfParseError> {
+        let start = self.position;
+        let mut units = Vec::new();
+        loop {
+            match u16::from_le_bytes(self.read_array()?) {
+                0 => break,
+                unit => units.push(unit),
+            }
+        }
+
+        String::from_utf16(&units)
+            .map_err(|_| VdfParseError::new("Invalid UTF-16 in string", start))
+    }
+}
+
+fn write_object(obj: &VdfObject, out: &mut Vec<u8>) -> Result<(), String> {
+    for (key, value) in obj {
+        match value {
+            VdfValue::String(s) => {
+                out.push(TYPE_STRING);
+                write_string(key, out).map_err(|_| {
+                    format!("Key '{}' contains a NUL character", key.escape_default())
+                })?;
+                write_string(s, out).map_err(|_| {
+                    format!(
+                        "Value of '{}' contains a NUL character",
+                        key.escape_default()
+                    )
+                })?;
+            }
+            VdfValue::Object(inner) => {
+                out.push(TYPE_NONE);
+                write_string(key, out).map_err(|_| {
+                    format!("Key '{}' contains a NUL character", key.escape_default())
+                })?;
+                write_object(inner, out)?;
+            }
+        }
+    }
+    out.push(TYPE_END);
+    Ok(())
+}
+
+/// Binary strings end at the first NUL, so a string containing one cannot be
+/// written.
+fn write_string(s: &str, out: &mut Vec<u8>) -> Result<(), ()> {
+    if s.contains('\0') {
+        return Err(());
+    }
+    out.extend_from_slice(s.as_bytes());
+    out.push(0);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VdfBinary;
+    use crate::inventory::vdf::{VdfObject, VdfParser, VdfValue};
+
+    #[test]
+    fn round_trips_text_through_binary() {
+        let text = r#"
+            "items"
+            {
+                "42" { "def_index" "7" "custom_name" "龙狙 🔥" }
+                "42" { "def_index" "9" }
+                "empty" { }
+            }
+        "#;
+        let vdf = VdfParser::parse(text).unwrap();
+
+        let bytes = VdfBinary::to_bytes(&vdf).unwrap();
+        let decoded = VdfBinary::parse(&bytes).unwrap();
+
+        assert_eq!(decoded, vdf);
+        assert_eq!(
+            VdfParser::parse(&VdfParser::to_string(&VdfValue::Object(decoded))).unwrap(),
+            vdf
+        );
+    }
+
+    #[test]
+    fn refuses_to_write_strings_with_nul() {
+        let mut vdf = VdfObject::new();
+        vdf.push("custom_name", VdfValue::String("AK\0-47".to_string()));
+        assert_eq!(
+            VdfBinary::to_bytes(&vdf).unwrap_err(),
+            "Value of 'custom_name' contains a NUL character"
+        );
+
+        let mut vdf = VdfObject::new();
+        vdf.push("a\0b", VdfValue::Object(VdfObject::new()));
+        assert_eq!(
+            VdfBinary::to_bytes(&vdf).unwrap_err(),
+            "Key 'a\\u{0}b' contains a NUL character"
+        );
+    }
+
+    #[test]
+    fn reads_typed_nodes_as_text() {
+        let mut bytes = vec![0];
+        bytes.extend(b"root\0");
+        bytes.extend(b"\x02int\0");
+        bytes.extend((-5i32).to_le_bytes());
+        bytes.extend(b"\x03float\0");
+        bytes.extend(0.25f32.to_le_bytes());
+        bytes.extend(b"\x04ptr\0");
+        bytes.extend(7u32.to_le_bytes());
+        bytes.extend(b"\x05wide\0");
+        for unit in "hé".encode_utf16().chain([0]) {
+            bytes.extend(unit.to_le_bytes());
+        }
+        bytes.extend(b"\x06color\0\xff\x80\x00\x01");
+        bytes.extend(b"\x07big\0");
+        bytes.extend(u64::MAX.to_le_bytes());
+        bytes.extend([8, 8]);
+
+        let vdf = VdfBinary::parse(&bytes).unwrap();
+        let root = vdf.get("root").and_then(VdfValue::as_object).unwrap();
+        let values: Vec<(&str, &str)> = root
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_string().unwrap()))
+            .collect();
+
+        assert_eq!(
+            values,
+            [
+                ("int", "-5"),
+                ("float", "0.25"),
+                ("ptr", "7"),
+                ("wide", "hé"),
+                ("color", "255 128 0 1"),
+                ("big", "18446744073709551615"),
+            ]
+        );
+        assert!(VdfBinary::parse(&bytes[..bytes.len() - 3]).is_err());
+    }
+}
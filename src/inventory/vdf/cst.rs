@@ -0,0 +1,564 @@
+//! Lossless KeyValues documents.
+//!
+//! [`VdfDocument`] keeps every byte of the source: whitespace and comments are
+//! stored as trivia in front of the token they precede, and keys and values
+//! keep their original text and byte span. Updating a document from a
+//! [`VdfObject`] only rewrites the entries whose value actually changed, so
+//! saving an unchanged file reproduces it byte for byte.
+//!
+//! Conditional suffixes such as `[$WIN32]` are kept verbatim as trivia and
+//! `#base`/`#include` directives as ordinary entries; the document is never
+//! evaluated, only edited.
+
+use super::{VdfObject, VdfParseError, VdfParser, VdfTokenizer, VdfValue};
+use std::collections::HashMap;
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VdfDocument {
+    has_bom: bool,
+    /// The line ending of the source, used for inserted entries.
+    newline: &'static str,
+    entries: Vec<CstEntry>,
+    trailing: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CstEntry {
+    leading: String,
+    key: CstToken,
+    value: CstValue,
+    trailing: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CstValue {
+    String {
+        separator: String,
+        token: CstToken,
+    },
+    Object {
+        separator: String,
+        entries: Vec<CstEntry>,
+        closing: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CstToken {
+    text: String,
+    value: String,
+    span: Option<Range<usize>>,
+}
+
+impl CstToken {
+    fn synthesized(value: &str) -> Self {
+        Self {
+            text: format!("\"{}\"", VdfParser::escape_string(value)),
+            value: value.to_string(),
+            span: None,
+        }
+    }
+
+    /// The decoded token value.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// The token exactly as it appears in the document, including quotes.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Byte range of the token in the parsed source, or `None` if the token
+    /// was written by [`VdfDocument::update`].
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.span.clone()
+    }
+}
+
+impl CstEntry {
+    pub fn key(&self) -> &CstToken {
+        &self.key
+    }
+
+    /// The string value token, or `None` if the entry holds an object.
+    pub fn value_token(&self) -> Option<&CstToken> {
+        match &self.value {
+            CstValue::String { token, .. } => Some(token),
+            CstValue::Object { .. } => None,
+        }
+    }
+
+    /// Child entries, or `None` if the entry holds a string.
+    pub fn children(&self) -> Option<&[CstEntry]> {
+        match &self.value {
+            CstValue::String { .. } => None,
+            CstValue::Object { entries, .. } => Some(entries),
+        }
+    }
+
+    /// Whitespace and comments in front of the key.
+    pub fn leading_trivia(&self) -> &str {
+        &self.leading
+    }
+
+    /// Whitespace and a `//` comment following the value on the same line.
+    pub fn trailing_trivia(&self) -> &str {
+        &self.trailing
+    }
+
+    fn to_value(&self) -> VdfValue {
+        match &self.value {
+            CstValue::String { token, .. } => VdfValue::String(token.value.clone()),
+            CstValue::Object { entries, .. } => VdfValue::Object(entries_to_object(entries)),
+        }
+    }
+
+    fn write(&self, out: &mut String) {
+        out.push_str(&self.leading);
+        out.push_str(&self.key.text);
+        match &self.value {
+            CstValue::String { separator, token } => {
+                out.push_str(separator);
+                out.push_str(&token.text);
+            }
+            CstValue::Object {
+                separator,
+                entries,
+                closing,
+            } => {
+                out.push_str(separator);
+                out.push('{');
+                for entry in entries {
+                    entry.write(out);
+                }
+                out.push_str(closing);
+                out.push('}');
+            }
+        }
+        out.push_str(&self.trailing);
+    }
+}
+
+impl VdfDocument {
+    pub fn parse(content: &str) -> Result<Self, VdfParseError> {
+        let (has_bom, content) = match content.strip_prefix('\u{feff}') {
+            Some(stripped) => (true, stripped),
+            None => (false, content),
+        };
+
+        let mut tokenizer = VdfTokenizer::new(content);
+        let (entries, trailing) =
+            parse_entries(&mut tokenizer, false).map_err(|e| e.locate(content, &tokenizer.path))?;
+
+        let newline = if content.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+
+        Ok(Self {
+            has_bom,
+            newline,
+            entries,
+            trailing,
+        })
+    }
+
+    pub fn entries(&self) -> &[CstEntry] {
+        &self.entries
+    }
+
+    /// The document as a plain value tree, without trivia.
+    pub fn to_object(&self) -> VdfObject {
+        entries_to_object(&self.entries)
+    }
+
+    /// Brings the document in line with `desired`.
+    ///
+    /// Entries are matched by key, and by occurrence for repeated keys. Matched
+    /// entries keep their position, trivia and original text unless their value
+    /// changed; new entries are inserted after the entry that precedes them in
+    /// `desired`, indented like their siblings and ending lines the way the
+    /// source does. Entries missing from `desired`
+    /// are removed only when `should_remove` returns `true` for their key path,
+    /// which lets callers keep keys they do not model.
+    pub fn update(&mut self, desired: &VdfObject, should_remove: &mut dyn FnMut(&[&str]) -> bool) {
+        let mut path = Vec::new();
+        let layout = Layout {
+            newline: self.newline,
+        };
+        update_entries(
+            &mut self.entries,
+            desired,
+            "",
+            &layout,
+            &mut path,
+            should_remove,
+        );
+    }
+}
+
+impl std::fmt::Display for VdfDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        if self.has_bom {
+            out.push('\u{feff}');
+        }
+        for entry in &self.entries {
+            entry.write(&mut out);
+        }
+        out.push_str(&self.trailing);
+        f.write_str(&out)
+    }
+}
+
+fn entries_to_object(entries: &[CstEntry]) -> VdfObject {
+    entries
+        .iter()
+        .map(|entry| (entry.key.value.clone(), entry.to_value()))
+        .collect()
+}
+
+fn skip_trivia(tokenizer: &mut VdfTokenizer) -> String {
+    let start = tokenizer.position;
+    tokenizer.skip_whitespace();
+    tokenizer.content[start..tokenizer.position].to_string()
+}
+
+/// Consumes blanks, an optional conditional suffix and an optional `//`
+/// comment up to, but not including, the end of the current line.
+fn skip_line_trivia(tokenizer: &mut VdfTokenizer) -> Result<String, VdfParseError> {
+    let start = tokenizer.position;
+    tokenizer.skip_inline_whitespace();
+    if tokenizer.parse_condition()?.is_some() {
+        tokenizer.skip_inline_whitespace();
+    }
+    if tokenizer.peek("//") {
+        tokenizer.position = tokenizer.content[tokenizer.position..]
+            .find(['\r', '\n'])
+            .map_or(tokenizer.content.len(), |offset| {
+                tokenizer.position + offset
+            });
+    }
+    Ok(tokenizer.content[start..tokenizer.position].to_string())
+}
+
+fn parse_entries(
+    tokenizer: &mut VdfTokenizer,
+    in_object: bool,
+) -> Result<(Vec<CstEntry>, String), VdfParseError> {
+    let mut entries = Vec::new();
+
+    loop {
+        let leading = skip_trivia(tokenizer);
+
+        if tokenizer.position >= tokenizer.content.len() {
+            if in_object {
+                return Err(VdfParseError::new("Expected '}'", tokenizer.position));
+            }
+            return Ok((entries, leading));
+        }
+
+        if tokenizer.peek("}") {
+            if in_object {
+                tokenizer.position += 1;
+                return Ok((entries, leading));
+            }
+            return Err(VdfParseError::new("Unexpected '}'", tokenizer.position));
+        }
+
+        let key_start = tokenizer.position;
+        let key_value = tokenizer.parse_key()?;
+        let key = CstToken {
+            text: tokenizer.content[key_start..tokenizer.position].to_string(),
+            value: key_value,
+            span: Some(key_start..tokenizer.position),
+        };
+        tokenizer.path.push(key.value.clone());
+
+        let mut separator = skip_trivia(tokenizer);
+        let condition_start = tokenizer.position;
+        if tokenizer.parse_condition()?.is_some() {
+            separator.push_str(&tokenizer.content[condition_start..tokenizer.position]);
+            separator.push_str(&skip_trivia(tokenizer));
+            if !tokenizer.peek("{") {
+                return Err(VdfParseError::new("Expected '{'", tokenizer.position));
+            }
+        }
+
+        let value = if tokenizer.consume("{") {
+            let (children, closing) = parse_entries(tokenizer, true)?;
+            CstValue::Object {
+                separator,
+                entries: children,
+                closing,
+            }
+        } else {
+            let value_start = tokenizer.position;
+            let value = tokenizer.parse_value()?;
+            CstValue::String {
+                separator,
+                token: CstToken {
+                    text: tokenizer.content[value_start..tokenizer.position].to_string(),
+                    value,
+                    span: Some(value_start..tokenizer.position),
+                },
+            }
+        };
+
+        tokenizer.path.pop();
+        let trailing = skip_line_trivia(tokenizer)?;
+
+        entries.push(CstEntry {
+            leading,
+            key,
+            value,
+            trailing,
+        });
+    }
+}
+
+/// How inserted entries are laid out.
+struct Layout {
+    newline: &'static str,
+}
+
+fn update_entries(
+    entries: &mut Vec<CstEntry>,
+    desired: &VdfObject,
+    parent_indent: &str,
+    layout: &Layout,
+    path: &mut Vec<String>,
+    should_remove: &mut dyn FnMut(&[&str]) -> bool,
+) {
+    let indent = sibling_indent(entries).unwrap_or_else(|| {
+        if path.is_empty() {
+            String::new()
+        } else {
+            format!("{}\t", parent_indent)
+        }
+    });
+    let unit = indent
+        .strip_prefix(parent_indent)
+        .filter(|unit| !unit.is_empty())
+        .unwrap_or("\t")
+        .to_string();
+
+    // Pair the n-th occurrence of a key in `desired` with its n-th occurrence
+    // in the document.
+    let mut occurrences: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, entry) in entries.iter().enumerate() {
+        occurrences
+            .entry(entry.key.value.as_str())
+            .or_default()
+            .push(index);
+    }
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    let matches: Vec<Option<usize>> = desired
+        .iter()
+        .map(|(key, _)| {
+            let nth = seen.entry(key.as_str()).or_insert(0);
+            let index = occurrences
+                .get(key.as_str())
+                .and_then(|v| v.get(*nth))
+                .copied();
+            *nth += 1;
+            index
+        })
+        .collect();
+
+    let mut matched = vec![false; entries.len()];
+    let mut inserts: HashMap<Option<usize>, Vec<CstEntry>> = HashMap::new();
+    let mut anchor = None;
+
+    for ((key, value), index) in desired.iter().zip(matches) {
+        match index {
+            Some(index) => {
+                matched[index] = true;
+                anchor = Some(index);
+                path.push(key.clone());
+                update_entry(
+                    &mut entries[index],
+                    value,
+                    &indent,
+                    &unit,
+                    layout,
+                    path,
+                    should_remove,
+                );
+                path.pop();
+            }
+            None => inserts
+                .entry(anchor)
+                .or_default()
+                .push(new_entry(key, value, &indent, &unit, layout)),
+        }
+    }
+
+    let old_entries = std::mem::take(entries);
+    entries.extend(inserts.remove(&None).unwrap_or_default());
+    for (index, entry) in old_entries.into_iter().enumerate() {
+        if !matched[index] {
+            let mut entry_path: Vec<&str> = path.iter().map(String::as_str).collect();
+            entry_path.push(&entry.key.value);
+            if should_remove(&entry_path) {
+                continue;
+            }
+        }
+        entries.push(entry);
+        entries.extend(inserts.remove(&Some(index)).unwrap_or_default());
+    }
+}
+
+fn update_entry(
+    entry: &mut CstEntry,
+    desired: &VdfValue,
+    indent: &str,
+    unit: &str,
+    layout: &Layout,
+    path: &mut Vec<String>,
+    should_remove: &mut dyn FnMut(&[&str]) -> bool,
+) {
+    match (&mut entry.value, desired) {
+        (CstValue::String { token, .. }, VdfValue::String(new_value)) => {
+            if token.value != *new_value {
+                *token = CstToken::synthesized(new_value);
+            }
+        }
+        (CstValue::Object { entries, .. }, VdfValue::Object(new_object)) => {
+            update_entries(entries, new_object, indent, layout, path, should_remove);
+        }
+        _ => entry.value = new_value(desired, indent, unit, layout),
+    }
+}
+
+fn new_entry(key: &str, value: &VdfValue, indent: &str, unit: &str, layout: &Layout) -> CstEntry {
+    CstEntry {
+        leading: format!("{}{}", layout.newline, indent),
+        key: CstToken::synthesized(key),
+        value: new_value(value, indent, unit, layout),
+        trailing: String::new(),
+    }
+}
+
+fn new_value(value: &VdfValue, indent: &str, unit: &str, layout: &Layout) -> CstValue {
+    match value {
+        VdfValue::String(s) => CstValue::String {
+            separator: "\t\t".to_string(),
+            token: CstToken::synthesized(s),
+        },
+        VdfValue::Object(object) => {
+            let child_indent = format!("{}{}", indent, unit);
+            CstValue::Object {
+                separator: format!("{}{}", layout.newline, indent),
+                entries: object
+                    .iter()
+                    .map(|(key, value)| new_entry(key, value, &child_indent, unit, layout))
+                    .collect(),
+                closing: format!("{}{}", layout.newline, indent),
+            }
+        }
+    }
+}
+
+/// The indentation used in front of the existing entries of a block, if it can
+/// be read from their trivia.
+fn sibling_indent(entries: &[CstEntry]) -> Option<String> {
+    entries.iter().find_map(|entry| {
+        let (_, last_line) = entry.leading.rsplit_once('\n')?;
+        last_line
+            .chars()
+            .all(|c| c == ' ' || c == '\t')
+            .then(|| last_line.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VdfDocument;
+    use crate::inventory::vdf::{VdfParser, VdfValue};
+
+    const SOURCE: &str = "// hand-written notes\n\"items\"\n{\n    \"1\"\n    {\n        \"rarity\"   \"3\" // keep me\n        \"note\" \"unknown key\"\n    }\n}\n";
+
+    #[test]
+    fn renders_unchanged_document_byte_for_byte() {
+        let document = VdfDocument::parse(SOURCE).unwrap();
+
+        assert_eq!(document.to_string(), SOURCE);
+
+        let conditional =
+            "#base \"base.txt\"\n\"a\" [$WIN32]\n{\n\t\"b\" value [!$X360] // pc\n}\n";
+        let document = VdfDocument::parse(conditional).unwrap();
+        assert_eq!(document.to_string(), conditional);
+    }
+
+    #[test]
+    fn rewrites_only_changed_values() {
+        let mut document = VdfDocument::parse(SOURCE).unwrap();
+        let mut desired = document.to_object();
+        let item = desired
+            .get_mut("items")
+            .and_then(VdfValue::as_object_mut)
+            .and_then(|items| items.get_mut("1"))
+            .and_then(VdfValue::as_object_mut)
+            .unwrap();
+        item.insert("rarity", VdfValue::String("6".to_string()));
+
+        document.update(&desired, &mut |_| true);
+
+        assert_eq!(document.to_string(), SOURCE.replace("\"3\"", "\"6\""));
+    }
+
+    #[test]
+    fn inserts_new_entries_with_sibling_indentation() {
+        let mut document = VdfDocument::parse(SOURCE).unwrap();
+        let desired = VdfParser::parse(
+            r#""items" { "1" { "rarity" "3" "note" "unknown key" } "2" { "rarity" "1" } }"#,
+        )
+        .unwrap();
+
+        document.update(&desired, &mut |_| true);
+
+        let output = document.to_string();
+        assert!(
+            output.contains("    }\n    \"2\"\n    {\n        \"rarity\"\t\t\"1\"\n    }\n}\n")
+        );
+        assert_eq!(VdfParser::parse(&output).unwrap(), desired);
+    }
+
+    #[test]
+    fn inserts_new_entries_with_the_sources_line_endings() {
+        let source = SOURCE.replace('\n', "\r\n");
+        let mut document = VdfDocument::parse(&source).unwrap();
+        assert_eq!(document.to_string(), source);
+        let desired = VdfParser::parse(
+            r#""items" { "1" { "rarity" "3" "note" "unknown key" } "2" { "rarity" "1" } }"#,
+        )
+        .unwrap();
+
+        document.update(&desired, &mut |_| true);
+
+        let output = document.to_string();
+        assert!(output.contains(
+            "    }\r\n    \"2\"\r\n    {\r\n        \"rarity\"\t\t\"1\"\r\n    }\r\n}\r\n"
+        ));
+        assert_eq!(output.matches('\n').count(), output.matches("\r\n").count());
+        assert_eq!(VdfParser::parse(&output).unwrap(), desired);
+    }
+
+    #[test]
+    fn removes_entries_only_when_allowed() {
+        let desired = VdfParser::parse(r#""items" { "1" { "rarity" "3" } }"#).unwrap();
+
+        let mut kept = VdfDocument::parse(SOURCE).unwrap();
+        kept.update(&desired, &mut |_| false);
+        assert_eq!(kept.to_string(), SOURCE);
+
+        let mut removed = VdfDocument::parse(SOURCE).unwrap();
+        removed.update(&desired, &mut |path| path.last() == Some(&"note"));
+        assert!(!removed.to_string().contains("unknown key"));
+        assert!(removed.to_string().contains("// keep me"));
+    }
+}
@@ -0,0 +1,431 @@
+//! Structured differences between VDF trees.
+//!
+//! [`VdfPatch::diff`] compares two objects and lists the entries that were
+//! added, removed or changed, each at the path of keys leading to it.
+//! [`VdfPatch::apply`] replays such a list onto another tree, checking that
+//! every removed or changed value still holds what the patch expects, so a
+//! patch made against one copy of a file is refused by a copy that has since
+//! diverged.
+//!
+//! Repeated keys are matched by occurrence: the second `"item"` in the old
+//! object is compared with the second `"item"` in the new one. Path segments
+//! for later occurrences record the index, so patches apply to files with
+//! repeated keys exactly. A value that turns from a string into an object (or
+//! back) is reported as a removal followed by an addition.
+//!
+//! Patches implement serde's `Serialize` and `Deserialize`; in JSON a path is
+//! a list of keys, with `["key", n]` in place of a key for its `n`th repeat.
+
+use super::{VdfObject, VdfValue};
+use serde::{Deserialize, Serialize};
+
+/// One step of a path: a key, and which occurrence of it (0 for the first).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum VdfPathSegment {
+    Key(String),
+    Occurrence(String, usize),
+}
+
+impl VdfPathSegment {
+    pub fn new(key: impl Into<String>, occurrence: usize) -> Self {
+        match occurrence {
+            0 => VdfPathSegment::Key(key.into()),
+            n => VdfPathSegment::Occurrence(key.into(), n),
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        match self {
+            VdfPathSegment::Key(key) | VdfPathSegment::Occurrence(key, _) => key,
+        }
+    }
+
+    pub fn occurrence(&self) -> usize {
+        match self {
+            VdfPathSegment::Key(_) => 0,
+            VdfPathSegment::Occurrence(_, n) => *n,
+        }
+    }
+}
+
+impl std::fmt::Display for VdfPathSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VdfPathSegment::Key(key) => write!(f, "{}", key),
+            VdfPathSegment::Occurrence(key, n) => write!(f, "{}#{}", key, n + 1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum VdfChange {
+    /// An entry appended to the object at the parent path.
+    Added {
+        path: Vec<VdfPathSegment>,
+        value: VdfValue,
+    },
+    /// An entry removed; `value` is what it held.
+    Removed {
+        path: Vec<VdfPathSegment>,
+        value: VdfValue,
+    },
+    /// A string value replaced by another string.
+    Changed {
+        path: Vec<VdfPathSegment>,
+        old: String,
+        new: String,
+    },
+}
+
+impl VdfChange {
+    pub fn path(&self) -> &[VdfPathSegment] {
+        match self {
+            VdfChange::Added { path, .. }
+            | VdfChange::Removed { path, .. }
+            | VdfChange::Changed { path, .. } => path,
+        }
+    }
+}
+
+impl std::fmt::Display for VdfChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = display_path(self.path());
+        match self {
+            VdfChange::Added { value, .. } => write!(f, "+ {}: {}", path, display_value(value)),
+            VdfChange::Removed { value, .. } => write!(f, "- {}: {}", path, display_value(value)),
+            VdfChange::Changed { old, new, .. } => write!(f, "~ {}: {} -> {}", path, old, new),
+        }
+    }
+}
+
+fn display_path(path: &[VdfPathSegment]) -> String {
+    path.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" > ")
+}
+
+fn display_value(value: &VdfValue) -> String {
+    match value {
+        VdfValue::String(s) => s.clone(),
+        VdfValue::Object(obj) if obj.is_empty() => "{}".to_string(),
+        VdfValue::Object(obj) => format!("{{ {} entries }}", obj.len()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct VdfPatch {
+    pub changes: Vec<VdfChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VdfPatchError {
+    pub message: String,
+    /// Index of the change that could not be applied.
+    pub change: usize,
+    pub path: Vec<VdfPathSegment>,
+}
+
+impl std::fmt::Display for VdfPatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "VDF Patch Error in change {} at {}: {}",
+            self.change + 1,
+            display_path(&self.path),
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for VdfPatchError {}
+
+impl VdfPatch {
+    /// The changes that turn `old` into `new`.
+    pub fn diff(old: &VdfObject, new: &VdfObject) -> Self {
+        let mut changes = Vec::new();
+        diff_objects(old, new, &mut Vec::new(), &mut changes);
+        Self { changes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Applies every change to `root` in order. On error `root` is left
+    /// unchanged.
+    pub fn apply(&self, root: &mut VdfObject) -> Result<(), VdfPatchError> {
+        let mut patched = root.clone();
+        for (index, change) in self.changes.iter().enumerate() {
+            apply_change(&mut patched, change).map_err(|message| VdfPatchError {
+                message,
+                change: index,
+                path: change.path().to_vec(),
+            })?;
+        }
+        *root = patched;
+        Ok(())
+    }
+
+    /// Applies the changes one at a time, for merging a patch into a tree
+    /// that has diverged. Changes `root` already holds are skipped, and those
+    /// that do not fit are returned instead of applied.
+    pub fn apply_each(&self, root: &mut VdfObject) -> Vec<VdfPatchError> {
+        let mut errors = Vec::new();
+        for (index, change) in self.changes.iter().enumerate() {
+            if is_applied(root, change) {
+                continue;
+            }
+            if let Err(message) = apply_change(root, change) {
+                errors.push(VdfPatchError {
+                    message,
+                    change: index,
+                    path: change.path().to_vec(),
+                });
+            }
+        }
+        errors
+    }
+}
+
+/// Whether `root` already holds the result of `change`.
+fn is_applied(root: &VdfObject, change: &VdfChange) -> bool {
+    let Some((last, parents)) = change.path().split_last() else {
+        return false;
+    };
+    let mut obj = root;
+    for segment in parents {
+        match nth_index(obj, segment).map(|index| &obj.entries[index].1) {
+            Some(VdfValue::Object(child)) => obj = child,
+            _ => return matches!(change, VdfChange::Removed { .. }),
+        }
+    }
+    let current = nth_index(obj, last).map(|index| &obj.entries[index].1);
+
+    match change {
+        VdfChange::Added { value, .. } => current == Some(value),
+        VdfChange::Removed { .. } => current.is_none(),
+        VdfChange::Changed { new, .. } => {
+            matches!(current, Some(VdfValue::String(s)) if s == new)
+        }
+    }
+}
+
+fn diff_objects(
+    old: &VdfObject,
+    new: &VdfObject,
+    path: &mut Vec<VdfPathSegment>,
+    changes: &mut Vec<VdfChange>,
+) {
+    let mut seen: Vec<&str> = Vec::new();
+
+    for key in old.keys().chain(new.keys()) {
+        if seen.contains(&key.as_str()) {
+            continue;
+        }
+        seen.push(key);
+
+        let old_values: Vec<&VdfValue> = old.get_all(key).collect();
+        let new_values: Vec<&VdfValue> = new.get_all(key).collect();
+
+        for (n, (old_value, new_value)) in old_values.iter().zip(&new_values).enumerate() {
+            path.push(VdfPathSegment::new(key.as_str(), n));
+            match (old_value, new_value) {
+                (VdfValue::Object(a), VdfValue::Object(b)) => diff_objects(a, b, path, changes),
+                (VdfValue::String(a), VdfValue::String(b)) if a != b => {
+                    changes.push(VdfChange::Changed {
+                        path: path.clone(),
+                        old: a.clone(),
+                        new: b.clone(),
+                    });
+                }
+                (VdfValue::String(_), VdfValue::String(_)) => {}
+                _ => {
+                    changes.push(VdfChange::Removed {
+                        path: path.clone(),
+                        value: (*old_value).clone(),
+                    });
+                    changes.push(VdfChange::Added {
+                        path: path.clone(),
+                        value: (*new_value).clone(),
+                    });
+                }
+            }
+            path.pop();
+        }
+
+        // Later occurrences go first so earlier indices stay valid while the
+        // patch is applied.
+        for n in (new_values.len()..old_values.len()).rev() {
+            path.push(VdfPathSegment::new(key.as_str(), n));
+            changes.push(VdfChange::Removed {
+                path: path.clone(),
+                value: old_values[n].clone(),
+            });
+            path.pop();
+        }
+        for (n, value) in new_values.iter().enumerate().skip(old_values.len()) {
+            path.push(VdfPathSegment::new(key.as_str(), n));
+            changes.push(VdfChange::Added {
+                path: path.clone(),
+                value: (*value).clone(),
+            });
+            path.pop();
+        }
+    }
+}
+
+fn apply_change(root: &mut VdfObject, change: &VdfChange) -> Result<(), String> {
+    let Some((last, parents)) = change.path().split_last() else {
+        return Err("empty path".to_string());
+    };
+
+    let mut obj = root;
+    for segment in parents {
+        obj = nth_mut(obj, segment)
+            .and_then(VdfValue::as_object_mut)
+            .ok_or_else(|| format!("no object at '{}'", segment))?;
+    }
+
+    match change {
+        VdfChange::Added { value, .. } => {
+            let count = obj.count(last.key());
+            if count != last.occurrence() {
+                return Err(format!(
+                    "expected {} existing '{}' entries, found {}",
+                    last.occurrence(),
+                    last.key(),
+                    count
+                ));
+            }
+            obj.push(last.key(), value.clone());
+        }
+        VdfChange::Removed { value, .. } => {
+            let index = nth_index(obj, last).ok_or("entry does not exist")?;
+            if &obj.entries[index].1 != value {
+                return Err("entry differs from the removed value".to_string());
+            }
+            obj.entries.remove(index);
+        }
+        VdfChange::Changed { old, new, .. } => {
+            let current = nth_mut(obj, last).ok_or("entry does not exist")?;
+            match current {
+                VdfValue::String(s) if s == old => *s = new.clone(),
+                VdfValue::String(s) => return Err(format!("expected '{}', found '{}'", old, s)),
+                VdfValue::Object(_) => return Err("expected a string, found an object".to_string()),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn nth_index(obj: &VdfObject, segment: &VdfPathSegment) -> Option<usize> {
+    obj.entries
+        .iter()
+        .enumerate()
+        .filter(|(_, (key, _))| key == segment.key())
+        .nth(segment.occurrence())
+        .map(|(index, _)| index)
+}
+
+fn nth_mut<'a>(obj: &'a mut VdfObject, segment: &VdfPathSegment) -> Option<&'a mut VdfValue> {
+    let index = nth_index(obj, segment)?;
+    Some(&mut obj.entries[index].1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VdfChange, VdfPatch, VdfPathSegment};
+    use crate::inventory::vdf::{VdfParser, VdfValue};
+
+    #[test]
+    fn diffs_and_applies_with_repeated_keys() {
+        let old = VdfParser::parse(
+            r#"
+            "items"
+            {
+                "1" { "level" "1" "attributes" { "6" "44" } }
+                "2" { "level" "1" }
+                "tag" "a"
+                "tag" "b"
+                "tag" "c"
+            }
+            "#,
+        )
+        .unwrap();
+        let new = VdfParser::parse(
+            r#"
+            "items"
+            {
+                "1" { "level" "2" "attributes" "none" }
+                "tag" "a"
+                "tag" "x"
+                "3" { "level" "1" }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let patch = VdfPatch::diff(&old, &new);
+        let lines: Vec<String> = patch.changes.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines,
+            [
+                "~ items > 1 > level: 1 -> 2",
+                "- items > 1 > attributes: { 1 entries }",
+                "+ items > 1 > attributes: none",
+                "- items > 2: { 1 entries }",
+                "~ items > tag#2: b -> x",
+                "- items > tag#3: c",
+                "+ items > 3: { 1 entries }",
+            ]
+        );
+        assert_eq!(
+            patch.changes[4].path()[1],
+            VdfPathSegment::Occurrence("tag".to_string(), 1)
+        );
+
+        let json = serde_json::to_string(&patch).unwrap();
+        assert!(json.contains(r#"["items",["tag",1]]"#));
+        let patch: VdfPatch = serde_json::from_str(&json).unwrap();
+
+        let mut patched = old.clone();
+        patch.apply(&mut patched).unwrap();
+        assert!(VdfPatch::diff(&patched, &new).is_empty());
+        assert!(VdfPatch::diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn refuses_patches_for_diverged_trees() {
+        let old = VdfParser::parse(r#""a" { "x" "1" "y" "2" }"#).unwrap();
+        let new = VdfParser::parse(r#""a" { "x" "5" }"#).unwrap();
+        let patch = VdfPatch::diff(&old, &new);
+
+        let mut diverged = VdfParser::parse(r#""a" { "x" "3" "y" "2" }"#).unwrap();
+        let before = diverged.clone();
+        let error = patch.apply(&mut diverged).unwrap_err();
+
+        assert_eq!(error.change, 0);
+        assert_eq!(
+            error.to_string(),
+            "VDF Patch Error in change 1 at a > x: expected '1', found '3'"
+        );
+        assert_eq!(diverged, before);
+
+        let errors = patch.apply_each(&mut diverged);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].change, 0);
+        assert_eq!(diverged, VdfParser::parse(r#""a" { "x" "3" }"#).unwrap());
+        assert!(patch.apply_each(&mut new.clone()).is_empty());
+        assert!(matches!(
+            &patch.changes[1],
+            VdfChange::Removed { value: VdfValue::String(s), .. } if s == "2"
+        ));
+    }
+}
@@ -0,0 +1,170 @@
+//! Conditional suffixes and `#base`/`#include` directives.
+//!
+//! Valve's KeyValues format lets an entry end in a condition such as
+//! `[$WIN32]` or `[!$X360&&!$PS3]`; the entry is kept only if the condition
+//! holds. Files may also pull in other files at the root with
+//! `#include "file.vdf"` (entries are appended) or `#base "file.vdf"`
+//! (entries fill in keys the including file does not define).
+
+use super::{VdfObject, VdfValue};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// The set of platform symbols that conditional suffixes are evaluated
+/// against. Symbols are compared without the `$` and case-insensitively.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VdfConditions {
+    defined: HashSet<String>,
+}
+
+impl VdfConditions {
+    /// No symbols defined: every positive condition is false.
+    pub fn none() -> Self {
+        Self {
+            defined: HashSet::new(),
+        }
+    }
+
+    /// The symbols the game defines on the platform the editor runs on.
+    pub fn current_platform() -> Self {
+        let symbols: &[&str] = if cfg!(target_os = "windows") {
+            if cfg!(target_pointer_width = "64") {
+                &["WINDOWS", "WIN32", "WIN64"]
+            } else {
+                &["WINDOWS", "WIN32"]
+            }
+        } else if cfg!(target_os = "macos") {
+            &["OSX", "POSIX"]
+        } else if cfg!(target_os = "linux") {
+            &["LINUX", "POSIX"]
+        } else {
+            &["POSIX"]
+        };
+
+        symbols.iter().fold(Self::none(), |c, s| c.with(s))
+    }
+
+    pub fn with(mut self, symbol: &str) -> Self {
+        self.defined.insert(normalize_symbol(symbol));
+        self
+    }
+
+    pub fn without(mut self, symbol: &str) -> Self {
+        self.defined.remove(&normalize_symbol(symbol));
+        self
+    }
+
+    pub fn is_defined(&self, symbol: &str) -> bool {
+        self.defined.contains(&normalize_symbol(symbol))
+    }
+
+    /// Evaluates the text between the brackets of a conditional suffix.
+    ///
+    /// Terms are symbols optionally prefixed with `!`, joined with `&&` and
+    /// `||`; `&&` binds tighter than `||`, as in Valve's parser.
+    pub fn evaluate(&self, expression: &str) -> bool {
+        expression.split("||").any(|all| {
+            all.split("&&").all(|term| {
+                let term = term.trim();
+                match term.strip_prefix('!') {
+                    Some(negated) => !self.is_defined(negated),
+                    None => self.is_defined(term),
+                }
+            })
+        })
+    }
+}
+
+impl Default for VdfConditions {
+    fn default() -> Self {
+        Self::current_platform()
+    }
+}
+
+fn normalize_symbol(symbol: &str) -> String {
+    symbol.trim().trim_start_matches('$').to_ascii_uppercase()
+}
+
+/// Loads the files named by `#base` and `#include` directives.
+pub trait VdfIncludeResolver {
+    fn resolve(&self, path: &str) -> std::io::Result<String>;
+}
+
+/// Resolves include paths relative to a directory on disk.
+pub struct VdfFileResolver {
+    root: PathBuf,
+}
+
+impl VdfFileResolver {
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl VdfIncludeResolver for VdfFileResolver {
+    fn resolve(&self, path: &str) -> std::io::Result<String> {
+        std::fs::read_to_string(self.root.join(path))
+    }
+}
+
+impl<F> VdfIncludeResolver for F
+where
+    F: Fn(&str) -> std::io::Result<String>,
+{
+    fn resolve(&self, path: &str) -> std::io::Result<String> {
+        self(path)
+    }
+}
+
+/// How [`VdfParser::parse_with`](super::VdfParser::parse_with) treats
+/// conditionals and directives.
+///
+/// Without a resolver, `#base` and `#include` are kept as ordinary entries.
+#[derive(Default)]
+pub struct VdfParseOptions<'a> {
+    pub conditions: VdfConditions,
+    pub resolver: Option<&'a dyn VdfIncludeResolver>,
+}
+
+impl<'a> VdfParseOptions<'a> {
+    pub fn with_resolver(resolver: &'a dyn VdfIncludeResolver) -> Self {
+        Self {
+            resolver: Some(resolver),
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Directive {
+    Base,
+    Include,
+}
+
+impl Directive {
+    pub(crate) fn from_key(key: &str) -> Option<Self> {
+        if key.eq_ignore_ascii_case("#base") {
+            Some(Directive::Base)
+        } else if key.eq_ignore_ascii_case("#include") {
+            Some(Directive::Include)
+        } else {
+            None
+        }
+    }
+}
+
+/// Adds the entries of a `#base` file that `target` does not already define,
+/// descending into objects present in both.
+pub(crate) fn merge_base(target: &mut VdfObject, base: VdfObject) {
+    for (key, value) in base {
+        match (target.get_mut(&key), value) {
+            (Some(VdfValue::Object(existing)), VdfValue::Object(inherited)) => {
+                merge_base(existing, inherited);
+            }
+            (Some(_), _) => {}
+            (None, value) => target.push(key, value),
+        }
+    }
+}
@@ -0,0 +1,973 @@
+mod binary;
+mod cst;
+mod diff;
+mod directives;
+mod query;
+mod stream;
+
+pub use binary::VdfBinary;
+pub use cst::VdfDocument;
+pub use diff::{VdfChange, VdfPatch, VdfPatchError, VdfPathSegment};
+pub use directives::{VdfConditions, VdfFileResolver, VdfIncludeResolver, VdfParseOptions};
+pub use query::{VdfMatch, VdfQuery, VdfQueryError};
+pub use stream::{VdfEvent, VdfReader};
+
+use directives::{Directive, merge_base};
+use std::borrow::Cow;
+
+/// Nesting limit for `#base`/`#include`, which also stops include cycles.
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VdfValue {
+    String(String),
+    Object(VdfObject),
+}
+
+impl VdfValue {
+    pub fn as_string(&self) -> Option<&str> {
+        match self {
+            VdfValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&VdfObject> {
+        match self {
+            VdfValue::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut VdfObject> {
+        match self {
+            VdfValue::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    /// Runs a query against an object value; strings have no matches.
+    pub fn select(&self, query: &str) -> Result<Vec<VdfMatch<'_>>, VdfQueryError> {
+        let query = VdfQuery::parse(query)?;
+        Ok(self.as_object().map(|o| query.find(o)).unwrap_or_default())
+    }
+}
+
+/// An ordered list of key/value entries.
+///
+/// KeyValues files may repeat a key inside the same block and the order of
+/// entries is meaningful, so entries are kept exactly as they were parsed.
+/// Single-key lookups resolve to the last occurrence, matching the way later
+/// keys override earlier ones when the file is read into a map.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VdfObject {
+    entries: Vec<(String, VdfValue)>,
+}
+
+impl VdfObject {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&VdfValue> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut VdfValue> {
+        self.entries
+            .iter_mut()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn get_first(&self, key: &str) -> Option<&VdfValue> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Every value stored under `key`, in document order.
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a VdfValue> + 'a {
+        self.entries
+            .iter()
+            .filter(move |(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// Every object stored under `key`, in document order. Non-object values are skipped.
+    pub fn objects<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a VdfObject> + 'a {
+        self.get_all(key).filter_map(VdfValue::as_object)
+    }
+
+    /// Concatenates the entries of every object stored under `key`.
+    pub fn merged_object(&self, key: &str) -> VdfObject {
+        self.objects(key)
+            .flat_map(|obj| obj.entries.iter().cloned())
+            .collect()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entries.iter().any(|(k, _)| k == key)
+    }
+
+    pub fn count(&self, key: &str) -> usize {
+        self.entries.iter().filter(|(k, _)| k == key).count()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &VdfValue> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, (String, VdfValue)> {
+        self.entries.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, (String, VdfValue)> {
+        self.entries.iter_mut()
+    }
+
+    /// Appends an entry, keeping any existing entries with the same key.
+    pub fn push(&mut self, key: impl Into<String>, value: VdfValue) {
+        self.entries.push((key.into(), value));
+    }
+
+    /// Replaces the last entry with the same key in place, or appends a new entry.
+    pub fn insert(&mut self, key: impl Into<String>, value: VdfValue) -> Option<VdfValue> {
+        let key = key.into();
+        match self.get_mut(&key) {
+            Some(existing) => Some(std::mem::replace(existing, value)),
+            None => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Removes every entry with the given key and returns the last removed value.
+    pub fn remove(&mut self, key: &str) -> Option<VdfValue> {
+        let mut removed = None;
+        self.entries.retain_mut(|(k, v)| {
+            if k == key {
+                removed = Some(std::mem::replace(v, VdfValue::String(String::new())));
+                false
+            } else {
+                true
+            }
+        });
+        removed
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(&str, &VdfValue) -> bool) {
+        self.entries.retain(|(k, v)| f(k, v));
+    }
+
+    /// Runs a [`VdfQuery`] such as `items/*[def_index=7]/attributes`.
+    pub fn select(&self, query: &str) -> Result<Vec<VdfMatch<'_>>, VdfQueryError> {
+        Ok(VdfQuery::parse(query)?.find(self))
+    }
+
+    /// The string at `query`. Like [`get`](Self::get), the last match wins;
+    /// an invalid query matches nothing.
+    pub fn select_str(&self, query: &str) -> Option<&str> {
+        self.select(query)
+            .ok()?
+            .into_iter()
+            .rev()
+            .find_map(|m| m.value.as_string())
+    }
+
+    /// Replaces every value matched by `query`; see [`VdfQuery::set`].
+    pub fn set_at(&mut self, query: &str, value: VdfValue) -> Result<usize, VdfQueryError> {
+        Ok(VdfQuery::parse(query)?.set(self, value))
+    }
+
+    /// Removes every entry matched by `query`.
+    pub fn delete_at(&mut self, query: &str) -> Result<usize, VdfQueryError> {
+        Ok(VdfQuery::parse(query)?.delete(self))
+    }
+}
+
+impl FromIterator<(String, VdfValue)> for VdfObject {
+    fn from_iter<I: IntoIterator<Item = (String, VdfValue)>>(iter: I) -> Self {
+        Self {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<(String, VdfValue)> for VdfObject {
+    fn extend<I: IntoIterator<Item = (String, VdfValue)>>(&mut self, iter: I) {
+        self.entries.extend(iter);
+    }
+}
+
+impl IntoIterator for VdfObject {
+    type Item = (String, VdfValue);
+    type IntoIter = std::vec::IntoIter<(String, VdfValue)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a VdfObject {
+    type Item = &'a (String, VdfValue);
+    type IntoIter = std::slice::Iter<'a, (String, VdfValue)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+pub struct VdfParser;
+
+impl VdfParser {
+    pub fn parse(content: &str) -> Result<VdfObject, VdfParseError> {
+        Self::parse_with(content, &VdfParseOptions::default())
+    }
+
+    /// Parses `content`, dropping entries whose conditional suffix does not
+    /// hold and expanding root-level `#base`/`#include` directives through the
+    /// configured resolver.
+    pub fn parse_with(
+        content: &str,
+        options: &VdfParseOptions,
+    ) -> Result<VdfObject, VdfParseError> {
+        Self::parse_nested(content, options, 0, false).map(|(obj, _)| obj)
+    }
+
+    /// Parses as much of `content` as possible, returning every entry that
+    /// could be read together with a diagnostic for each error.
+    ///
+    /// After an error, parsing resumes on the next line or at the closing
+    /// brace that ended the entry; an unclosed quote ends at the line break.
+    pub fn parse_recovering(content: &str) -> (VdfObject, Vec<VdfParseError>) {
+        Self::parse_recovering_with(content, &VdfParseOptions::default())
+    }
+
+    pub fn parse_recovering_with(
+        content: &str,
+        options: &VdfParseOptions,
+    ) -> (VdfObject, Vec<VdfParseError>) {
+        // A recovering parse reports errors instead of returning them.
+        Self::parse_nested(content, options, 0, true)
+            .unwrap_or_else(|error| (VdfObject::new(), vec![error]))
+    }
+
+    fn parse_nested(
+        content: &str,
+        options: &VdfParseOptions,
+        depth: usize,
+        recovering: bool,
+    ) -> Result<(VdfObject, Vec<VdfParseError>), VdfParseError> {
+        let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+        let mut parser = VdfTokenizer::new(content);
+        if recovering {
+            parser.diagnostics = Some(Vec::new());
+        }
+
+        let obj = Self::parse_root(&mut parser, options, depth)?;
+        Ok((obj, parser.diagnostics.unwrap_or_default()))
+    }
+
+    fn parse_root(
+        parser: &mut VdfTokenizer,
+        options: &VdfParseOptions,
+        depth: usize,
+    ) -> Result<VdfObject, VdfParseError> {
+        parser.skip_whitespace();
+
+        if parser.consume("{") {
+            return Self::parse_object(parser, &options.conditions);
+        }
+
+        let mut obj = VdfObject::new();
+        let mut directives = Vec::new();
+
+        loop {
+            parser.skip_whitespace();
+            if parser.position >= parser.content.len() {
+                break;
+            }
+
+            let start = parser.position;
+            match Self::parse_entry(parser, &options.conditions) {
+                Ok(Some((key, value))) => match (Directive::from_key(&key), value) {
+                    (Some(directive), VdfValue::String(file)) if options.resolver.is_some() => {
+                        directives.push((directive, file, start));
+                    }
+                    (_, value) => obj.push(key, value),
+                },
+                Ok(None) => {}
+                Err(error) => parser.recover(error, start, 0)?,
+            }
+        }
+
+        let Some(resolver) = options.resolver else {
+            return Ok(obj);
+        };
+
+        for (directive, file, position) in directives {
+            let included = if depth >= MAX_INCLUDE_DEPTH {
+                Err(format!("Too many nested includes at \"{}\"", file))
+            } else {
+                resolver
+                    .resolve(&file)
+                    .map_err(|e| format!("Cannot read \"{}\": {}", file, e))
+                    .and_then(|content| {
+                        Self::parse_nested(&content, options, depth + 1, parser.is_recovering())
+                            .map_err(|e| describe_include_error(&file, &e))
+                    })
+            };
+
+            match included {
+                Ok((included, diagnostics)) => {
+                    for e in diagnostics {
+                        parser.report(VdfParseError::new(
+                            describe_include_error(&file, &e),
+                            position,
+                        ))?;
+                    }
+                    match directive {
+                        Directive::Include => obj.extend(included),
+                        Directive::Base => merge_base(&mut obj, included),
+                    }
+                }
+                Err(message) => parser.report(VdfParseError::new(message, position))?,
+            }
+        }
+
+        Ok(obj)
+    }
+
+    fn parse_object(
+        parser: &mut VdfTokenizer,
+        conditions: &VdfConditions,
+    ) -> Result<VdfObject, VdfParseError> {
+        let mut obj = VdfObject::new();
+
+        loop {
+            parser.skip_whitespace();
+
+            if parser.peek("}") || parser.position >= parser.content.len() {
+                break;
+            }
+
+            let start = parser.position;
+            let depth = parser.path.len();
+            match Self::parse_entry(parser, conditions) {
+                Ok(Some((key, value))) => obj.push(key, value),
+                Ok(None) => {}
+                Err(error) => parser.recover(error, start, depth)?,
+            }
+        }
+
+        if let Err(error) = parser.expect("}") {
+            parser.report(error)?;
+        }
+        Ok(obj)
+    }
+
+    /// Parses one key with its value and optional conditional suffix, returning
+    /// `None` if the condition does not hold.
+    fn parse_entry(
+        parser: &mut VdfTokenizer,
+        conditions: &VdfConditions,
+    ) -> Result<Option<(String, VdfValue)>, VdfParseError> {
+        let key = parser.parse_key()?;
+        parser.path.push(key.clone());
+        parser.skip_whitespace();
+
+        // Objects carry their condition between the key and the brace.
+        let (value, condition) = match parser.parse_condition()? {
+            Some(condition) => {
+                parser.skip_whitespace();
+                parser.expect("{")?;
+                let value = VdfValue::Object(Self::parse_object(parser, conditions)?);
+                (value, Some(condition))
+            }
+            None if parser.consume("{") => {
+                let value = VdfValue::Object(Self::parse_object(parser, conditions)?);
+                (value, None)
+            }
+            None => {
+                let value = VdfValue::String(parser.parse_value()?);
+                parser.skip_inline_whitespace();
+                (value, parser.parse_condition()?)
+            }
+        };
+
+        parser.path.pop();
+        let keep = condition.is_none_or(|condition| conditions.evaluate(condition));
+        Ok(keep.then_some((key, value)))
+    }
+
+    /// Writes a value in the editor's canonical layout, keeping entry order.
+    pub fn to_string(value: &VdfValue) -> String {
+        Self::to_string_internal(value, 0)
+    }
+
+    fn to_string_internal(value: &VdfValue, depth: usize) -> String {
+        match value {
+            VdfValue::String(s) => format!("\"{}\"", Self::escape_string(s)),
+            VdfValue::Object(o) => {
+                let indent_str = "\t".repeat(depth);
+                let mut result = String::new();
+
+                for (key, val) in o {
+                    match val {
+                        VdfValue::String(s) => {
+                            result.push_str(&format!(
+                                "{}\"{}\"\t\t\"{}\"\n",
+                                indent_str,
+                                Self::escape_string(key),
+                                Self::escape_string(s)
+                            ));
+                        }
+                        VdfValue::Object(_inner) => {
+                            result.push_str(&format!(
+                                "{}\"{}\"\n{}{{\n",
+                                indent_str,
+                                Self::escape_string(key),
+                                indent_str
+                            ));
+                            result.push_str(&Self::to_string_internal(val, depth + 1));
+                            result.push_str(&format!("{}}}\n", indent_str));
+                        }
+                    }
+                }
+                result
+            }
+        }
+    }
+
+    fn escape_string(s: &str) -> String {
+        s.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+            .replace('\t', "\\t")
+    }
+}
+
+pub(crate) fn decode_escape(ch: char) -> Option<char> {
+    match ch {
+        'n' => Some('\n'),
+        'r' => Some('\r'),
+        't' => Some('\t'),
+        '"' => Some('"'),
+        '\\' => Some('\\'),
+        _ => None,
+    }
+}
+
+pub(crate) fn get_string_from_obj(obj: &VdfObject, key: &str) -> Option<String> {
+    obj.get(key)
+        .and_then(|v| v.as_string().map(|s| s.to_string()))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VdfParseError {
+    pub message: String,
+    /// Byte offset into the parsed text.
+    pub position: usize,
+    /// 1-based line, or 0 if the error has not been located in its source.
+    pub line: usize,
+    /// 1-based column, counted in characters.
+    pub column: usize,
+    /// Keys of the objects being parsed, outermost first.
+    pub key_path: Vec<String>,
+    /// The offending line followed by a caret under the error column.
+    pub snippet: String,
+}
+
+impl VdfParseError {
+    pub fn new(message: impl Into<String>, position: usize) -> Self {
+        Self {
+            message: message.into(),
+            position,
+            line: 0,
+            column: 0,
+            key_path: Vec::new(),
+            snippet: String::new(),
+        }
+    }
+
+    /// Fills in line, column, snippet and key path from the parsed source.
+    fn locate(mut self, source: &str, key_path: &[String]) -> Self {
+        if self.line != 0 {
+            return self;
+        }
+
+        let position = self.position.min(source.len());
+        let line_start = source[..position].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[position..]
+            .find('\n')
+            .map_or(source.len(), |i| position + i);
+        let prefix = &source[line_start..position];
+
+        self.line = source[..line_start].matches('\n').count() + 1;
+        self.column = prefix.chars().count() + 1;
+        // Keep tabs so the caret lines up with tab-indented source.
+        let padding: String = prefix
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        self.snippet = format!(
+            "{}\n{}^",
+            source[line_start..line_end].trim_end_matches('\r'),
+            padding
+        );
+        self.key_path = key_path.to_vec();
+        self
+    }
+}
+
+impl std::fmt::Display for VdfParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            return write!(
+                f,
+                "VDF Parse Error at position {}: {}",
+                self.position, self.message
+            );
+        }
+
+        write!(
+            f,
+            "VDF Parse Error at line {}, column {}",
+            self.line, self.column
+        )?;
+        if !self.key_path.is_empty() {
+            write!(f, " (in {})", self.key_path.join(" > "))?;
+        }
+        write!(f, ": {}", self.message)?;
+        if !self.snippet.is_empty() {
+            write!(f, "\n{}", self.snippet)?;
+        }
+        Ok(())
+    }
+}
+
+fn describe_include_error(file: &str, error: &VdfParseError) -> String {
+    format!(
+        "In \"{}\" at line {}, column {}: {}",
+        file, error.line, error.column, error.message
+    )
+}
+
+impl std::error::Error for VdfParseError {}
+
+struct VdfTokenizer<'a> {
+    content: &'a str,
+    position: usize,
+    /// Keys of the entries currently being parsed.
+    path: Vec<String>,
+    /// Collected errors when parsing in recovering mode.
+    diagnostics: Option<Vec<VdfParseError>>,
+}
+
+impl<'a> VdfTokenizer<'a> {
+    fn new(content: &'a str) -> Self {
+        Self {
+            content,
+            position: 0,
+            path: Vec::new(),
+            diagnostics: None,
+        }
+    }
+
+    fn is_recovering(&self) -> bool {
+        self.diagnostics.is_some()
+    }
+
+    /// Locates `error` and records it when recovering, or returns it.
+    fn report(&mut self, error: VdfParseError) -> Result<(), VdfParseError> {
+        let error = error.locate(self.content, &self.path);
+        match &mut self.diagnostics {
+            Some(diagnostics) => {
+                let reported = diagnostics
+                    .iter()
+                    .any(|d| d.position == error.position && d.message == error.message);
+                if !reported {
+                    diagnostics.push(error);
+                }
+                Ok(())
+            }
+            None => Err(error),
+        }
+    }
+
+    /// Reports `error` for the entry that started at `start`, then skips to
+    /// where parsing can resume: the closing brace the error stopped at, or
+    /// the start of the next line.
+    fn recover(
+        &mut self,
+        error: VdfParseError,
+        start: usize,
+        depth: usize,
+    ) -> Result<(), VdfParseError> {
+        self.report(error)?;
+        self.path.truncate(depth);
+
+        if self.position > start && self.peek("}") {
+            return Ok(());
+        }
+
+        let from = self.position.max(start + 1).min(self.content.len());
+        self.position = self.content[from..]
+            .find('\n')
+            .map_or(self.content.len(), |offset| from + offset + 1);
+        Ok(())
+    }
+
+    /// Skips whitespace and `//` line comments.
+    fn skip_whitespace(&mut self) {
+        while self.position < self.content.len() {
+            match self.content.as_bytes()[self.position] {
+                b' ' | b'\n' | b'\r' | b'\t' => self.position += 1,
+                b'/' if self.peek("//") => {
+                    self.position = self.content[self.position..]
+                        .find('\n')
+                        .map_or(self.content.len(), |offset| self.position + offset);
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn skip_inline_whitespace(&mut self) {
+        let rest = &self.content[self.position..];
+        self.position += rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    }
+
+    fn peek(&self, s: &str) -> bool {
+        self.content[self.position..].starts_with(s)
+    }
+
+    fn consume(&mut self, s: &str) -> bool {
+        if self.peek(s) {
+            self.position += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), VdfParseError> {
+        self.skip_whitespace();
+        if self.consume(s) {
+            Ok(())
+        } else {
+            Err(VdfParseError::new(
+                format!("Expected '{}'", s),
+                self.position,
+            ))
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<String, VdfParseError> {
+        self.parse_key_cow().map(Cow::into_owned)
+    }
+
+    fn parse_value(&mut self) -> Result<String, VdfParseError> {
+        self.parse_value_cow().map(Cow::into_owned)
+    }
+
+    fn parse_key_cow(&mut self) -> Result<Cow<'a, str>, VdfParseError> {
+        self.skip_whitespace();
+
+        if self.peek("\"") {
+            self.parse_string_cow()
+        } else {
+            self.parse_unquoted("key").map(Cow::Borrowed)
+        }
+    }
+
+    fn parse_value_cow(&mut self) -> Result<Cow<'a, str>, VdfParseError> {
+        if self.peek("\"") {
+            self.parse_string_cow()
+        } else {
+            self.parse_unquoted("value").map(Cow::Borrowed)
+        }
+    }
+
+    fn parse_unquoted(&mut self, expected: &str) -> Result<&'a str, VdfParseError> {
+        let content = self.content;
+        let rest = &content[self.position..];
+        let end = rest
+            .find(['{', '}', ' ', '\t', '\n', '\r'])
+            .unwrap_or(rest.len());
+
+        let token = &rest[..end];
+        if token.is_empty() {
+            return Err(VdfParseError::new(
+                format!("Expected {}", expected),
+                self.position,
+            ));
+        }
+
+        self.position += end;
+        Ok(token)
+    }
+
+    /// Reads a `[...]` conditional suffix, returning the text between the
+    /// brackets.
+    fn parse_condition(&mut self) -> Result<Option<&'a str>, VdfParseError> {
+        if !self.peek("[") {
+            return Ok(None);
+        }
+
+        let content = self.content;
+        let start = self.position;
+        match content[start..].find(']') {
+            Some(offset) => {
+                self.position += offset + 1;
+                Ok(Some(&content[start + 1..start + offset]))
+            }
+            None => Err(VdfParseError::new("Unterminated condition", start)),
+        }
+    }
+
+    /// Reads a quoted string, borrowing it from the source unless it contains
+    /// escape sequences.
+    fn parse_string_cow(&mut self) -> Result<Cow<'a, str>, VdfParseError> {
+        if !self.consume("\"") {
+            return Err(VdfParseError::new("Expected '\"'", self.position));
+        }
+
+        let content = self.content;
+        let start = self.position - 1;
+        let stops: &[char] = if self.is_recovering() {
+            &['"', '\\', '\n']
+        } else {
+            &['"', '\\']
+        };
+        let mut result = String::new();
+
+        // Copy runs of plain text at once; only quotes, escapes and (when
+        // recovering) line breaks need attention, and all are ASCII, so every
+        // slice stays on a char boundary.
+        while let Some(offset) = content[self.position..].find(stops) {
+            let text = &content[self.position..self.position + offset];
+            self.position += offset;
+
+            if self.consume("\"") {
+                if result.is_empty() {
+                    return Ok(Cow::Borrowed(text));
+                }
+                result.push_str(text);
+                return Ok(Cow::Owned(result));
+            }
+            result.push_str(text);
+            if self.peek("\n") {
+                self.report(VdfParseError::new("Unterminated string", start))?;
+                return Ok(Cow::Owned(result.trim_end_matches('\r').to_string()));
+            }
+
+            self.position += 1;
+            let Some(escaped) = content[self.position..].chars().next() else {
+                break;
+            };
+            result.push(decode_escape(escaped).unwrap_or(escaped));
+            self.position += escaped.len_utf8();
+        }
+
+        self.position = content.len();
+        Err(VdfParseError::new("Unterminated string", self.position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VdfConditions, VdfParseOptions, VdfParser, VdfValue};
+
+    #[test]
+    fn keeps_entry_order() {
+        let vdf = VdfParser::parse(r#""root" { "b" "1" "a" "2" "c" "3" }"#).unwrap();
+        let root = vdf.get("root").and_then(|v| v.as_object()).unwrap();
+
+        let keys: Vec<&str> = root.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["b", "a", "c"]);
+    }
+
+    #[test]
+    fn keeps_duplicate_keys() {
+        let content = r#"
+            "root"
+            {
+                "key" "first"
+                "block" { "a" "1" }
+                "key" "second"
+                "block" { "b" "2" }
+            }
+        "#;
+
+        let vdf = VdfParser::parse(content).unwrap();
+        let root = vdf.get("root").and_then(|v| v.as_object()).unwrap();
+
+        assert_eq!(root.len(), 4);
+        assert_eq!(root.count("key"), 2);
+        assert_eq!(
+            root.get("key").and_then(VdfValue::as_string),
+            Some("second")
+        );
+        assert_eq!(
+            root.get_first("key").and_then(VdfValue::as_string),
+            Some("first")
+        );
+        assert_eq!(root.objects("block").count(), 2);
+
+        let merged = root.merged_object("block");
+        let keys: Vec<&str> = merged.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["a", "b"]);
+    }
+
+    #[test]
+    fn insert_replaces_last_duplicate_in_place() {
+        let mut vdf = VdfParser::parse(r#""k" "1" "other" "x" "k" "2""#).unwrap();
+
+        vdf.insert("k", VdfValue::String("3".to_string()));
+
+        let values: Vec<&str> = vdf.get_all("k").filter_map(VdfValue::as_string).collect();
+        assert_eq!(values, ["1", "3"]);
+        assert_eq!(vdf.len(), 3);
+    }
+
+    #[test]
+    fn writes_duplicate_keys() {
+        let vdf = VdfParser::parse(r#""k" "1" "k" "2""#).unwrap();
+
+        let output = VdfParser::to_string(&VdfValue::Object(vdf));
+
+        assert_eq!(output, "\"k\"\t\t\"1\"\n\"k\"\t\t\"2\"\n");
+    }
+
+    #[test]
+    fn skips_comments_and_evaluates_conditionals() {
+        let content = r#"
+            // header
+            "root"
+            {
+                "a" "pc" [$WIN32||$LINUX] // platform value
+                "a" "console" [$X360]
+                "b" plain
+                "block" [!$X360&&$WIN32]
+                {
+                    "c" "1"
+                }
+            }
+        "#;
+        let options = VdfParseOptions {
+            conditions: VdfConditions::none().with("$WIN32"),
+            resolver: None,
+        };
+
+        let vdf = VdfParser::parse_with(content, &options).unwrap();
+        let root = vdf.get("root").and_then(|v| v.as_object()).unwrap();
+
+        let values: Vec<&str> = root.get_all("a").filter_map(VdfValue::as_string).collect();
+        assert_eq!(values, ["pc"]);
+        assert_eq!(root.get("b").and_then(VdfValue::as_string), Some("plain"));
+        assert!(root.contains_key("block"));
+
+        let options = VdfParseOptions {
+            conditions: VdfConditions::none().with("X360"),
+            resolver: None,
+        };
+        let vdf = VdfParser::parse_with(content, &options).unwrap();
+        let root = vdf.get("root").and_then(|v| v.as_object()).unwrap();
+        assert_eq!(root.get("a").and_then(VdfValue::as_string), Some("console"));
+        assert!(!root.contains_key("block"));
+    }
+
+    #[test]
+    fn expands_base_and_include_directives() {
+        let resolver = |path: &str| match path {
+            "base.txt" => Ok(r#""root" { "a" "base" "b" "base" }"#.to_string()),
+            "extra.txt" => Ok(r#""extra" { "c" "1" }"#.to_string()),
+            "loop.txt" => Ok(r#"#include "loop.txt""#.to_string()),
+            _ => Err(std::io::Error::from(std::io::ErrorKind::NotFound)),
+        };
+        let options = VdfParseOptions::with_resolver(&resolver);
+
+        let content = r#"
+            #base "base.txt"
+            #include "extra.txt"
+            "root" { "a" "own" }
+        "#;
+        let vdf = VdfParser::parse_with(content, &options).unwrap();
+
+        let keys: Vec<&str> = vdf.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["root", "extra"]);
+        let root = vdf.get("root").and_then(|v| v.as_object()).unwrap();
+        assert_eq!(root.get("a").and_then(VdfValue::as_string), Some("own"));
+        assert_eq!(root.get("b").and_then(VdfValue::as_string), Some("base"));
+
+        assert!(VdfParser::parse_with(r#"#include "missing.txt""#, &options).is_err());
+        assert!(VdfParser::parse_with(r#"#include "loop.txt""#, &options).is_err());
+
+        // Without a resolver the directives are plain entries.
+        let vdf = VdfParser::parse(content).unwrap();
+        assert_eq!(
+            vdf.get("#base").and_then(VdfValue::as_string),
+            Some("base.txt")
+        );
+    }
+
+    #[test]
+    fn reports_line_column_and_key_path() {
+        let content =
+            "\"items\"\n{\n\t\"123\"\n\t{\n\t\t\"attributes\"\n\t\t{\n\t\t\t\"6\" }\n\t}\n}\n";
+
+        let error = VdfParser::parse(content).unwrap_err();
+
+        assert_eq!((error.line, error.column), (7, 8));
+        assert_eq!(error.key_path, ["items", "123", "attributes", "6"]);
+        assert_eq!(error.snippet, "\t\t\t\"6\" }\n\t\t\t    ^");
+        assert!(
+            error.to_string().starts_with(
+                "VDF Parse Error at line 7, column 8 (in items > 123 > attributes > 6)"
+            )
+        );
+    }
+
+    #[test]
+    fn recovers_after_errors() {
+        let content = r#"
+            "root"
+            {
+                "a" "1"
+                "broken" "unterminated
+                "b" "2"
+                "missing" }
+            "after" { "c" "3"
+        "#;
+
+        let (vdf, diagnostics) = VdfParser::parse_recovering(content);
+
+        let root = vdf.get("root").and_then(|v| v.as_object()).unwrap();
+        assert_eq!(root.get("a").and_then(VdfValue::as_string), Some("1"));
+        assert_eq!(root.get("b").and_then(VdfValue::as_string), Some("2"));
+        let after = vdf.get("after").and_then(|v| v.as_object()).unwrap();
+        assert_eq!(after.get("c").and_then(VdfValue::as_string), Some("3"));
+
+        let lines: Vec<usize> = diagnostics.iter().map(|d| d.line).collect();
+        assert_eq!(lines, [5, 7, 9]);
+    }
+}
@@ -0,0 +1,367 @@
+//! Path queries over VDF value trees.
+//!
+//! A query is a `/`-separated list of segments, each matching the keys of one
+//! nesting level:
+//!
+//! - `items` matches the key `items` exactly; `"key with / in it"` quotes a key.
+//! - `*` matches any key.
+//! - A segment may be followed by predicates on the matched object's own
+//!   entries: `[def_index=7]`, `[custom_name!=x]` or `[attributes]` (the key
+//!   exists). Values may be quoted, and a segment may have several predicates.
+//!
+//! For example `items/*[def_index=7]/attributes/6` finds attribute 6 of every
+//! item with definition index 7. Repeated keys are all visited, in document
+//! order.
+
+use super::{VdfObject, VdfValue};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VdfQuery {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Segment {
+    key: Option<String>,
+    predicates: Vec<Predicate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Predicate {
+    key: String,
+    test: Test,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Test {
+    Exists,
+    Equals(String),
+    NotEquals(String),
+}
+
+/// A value found by a query, with the keys leading to it from the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VdfMatch<'a> {
+    pub path: Vec<&'a str>,
+    pub value: &'a VdfValue,
+}
+
+impl VdfMatch<'_> {
+    /// The key the value is stored under.
+    pub fn key(&self) -> &str {
+        self.path.last().copied().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VdfQueryError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl std::fmt::Display for VdfQueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "VDF Query Error at position {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl std::error::Error for VdfQueryError {}
+
+impl VdfQuery {
+    pub fn parse(query: &str) -> Result<Self, VdfQueryError> {
+        let mut parser = QueryParser { query, position: 0 };
+        let mut segments = Vec::new();
+
+        loop {
+            segments.push(parser.parse_segment()?);
+            if parser.position >= query.len() {
+                break;
+            }
+            parser.expect('/')?;
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Whether the query names a single path, without wildcards or predicates.
+    fn is_literal(&self) -> bool {
+        self.segments
+            .iter()
+            .all(|s| s.key.is_some() && s.predicates.is_empty())
+    }
+
+    pub fn find<'a>(&self, root: &'a VdfObject) -> Vec<VdfMatch<'a>> {
+        let mut matches = Vec::new();
+        find_in(root, &self.segments, &mut Vec::new(), &mut matches);
+        matches
+    }
+
+    /// Replaces every matched value with `value` and returns how many were
+    /// replaced. A literal path that matches nothing is created, along with any
+    /// missing parent objects.
+    pub fn set(&self, root: &mut VdfObject, value: VdfValue) -> usize {
+        let replaced = set_in(root, &self.segments, &value);
+        if replaced > 0 || !self.is_literal() {
+            return replaced;
+        }
+
+        let mut obj = root;
+        for segment in &self.segments[..self.segments.len() - 1] {
+            let key = segment.key.as_deref().unwrap_or_default();
+            if !matches!(obj.get(key), Some(VdfValue::Object(_))) {
+                obj.insert(key, VdfValue::Object(VdfObject::new()));
+            }
+            obj = obj
+                .get_mut(key)
+                .and_then(VdfValue::as_object_mut)
+                .expect("object was just inserted");
+        }
+        let key = self.segments[self.segments.len() - 1]
+            .key
+            .clone()
+            .unwrap_or_default();
+        obj.push(key, value);
+        1
+    }
+
+    /// Removes every matched entry and returns how many were removed.
+    pub fn delete(&self, root: &mut VdfObject) -> usize {
+        delete_in(root, &self.segments)
+    }
+}
+
+impl FromStr for VdfQuery {
+    type Err = VdfQueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Segment {
+    fn matches(&self, key: &str, value: &VdfValue) -> bool {
+        if self.key.as_deref().is_some_and(|k| k != key) {
+            return false;
+        }
+        if self.predicates.is_empty() {
+            return true;
+        }
+
+        let Some(obj) = value.as_object() else {
+            return false;
+        };
+        self.predicates.iter().all(|predicate| {
+            let mut values = obj.get_all(&predicate.key);
+            match &predicate.test {
+                Test::Exists => values.next().is_some(),
+                Test::Equals(expected) => values.any(|v| v.as_string() == Some(expected)),
+                Test::NotEquals(expected) => values.all(|v| v.as_string() != Some(expected)),
+            }
+        })
+    }
+}
+
+fn find_in<'a>(
+    obj: &'a VdfObject,
+    segments: &[Segment],
+    path: &mut Vec<&'a str>,
+    matches: &mut Vec<VdfMatch<'a>>,
+) {
+    let Some((segment, rest)) = segments.split_first() else {
+        return;
+    };
+
+    for (key, value) in obj {
+        if !segment.matches(key, value) {
+            continue;
+        }
+
+        path.push(key);
+        if rest.is_empty() {
+            matches.push(VdfMatch {
+                path: path.clone(),
+                value,
+            });
+        } else if let VdfValue::Object(inner) = value {
+            find_in(inner, rest, path, matches);
+        }
+        path.pop();
+    }
+}
+
+fn set_in(obj: &mut VdfObject, segments: &[Segment], new_value: &VdfValue) -> usize {
+    let Some((segment, rest)) = segments.split_first() else {
+        return 0;
+    };
+
+    let mut count = 0;
+    for (key, value) in obj.iter_mut() {
+        if !segment.matches(key, value) {
+            continue;
+        }
+
+        if rest.is_empty() {
+            *value = new_value.clone();
+            count += 1;
+        } else if let VdfValue::Object(inner) = value {
+            count += set_in(inner, rest, new_value);
+        }
+    }
+    count
+}
+
+fn delete_in(obj: &mut VdfObject, segments: &[Segment]) -> usize {
+    let Some((segment, rest)) = segments.split_first() else {
+        return 0;
+    };
+
+    if rest.is_empty() {
+        let before = obj.len();
+        obj.retain(|key, value| !segment.matches(key, value));
+        return before - obj.len();
+    }
+
+    let mut count = 0;
+    for (key, value) in obj.iter_mut() {
+        if segment.matches(key, value)
+            && let VdfValue::Object(inner) = value
+        {
+            count += delete_in(inner, rest);
+        }
+    }
+    count
+}
+
+struct QueryParser<'a> {
+    query: &'a str,
+    position: usize,
+}
+
+impl QueryParser<'_> {
+    fn error(&self, message: &str) -> VdfQueryError {
+        VdfQueryError {
+            message: message.to_string(),
+            position: self.position,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.query[self.position..].chars().next()
+    }
+
+    fn expect(&mut self, ch: char) -> Result<(), VdfQueryError> {
+        if self.peek() == Some(ch) {
+            self.position += ch.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", ch)))
+        }
+    }
+
+    fn parse_segment(&mut self) -> Result<Segment, VdfQueryError> {
+        let key = if self.peek() == Some('*') {
+            self.position += 1;
+            None
+        } else {
+            Some(self.parse_word(&['/', '['])?)
+        };
+
+        let mut predicates = Vec::new();
+        while self.peek() == Some('[') {
+            self.position += 1;
+            let key = self.parse_word(&['=', '!', ']'])?;
+            let test = if self.query[self.position..].starts_with("!=") {
+                self.position += 2;
+                Test::NotEquals(self.parse_word(&[']'])?)
+            } else if self.peek() == Some('=') {
+                self.position += 1;
+                Test::Equals(self.parse_word(&[']'])?)
+            } else {
+                Test::Exists
+            };
+            self.expect(']')?;
+            predicates.push(Predicate { key, test });
+        }
+
+        Ok(Segment { key, predicates })
+    }
+
+    /// A quoted string, or the text up to the next stop character.
+    fn parse_word(&mut self, stops: &[char]) -> Result<String, VdfQueryError> {
+        if self.peek() == Some('"') {
+            self.position += 1;
+            let rest = &self.query[self.position..];
+            let end = rest
+                .find('"')
+                .ok_or_else(|| self.error("Unterminated quote"))?;
+            self.position += end + 1;
+            return Ok(rest[..end].to_string());
+        }
+
+        let rest = &self.query[self.position..];
+        let end = rest.find(stops).unwrap_or(rest.len());
+        if end == 0 {
+            return Err(self.error("Expected key"));
+        }
+        self.position += end;
+        Ok(rest[..end].to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VdfQuery;
+    use crate::inventory::vdf::{VdfParser, VdfValue};
+
+    const SOURCE: &str = r#"
+        "items"
+        {
+            "1" { "def_index" "7" "attributes" { "6" "44" } }
+            "2" { "def_index" "9" "attributes" { "6" "12" } }
+            "3" { "def_index" "7" }
+        }
+    "#;
+
+    #[test]
+    fn finds_matches_with_wildcards_and_predicates() {
+        let vdf = VdfParser::parse(SOURCE).unwrap();
+
+        let matches = VdfQuery::parse("items/*[def_index=7][attributes]/attributes/6")
+            .unwrap()
+            .find(&vdf);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].path, ["items", "1", "attributes", "6"]);
+        assert_eq!(matches[0].value.as_string(), Some("44"));
+
+        let matches = VdfQuery::parse("items/*[def_index!=9]").unwrap().find(&vdf);
+        let keys: Vec<&str> = matches.iter().map(|m| m.key()).collect();
+        assert_eq!(keys, ["1", "3"]);
+
+        assert!(VdfQuery::parse("items/*[def_index=7").is_err());
+        assert!(VdfQuery::parse("items//x").is_err());
+    }
+
+    #[test]
+    fn sets_and_deletes_matches() {
+        let mut vdf = VdfParser::parse(SOURCE).unwrap();
+
+        let query = VdfQuery::parse("items/*/attributes/6").unwrap();
+        assert_eq!(query.set(&mut vdf, VdfValue::String("0".to_string())), 2);
+        assert_eq!(vdf.select_str("items/2/attributes/6"), Some("0"));
+
+        let created = VdfQuery::parse("items/3/attributes/6").unwrap();
+        assert_eq!(created.set(&mut vdf, VdfValue::String("5".to_string())), 1);
+        assert_eq!(vdf.select_str("items/3/attributes/6"), Some("5"));
+
+        let removed = VdfQuery::parse("items/*[def_index=7]")
+            .unwrap()
+            .delete(&mut vdf);
+        assert_eq!(removed, 2);
+        assert_eq!(vdf.select("items/*").unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,241 @@
+//! Pull-based reading of KeyValues text.
+//!
+//! [`VdfReader`] walks a document one entry at a time without building a
+//! tree. Keys and values borrow from the source unless they contain escape
+//! sequences, so skipping a section costs no allocations. Callers can turn the
+//! sections they care about into a [`VdfObject`] with
+//! [`VdfReader::read_object`] and pass over the rest with
+//! [`VdfReader::skip_object`].
+
+use super::{VdfConditions, VdfObject, VdfParseError, VdfTokenizer, VdfValue};
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VdfEvent<'a> {
+    /// A key with a string value.
+    String {
+        key: Cow<'a, str>,
+        value: Cow<'a, str>,
+    },
+    /// A key whose object value follows, up to the matching `ObjectEnd`.
+    ObjectStart {
+        key: Cow<'a, str>,
+    },
+    ObjectEnd,
+}
+
+pub struct VdfReader<'a> {
+    tokenizer: VdfTokenizer<'a>,
+    conditions: VdfConditions,
+    depth: usize,
+    failed: bool,
+}
+
+impl<'a> VdfReader<'a> {
+    /// Reads `content`, evaluating conditional suffixes for the current
+    /// platform. `#base`/`#include` directives are reported as plain entries.
+    pub fn new(content: &'a str) -> Self {
+        Self::with_conditions(content, VdfConditions::default())
+    }
+
+    pub fn with_conditions(content: &'a str, conditions: VdfConditions) -> Self {
+        let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+        Self {
+            tokenizer: VdfTokenizer::new(content),
+            conditions,
+            depth: 0,
+            failed: false,
+        }
+    }
+
+    /// Number of objects currently open.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The next entry, or `None` at the end of the document. Entries whose
+    /// condition does not hold are skipped.
+    pub fn next_event(&mut self) -> Result<Option<VdfEvent<'a>>, VdfParseError> {
+        self.next_event_inner()
+            .map_err(|e| e.locate(self.tokenizer.content, &self.tokenizer.path))
+    }
+
+    fn next_event_inner(&mut self) -> Result<Option<VdfEvent<'a>>, VdfParseError> {
+        loop {
+            self.tokenizer.skip_whitespace();
+
+            if self.tokenizer.position >= self.tokenizer.content.len() {
+                if self.depth > 0 {
+                    return Err(VdfParseError::new("Expected '}'", self.tokenizer.position));
+                }
+                return Ok(None);
+            }
+
+            if self.tokenizer.peek("}") {
+                if self.depth == 0 {
+                    return Err(VdfParseError::new(
+                        "Unexpected '}'",
+                        self.tokenizer.position,
+                    ));
+                }
+                self.tokenizer.position += 1;
+                self.tokenizer.path.pop();
+                self.depth -= 1;
+                return Ok(Some(VdfEvent::ObjectEnd));
+            }
+
+            let key = self.tokenizer.parse_key_cow()?;
+            self.tokenizer.skip_whitespace();
+
+            let condition = self.tokenizer.parse_condition()?;
+            if condition.is_some() {
+                self.tokenizer.skip_whitespace();
+                if !self.tokenizer.peek("{") {
+                    return Err(VdfParseError::new("Expected '{'", self.tokenizer.position));
+                }
+            }
+
+            if self.tokenizer.consume("{") {
+                self.tokenizer.path.push(key.to_string());
+                self.depth += 1;
+                if condition.is_some_and(|c| !self.conditions.evaluate(c)) {
+                    self.skip_object()?;
+                    continue;
+                }
+                return Ok(Some(VdfEvent::ObjectStart { key }));
+            }
+
+            let value = self.tokenizer.parse_value_cow()?;
+            self.tokenizer.skip_inline_whitespace();
+            if let Some(condition) = self.tokenizer.parse_condition()?
+                && !self.conditions.evaluate(condition)
+            {
+                continue;
+            }
+            return Ok(Some(VdfEvent::String { key, value }));
+        }
+    }
+
+    /// Skips the rest of the object most recently started.
+    pub fn skip_object(&mut self) -> Result<(), VdfParseError> {
+        let target = self.depth.saturating_sub(1);
+        while self.depth > target {
+            if self.next_event()?.is_none() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the rest of the object most recently started into a tree.
+    pub fn read_object(&mut self) -> Result<VdfObject, VdfParseError> {
+        let mut stack = vec![(String::new(), VdfObject::new())];
+
+        while let Some(event) = self.next_event()? {
+            match event {
+                VdfEvent::String { key, value } => {
+                    let (_, obj) = stack.last_mut().expect("stack is never empty");
+                    obj.push(key, VdfValue::String(value.into_owned()));
+                }
+                VdfEvent::ObjectStart { key } => stack.push((key.into_owned(), VdfObject::new())),
+                VdfEvent::ObjectEnd => {
+                    let (key, done) = stack.pop().expect("stack is never empty");
+                    match stack.last_mut() {
+                        Some((_, parent)) => parent.push(key, VdfValue::Object(done)),
+                        None => return Ok(done),
+                    }
+                }
+            }
+        }
+
+        // Only reachable at depth 0, where there is no object to finish.
+        Err(VdfParseError::new(
+            "No object to read",
+            self.tokenizer.position,
+        ))
+    }
+}
+
+impl<'a> Iterator for VdfReader<'a> {
+    type Item = Result<VdfEvent<'a>, VdfParseError>;
+
+    /// Ends after the first error.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let event = self.next_event();
+        self.failed = event.is_err();
+        event.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VdfEvent, VdfReader};
+    use crate::inventory::vdf::VdfParser;
+    use std::borrow::Cow;
+
+    #[test]
+    fn borrows_plain_tokens() {
+        let mut reader = VdfReader::new(r#""items_game" { "name" plain }"#);
+        assert!(matches!(
+            reader.next_event().unwrap(),
+            Some(VdfEvent::ObjectStart {
+                key: Cow::Borrowed("items_game")
+            })
+        ));
+        assert!(matches!(
+            reader.next_event().unwrap(),
+            Some(VdfEvent::String {
+                key: Cow::Borrowed("name"),
+                value: Cow::Borrowed("plain")
+            })
+        ));
+        assert_eq!(reader.next_event().unwrap(), Some(VdfEvent::ObjectEnd));
+        assert_eq!(reader.next_event().unwrap(), None);
+    }
+
+    #[test]
+    fn skips_nested_objects() {
+        let mut reader = VdfReader::new(r#""skip" { "x" { "y" "z" } } "after" "1""#);
+        assert!(matches!(
+            reader.next_event().unwrap(),
+            Some(VdfEvent::ObjectStart {
+                key: Cow::Borrowed("skip")
+            })
+        ));
+        reader.skip_object().unwrap();
+        assert_eq!(reader.depth(), 0);
+        assert!(matches!(
+            reader.next_event().unwrap(),
+            Some(VdfEvent::String {
+                key: Cow::Borrowed("after"),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn reads_objects_like_the_tree_parser() {
+        let content = r#"
+            "keep" { "name" "a \"b\"" plain "1" "pc" "1" [$WIN32] "xbox" "1" [$X360] }
+        "#;
+
+        let mut reader = VdfReader::new(content);
+        reader.next_event().unwrap();
+        let keep = reader.read_object().unwrap();
+
+        let tree = VdfParser::parse(content).unwrap();
+        let expected = tree.get("keep").and_then(|v| v.as_object()).unwrap();
+        assert_eq!(&keep, expected);
+    }
+
+    #[test]
+    fn fails_on_unterminated_objects() {
+        assert!(matches!(
+            VdfReader::new(r#""a" { "b" "c""#).last(),
+            Some(Err(_))
+        ));
+    }
+}
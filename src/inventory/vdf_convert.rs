@@ -0,0 +1,269 @@
+//! Conversion between VDF value trees and JSON.
+//!
+//! Two layouts are supported:
+//!
+//! - [`JsonLayout::Objects`] maps a VDF object to a JSON object with keys in
+//!   document order and strings to JSON strings. A key that appears more than
+//!   once becomes a single JSON key, at the position of its first occurrence,
+//!   holding an array with one element per occurrence. Arrays never appear
+//!   otherwise, so the mapping is reversible, except that repeated keys come
+//!   back next to each other.
+//! - [`JsonLayout::Entries`] maps a VDF object to an array of `[key, value]`
+//!   pairs, which keeps every entry in its exact position.
+//!
+//! The empty key is an ordinary key in both layouts: `"" "x"` becomes
+//! `{"": "x"}` or `[["", "x"]]`, also when it is the only key of its object.
+//!
+//! When importing, JSON numbers are taken as their text and booleans as `"1"`
+//! or `"0"`; `null` is rejected. [`VdfObject`] also implements serde's
+//! `Serialize` and `Deserialize` with the objects layout, as does [`VdfValue`],
+//! so both can be written to any serde format.
+
+use crate::inventory::vdf::{VdfObject, VdfValue};
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonLayout {
+    #[default]
+    Objects,
+    Entries,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonConvertError {
+    pub message: String,
+    /// Keys leading to the offending value, outermost first.
+    pub key_path: Vec<String>,
+}
+
+impl std::fmt::Display for JsonConvertError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.key_path.is_empty() {
+            write!(f, "JSON Conversion Error: {}", self.message)
+        } else {
+            write!(
+                f,
+                "JSON Conversion Error at {}: {}",
+                self.key_path.join(" > "),
+                self.message
+            )
+        }
+    }
+}
+
+impl std::error::Error for JsonConvertError {}
+
+pub fn vdf_to_json(obj: &VdfObject, layout: JsonLayout) -> Value {
+    match layout {
+        JsonLayout::Objects => {
+            let mut map = Map::new();
+            for (key, value) in obj {
+                let value = value_to_json(value, layout);
+                match map.get_mut(key) {
+                    Some(Value::Array(values)) => values.push(value),
+                    Some(first) => *first = Value::Array(vec![first.take(), value]),
+                    None => {
+                        map.insert(key.clone(), value);
+                    }
+                }
+            }
+            Value::Object(map)
+        }
+        JsonLayout::Entries => Value::Array(
+            obj.iter()
+                .map(|(key, value)| {
+                    Value::Array(vec![
+                        Value::String(key.clone()),
+                        value_to_json(value, layout),
+                    ])
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn value_to_json(value: &VdfValue, layout: JsonLayout) -> Value {
+    match value {
+        VdfValue::String(s) => Value::String(s.clone()),
+        VdfValue::Object(obj) => vdf_to_json(obj, layout),
+    }
+}
+
+pub fn json_to_vdf(value: &Value, layout: JsonLayout) -> Result<VdfObject, JsonConvertError> {
+    let mut path = Vec::new();
+    object_from_json(value, layout, &mut path)
+}
+
+fn object_from_json(
+    value: &Value,
+    layout: JsonLayout,
+    path: &mut Vec<String>,
+) -> Result<VdfObject, JsonConvertError> {
+    let mut obj = VdfObject::new();
+
+    match (layout, value) {
+        (JsonLayout::Objects, Value::Object(map)) => {
+            for (key, value) in map {
+                path.push(key.clone());
+                match value {
+                    Value::Array(values) => {
+                        for value in values {
+                            if value.is_array() {
+                                return Err(error("nested arrays are not allowed", path));
+                            }
+                            obj.push(key.clone(), value_from_json(value, layout, path)?);
+                        }
+                    }
+                    _ => obj.push(key.clone(), value_from_json(value, layout, path)?),
+                }
+                path.pop();
+            }
+        }
+        (JsonLayout::Entries, Value::Array(entries)) => {
+            for entry in entries {
+                let Some([Value::String(key), value]) = entry.as_array().map(Vec::as_slice) else {
+                    return Err(error("expected a [key, value] pair", path));
+                };
+                path.push(key.clone());
+                obj.push(key.clone(), value_from_json(value, layout, path)?);
+                path.pop();
+            }
+        }
+        (JsonLayout::Objects, _) => return Err(error("expected an object", path)),
+        (JsonLayout::Entries, _) => return Err(error("expected an array of entries", path)),
+    }
+
+    Ok(obj)
+}
+
+fn value_from_json(
+    value: &Value,
+    layout: JsonLayout,
+    path: &mut Vec<String>,
+) -> Result<VdfValue, JsonConvertError> {
+    match value {
+        Value::String(s) => Ok(VdfValue::String(s.clone())),
+        Value::Number(n) => Ok(VdfValue::String(n.to_string())),
+        Value::Bool(b) => Ok(VdfValue::String(if *b { "1" } else { "0" }.to_string())),
+        Value::Null => Err(error("null has no VDF equivalent", path)),
+        Value::Object(_) | Value::Array(_) => {
+            Ok(VdfValue::Object(object_from_json(value, layout, path)?))
+        }
+    }
+}
+
+fn error(message: &str, path: &[String]) -> JsonConvertError {
+    JsonConvertError {
+        message: message.to_string(),
+        key_path: path.to_vec(),
+    }
+}
+
+impl serde::Serialize for VdfObject {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        vdf_to_json(self, JsonLayout::Objects).serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for VdfObject {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        json_to_vdf(&value, JsonLayout::Objects).map_err(serde::de::Error::custom)
+    }
+}
+
+impl serde::Serialize for VdfValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        value_to_json(self, JsonLayout::Objects).serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for VdfValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        value_from_json(&value, JsonLayout::Objects, &mut Vec::new())
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JsonLayout, json_to_vdf, vdf_to_json};
+    use crate::inventory::vdf::VdfParser;
+    use serde_json::json;
+
+    const SOURCE: &str = r#"
+        "root"
+        {
+            "z" "1"
+            "block" { "k" "a" }
+            "block" { "k" "b" }
+            "a" "2"
+            "z" "3"
+        }
+    "#;
+
+    #[test]
+    fn maps_duplicate_keys_to_arrays() {
+        let vdf = VdfParser::parse(SOURCE).unwrap();
+
+        let value = vdf_to_json(&vdf, JsonLayout::Objects);
+
+        assert_eq!(
+            value,
+            json!({ "root": {
+                "z": ["1", "3"],
+                "block": [{ "k": "a" }, { "k": "b" }],
+                "a": "2",
+            }})
+        );
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"root":{"z":["1","3"],"block":[{"k":"a"},{"k":"b"}],"a":"2"}}"#
+        );
+
+        let back = json_to_vdf(&value, JsonLayout::Objects).unwrap();
+        let root = back.get("root").and_then(|v| v.as_object()).unwrap();
+        let keys: Vec<&str> = root.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["z", "z", "block", "block", "a"]);
+
+        let imported = json_to_vdf(&json!({ "n": 5, "b": true }), JsonLayout::Objects).unwrap();
+        assert_eq!(imported.get("n").and_then(|v| v.as_string()), Some("5"));
+        assert_eq!(imported.get("b").and_then(|v| v.as_string()), Some("1"));
+        assert!(json_to_vdf(&json!({ "x": { "y": null } }), JsonLayout::Objects).is_err());
+    }
+
+    #[test]
+    fn round_trips_objects_whose_only_key_is_empty() {
+        let vdf = VdfParser::parse(
+            r#"
+            "" { "" "x" }
+            "root" { "" { } "nested" { "" { "" "y" } } }
+            "#,
+        )
+        .unwrap();
+
+        let objects = vdf_to_json(&vdf, JsonLayout::Objects);
+        assert_eq!(
+            objects,
+            json!({ "": { "": "x" }, "root": { "": {}, "nested": { "": { "": "y" } } } })
+        );
+        let text = serde_json::to_string(&objects).unwrap();
+        let parsed = serde_json::from_str(&text).unwrap();
+        assert_eq!(json_to_vdf(&parsed, JsonLayout::Objects).unwrap(), vdf);
+
+        let entries = vdf_to_json(&vdf, JsonLayout::Entries);
+        assert_eq!(entries[0], json!(["", [["", "x"]]]));
+        assert_eq!(json_to_vdf(&entries, JsonLayout::Entries).unwrap(), vdf);
+    }
+
+    #[test]
+    fn entries_layout_round_trips_exactly() {
+        let vdf = VdfParser::parse(SOURCE).unwrap();
+
+        let value = vdf_to_json(&vdf, JsonLayout::Entries);
+
+        assert_eq!(value[0][1][0], json!(["z", "1"]));
+        assert_eq!(json_to_vdf(&value, JsonLayout::Entries).unwrap(), vdf);
+    }
+}
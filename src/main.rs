@@ -10,7 +10,7 @@ pub mod settings;
 pub mod ui;
 
 use crate::app::{CsgoInventoryEditor, ItemTemplate, Page, SelectWindowPurpose};
-use crate::inventory::{ItemAttribute, get_attribute_default_value};
+use crate::inventory::{InventoryEdit, Item, ItemAttribute};
 use eframe::egui;
 use egui_i18n::tr;
 
@@ -23,6 +23,15 @@ fn main() -> eframe::Result<()> {
     )
 }
 
+const UNDO_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+const REDO_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+const REDO_SHIFT_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(
+    egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT),
+    egui::Key::Z,
+);
+
 impl eframe::App for CsgoInventoryEditor {
     fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame) {
         let ctx = ui.ctx().clone();
@@ -56,6 +65,29 @@ impl eframe::App for CsgoInventoryEditor {
             self.delete_confirm_item_id = None;
         }
 
+        if self.game_dir.is_some() {
+            self.poll_external_changes();
+            ctx.request_repaint_after(std::time::Duration::from_secs(1));
+        }
+
+        // Text fields keep Ctrl+Z for their own undo.
+        if !self.is_live_rcon() && !ctx.text_edit_focused() {
+            // Ctrl+Shift+Z also matches Ctrl+Z, so it is checked first.
+            let (undo, redo) = ctx.input_mut(|i| {
+                let redo =
+                    i.consume_shortcut(&REDO_SHIFT_SHORTCUT) || i.consume_shortcut(&REDO_SHORTCUT);
+                let undo = i.consume_shortcut(&UNDO_SHORTCUT);
+                (undo, redo)
+            });
+            if undo {
+                let result = self.undo();
+                self.record_result(result, "undo");
+            } else if redo {
+                let result = self.redo();
+                self.record_result(result, "redo");
+            }
+        }
+
         egui::Panel::left("sidebar")
             .exact_size(120.0)
             .show_inside(ui, |ui| {
@@ -66,9 +98,15 @@ impl eframe::App for CsgoInventoryEditor {
             Page::Inventory => {
                 ui::draw_inventory_page(ui, self);
             }
+            Page::Loadout => {
+                ui::draw_loadout_page(ui, self);
+            }
             Page::Rcon => {
                 ui::draw_rcon_page(ui, self);
             }
+            Page::History => {
+                ui::draw_history_page(ui, self);
+            }
             Page::Settings => {
                 ui::draw_settings_page(ui, self);
             }
@@ -104,6 +142,9 @@ impl eframe::App for CsgoInventoryEditor {
             }
         }
 
+        ui::draw_merge_windows(&ctx, self);
+        ui::draw_trade_up_window(&ctx, self);
+
         if self.pending_add_item {
             self.pending_add_item = false;
             let template = self.selected_template.unwrap_or(ItemTemplate::Empty);
@@ -209,26 +250,16 @@ impl eframe::App for CsgoInventoryEditor {
                     if let Some((def_index_str, _, _)) = self.select_window_items.get(selected_idx)
                         && let Ok(def_index) = def_index_str.parse::<u32>()
                     {
-                        let new_inventory_id = self
-                            .inventory
-                            .items
-                            .iter()
-                            .map(|i| i.inventory)
-                            .max()
-                            .unwrap_or(0)
-                            + 1;
+                        let new_inventory_id = self.inventory.next_inventory_position();
 
-                        let new_item_id =
-                            self.inventory.items.iter().map(|i| i.id).max().unwrap_or(1) + 1;
+                        let new_item_id = self.allocate_item_ids(1)[0];
 
                         let template = self.selected_template.unwrap_or(ItemTemplate::Empty);
                         let mut new_item = template.create_item(new_item_id, def_index);
                         new_item.inventory = new_inventory_id;
 
-                        self.inventory.items.push(new_item);
-                        self.mark_inventory_changed();
+                        let result = self.apply_edit(InventoryEdit::AddItem(new_item));
                         self.open_item_windows.insert(new_item_id);
-                        let result = self.save_inventory();
                         self.record_result(result, "save inventory");
                     }
                     self.close_select_window();
@@ -239,24 +270,16 @@ impl eframe::App for CsgoInventoryEditor {
                     if let Some((def_index_str, _, _)) = self.select_window_items.get(selected_idx)
                         && let Ok(def_index) = def_index_str.parse::<u32>()
                     {
-                        let mut next_inventory_id = self
-                            .inventory
-                            .items
-                            .iter()
-                            .map(|i| i.inventory)
-                            .max()
-                            .unwrap_or(0)
-                            + 1;
+                        let mut next_inventory_id = self.inventory.next_inventory_position();
 
-                        let mut next_item_id =
-                            self.inventory.items.iter().map(|i| i.id).max().unwrap_or(1) + 1;
+                        let item_ids = self.allocate_item_ids(2);
 
                         let template = self.selected_template.unwrap_or(ItemTemplate::WeaponCase);
-                        let mut new_item = template.create_item(next_item_id, def_index);
+                        let mut new_item = template.create_item(item_ids[0], def_index);
                         new_item.inventory = next_inventory_id;
 
-                        self.inventory.items.push(new_item);
-                        self.open_item_windows.insert(next_item_id);
+                        let mut edits = vec![InventoryEdit::AddItem(new_item)];
+                        self.open_item_windows.insert(item_ids[0]);
 
                         if let Some(key_def_index) = self
                             .get_associated_item_def_indexes(def_index)
@@ -264,16 +287,17 @@ impl eframe::App for CsgoInventoryEditor {
                             .copied()
                         {
                             next_inventory_id += 1;
-                            next_item_id += 1;
 
                             let mut key_item =
-                                ItemTemplate::Empty.create_item(next_item_id, key_def_index);
+                                ItemTemplate::Empty.create_item(item_ids[1], key_def_index);
                             key_item.inventory = next_inventory_id;
-                            self.inventory.items.push(key_item);
+                            edits.push(InventoryEdit::AddItem(key_item));
                         }
 
-                        self.mark_inventory_changed();
-                        let result = self.save_inventory();
+                        let result = self.apply_edit(InventoryEdit::Batch {
+                            description: "Add case".to_string(),
+                            edits,
+                        });
                         self.record_result(result, "save inventory");
                     }
                     self.close_select_window();
@@ -286,14 +310,14 @@ impl eframe::App for CsgoInventoryEditor {
                             self.select_window_items.get(selected_idx)
                     {
                         if let Ok(def_index) = def_index_str.parse::<u32>() {
-                            if let Some(item) = self
-                                .inventory
-                                .items
-                                .iter_mut()
-                                .find(|i| i.id == for_item_id)
+                            if let Some(item) =
+                                self.inventory.items.iter().find(|i| i.id == for_item_id)
                             {
-                                item.def_index = def_index;
-                                let result = self.save_inventory();
+                                let edit = InventoryEdit::ReplaceItem(Item {
+                                    def_index,
+                                    ..item.clone()
+                                });
+                                let result = self.apply_edit(edit);
                                 self.record_result(result, "save inventory");
                             } else {
                                 self.record_result::<(), _>(
@@ -354,17 +378,9 @@ impl eframe::App for CsgoInventoryEditor {
                         self.select_window_items.get(selected_idx)
                         && let Ok(music_id) = music_index_str.parse::<u32>()
                     {
-                        let new_inventory_id = self
-                            .inventory
-                            .items
-                            .iter()
-                            .map(|i| i.inventory)
-                            .max()
-                            .unwrap_or(0)
-                            + 1;
+                        let new_inventory_id = self.inventory.next_inventory_position();
 
-                        let new_item_id =
-                            self.inventory.items.iter().map(|i| i.id).max().unwrap_or(1) + 1;
+                        let new_item_id = self.allocate_item_ids(1)[0];
 
                         let template = self
                             .selected_template
@@ -372,10 +388,8 @@ impl eframe::App for CsgoInventoryEditor {
                         let mut new_item = template.create_music_kit(new_item_id, music_id);
                         new_item.inventory = new_inventory_id;
 
-                        self.inventory.items.push(new_item);
-                        self.mark_inventory_changed();
+                        let result = self.apply_edit(InventoryEdit::AddItem(new_item));
                         self.open_item_windows.insert(new_item_id);
-                        let result = self.save_inventory();
                         self.record_result(result, "save inventory");
                     }
                     self.close_select_window();
@@ -416,9 +430,10 @@ impl eframe::App for CsgoInventoryEditor {
                         && let Ok(attr_id) = attr_id_str.parse::<u32>()
                         && let Some(edit_state) = self.edit_item_states.get_mut(&for_item_id)
                     {
-                        edit_state
-                            .attributes
-                            .insert(attr_id, get_attribute_default_value(attr_id).to_string());
+                        edit_state.attributes.insert(
+                            attr_id,
+                            self.items_game.get_attribute_default_value(attr_id),
+                        );
                     }
                     self.close_select_window();
                 }
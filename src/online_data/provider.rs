@@ -1,5 +1,5 @@
 use crate::inventory::item_attribute::ItemAttribute;
-use crate::inventory::{GameTranslation, Item, ItemsGame};
+use crate::inventory::{GameTranslation, Item, ItemsGame, SkinCatalog};
 use crate::online_data::models::{InventorySkinItem, OnlineGameData};
 use std::sync::Arc;
 
@@ -124,31 +124,17 @@ impl DataProvider {
         let item_name = self.get_item_display_name(item.def_index);
 
         if let Some(paint_index) = item.attributes.get(&ItemAttribute::SkinPaintIndex.id())
-            && let Ok(paint_id_f32) = paint_index.parse::<f32>()
+            && let Some(paint_id) = paint_index.as_u32()
+            && let Some(skin_name) = self.get_skin_full_name(item.def_index, paint_id)
         {
-            let paint_id = paint_id_f32 as u32;
-            match self {
-                DataProvider::Local { .. } => {
-                    // Local mode: combine item name and paint name
-                    if let Some(paint_name) = self.get_skin_display_name(item.def_index, paint_id) {
-                        return format!("{} | {}", item_name, paint_name);
-                    }
-                }
-                DataProvider::Online { data, .. } => {
-                    // Online mode: skin name is already full name (e.g., "AK-47 | Redline")
-                    if let Some(skin) = data.get_inventory_skin(item.def_index, paint_id) {
-                        return skin.name.clone();
-                    }
-                    // Fallback to local format if online data not found
-                    if let Some(paint_name) = self.get_skin_display_name(item.def_index, paint_id) {
-                        return format!("{} | {}", item_name, paint_name);
-                    }
-                }
-            }
+            return match self.get_wear_tier_display_name(item) {
+                Some(tier) => format!("{} ({})", skin_name, tier),
+                None => skin_name,
+            };
         }
 
         if let Some(music_index) = item.attributes.get(&ItemAttribute::MusicID.id())
-            && let Ok(music_id) = music_index.parse::<u32>()
+            && let Some(music_id) = music_index.as_u32()
         {
             match self {
                 DataProvider::Local { .. } => {
@@ -171,7 +157,7 @@ impl DataProvider {
         }
 
         if let Some(sticker_index) = item.attributes.get(&ItemAttribute::Sticker0ID.id())
-            && let Ok(sticker_id) = sticker_index.parse::<u32>()
+            && let Some(sticker_id) = sticker_index.as_u32()
         {
             match self {
                 DataProvider::Local { .. } => {
@@ -193,6 +179,36 @@ impl DataProvider {
         item_name
     }
 
+    /// "Weapon | Skin" for `weapon_id` painted with `paint_index`.
+    fn get_skin_full_name(&self, weapon_id: u32, paint_index: u32) -> Option<String> {
+        // Online skin names are already full names (e.g., "AK-47 | Redline")
+        if let DataProvider::Online { data, .. } = self
+            && let Some(skin) = data.get_inventory_skin(weapon_id, paint_index)
+        {
+            return Some(skin.name.clone());
+        }
+        self.get_skin_display_name(weapon_id, paint_index)
+            .map(|paint_name| format!("{} | {}", self.get_item_display_name(weapon_id), paint_name))
+    }
+
+    /// The exterior of a skin, e.g. "Field-Tested".
+    pub fn get_wear_tier_display_name(&self, item: &Item) -> Option<String> {
+        let (items_game, translations) = match self {
+            DataProvider::Local {
+                items_game,
+                translations,
+            }
+            | DataProvider::Online {
+                items_game,
+                translations,
+                ..
+            } => (items_game, translations),
+        };
+        items_game
+            .get_item_wear_tier(item)
+            .map(|tier| tier.get_display_name(translations))
+    }
+
     pub fn create_item_select_list(&self) -> Vec<(String, String)> {
         match self {
             DataProvider::Local {
@@ -232,7 +248,7 @@ impl DataProvider {
                 items_game,
                 translations,
             } => items_game
-                .create_paint_kit_select_list(translations)
+                .create_paint_kit_select_list_for_weapon(weapon_id, translations)
                 .into_iter()
                 .map(|(id, name)| (id, name, None))
                 .collect(),
@@ -263,7 +279,7 @@ impl DataProvider {
                 }
                 // Fallback to local data
                 items_game
-                    .create_paint_kit_select_list(translations)
+                    .create_paint_kit_select_list_for_weapon(weapon_id, translations)
                     .into_iter()
                     .map(|(id, name)| (id, name, None))
                     .collect()
@@ -342,3 +358,43 @@ fn build_online_select_list<'a>(
     items.sort_by_key(|(key, _, _)| key.parse::<u32>().unwrap_or(0));
     items
 }
+
+impl SkinCatalog for DataProvider {
+    /// From online data where it lists the weapon, otherwise from the icons
+    /// and item sets of items_game.
+    fn paint_kits_for_weapon(&self, def_index: u32) -> Option<Vec<u32>> {
+        let (items_game, online_skins) = match self {
+            DataProvider::Local { items_game, .. } => (items_game, None),
+            DataProvider::Online {
+                data, items_game, ..
+            } => (
+                items_game,
+                data.inventory
+                    .as_ref()
+                    .and_then(|inventory| inventory.skins.get(&def_index.to_string())),
+            ),
+        };
+        let Some(skins) = online_skins else {
+            return items_game
+                .get_weapon_paint_kits(def_index)
+                .map(|paint_indexes| paint_indexes.iter().copied().collect());
+        };
+        Some(
+            skins
+                .keys()
+                // "null" in online data means no paint (paint_index = 0)
+                .filter_map(|key| {
+                    if key == "null" {
+                        Some(0)
+                    } else {
+                        key.parse().ok()
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    fn skin_rarity(&self, def_index: u32, paint_index: u32) -> Option<u32> {
+        self.get_skin_rarity(def_index, paint_index)
+    }
+}
@@ -85,7 +85,9 @@ fn push_u32_attr(
     key: &str,
 ) -> Result<(), String> {
     if let Some(value) = item.attributes.get(&attr_id) {
-        let parsed = parse_u32_attr(value, key)?;
+        let parsed = value
+            .as_u32()
+            .ok_or_else(|| format!("invalid parameter {}", key))?;
         parts.push(format!("{}={}", key, parsed));
     }
     Ok(())
@@ -99,23 +101,9 @@ fn push_float_attr(
 ) -> Result<(), String> {
     if let Some(value) = item.attributes.get(&attr_id) {
         let parsed = value
-            .parse::<f32>()
-            .map_err(|_| format!("invalid parameter {}", key))?;
+            .as_f32()
+            .ok_or_else(|| format!("invalid parameter {}", key))?;
         parts.push(format!("{}={}", key, parsed));
     }
     Ok(())
 }
-
-fn parse_u32_attr(value: &str, key: &str) -> Result<u32, String> {
-    if let Ok(parsed) = value.parse::<u32>() {
-        return Ok(parsed);
-    }
-
-    let parsed_float = value
-        .parse::<f32>()
-        .map_err(|_| format!("invalid parameter {}", key))?;
-    if parsed_float < 0.0 || parsed_float.fract() != 0.0 || parsed_float > u32::MAX as f32 {
-        return Err(format!("invalid parameter {}", key));
-    }
-    Ok(parsed_float as u32)
-}
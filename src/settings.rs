@@ -1,4 +1,5 @@
 use crate::core::game_dir::editor_dir;
+use crate::inventory::IdStrategy;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -67,6 +68,23 @@ pub struct Settings {
     pub last_online_update: Option<String>,
     #[serde(default)]
     pub rcon: RconClientSettings,
+    /// How many backups of each game file to keep; 0 turns backups off.
+    #[serde(default = "default_backup_count")]
+    pub backup_count: usize,
+    /// How many inventory snapshots to keep; 0 turns snapshots off.
+    #[serde(default = "default_snapshot_count")]
+    pub snapshot_count: usize,
+    /// Where the ids of items added in the editor come from.
+    #[serde(default)]
+    pub id_strategy: IdStrategy,
+}
+
+fn default_backup_count() -> usize {
+    10
+}
+
+fn default_snapshot_count() -> usize {
+    100
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -94,6 +112,9 @@ impl Default for Settings {
             mirror_site: MirrorSite::default(),
             last_online_update: None,
             rcon: RconClientSettings::default(),
+            backup_count: default_backup_count(),
+            snapshot_count: default_snapshot_count(),
+            id_strategy: IdStrategy::default(),
         }
     }
 }
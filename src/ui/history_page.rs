@@ -0,0 +1,109 @@
+use eframe::egui;
+use egui_i18n::tr;
+
+use crate::app::CsgoInventoryEditor;
+
+pub fn draw_history_page(ui: &mut egui::Ui, state: &mut CsgoInventoryEditor) {
+    ui.heading(tr!("history-title"));
+    ui.separator();
+
+    if let Some(message) = &state.status_message {
+        crate::ui::draw_status_message(ui, message);
+        ui.separator();
+    }
+
+    ui.horizontal(|ui| {
+        ui.label(tr!("history-snapshot-count"));
+        ui.add(egui::DragValue::new(&mut state.settings.snapshot_count).range(0..=1000));
+        if ui.button(tr!("btn-switch")).clicked() {
+            let result = state.settings.save();
+            state.record_result(result, "save settings");
+        }
+        if ui.button(tr!("btn-refresh-backups")).clicked() {
+            state.refresh_snapshot_list();
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr!("history-message"));
+        ui.text_edit_singleline(&mut state.snapshot_message);
+        if ui.button(tr!("btn-take-snapshot")).clicked() {
+            let message = std::mem::take(&mut state.snapshot_message);
+            let result = state.take_snapshot(&message);
+            state.record_result(result, "take snapshot");
+        }
+    });
+
+    ui.add_space(8.0);
+
+    let read_only = state.is_live_rcon();
+    let selected_path = state
+        .selected_snapshot
+        .as_ref()
+        .map(|(entry, _)| entry.path.clone());
+    let mut compare = None;
+    let mut roll_back = None;
+
+    let snapshots = state.get_snapshot_list();
+    if snapshots.is_empty() {
+        ui.label(egui::RichText::new(tr!("history-no-snapshots")).color(egui::Color32::GRAY));
+    }
+
+    egui::ScrollArea::vertical()
+        .id_salt("snapshot_list")
+        .max_height(300.0)
+        .show(ui, |ui| {
+            egui::Grid::new("snapshot_grid")
+                .striped(true)
+                .num_columns(5)
+                .show(ui, |ui| {
+                    for snapshot in snapshots {
+                        let is_selected = selected_path.as_ref() == Some(&snapshot.path);
+                        ui.label(snapshot.created.format("%Y-%m-%d %H:%M:%S").to_string());
+                        ui.label(&snapshot.message);
+                        ui.label(
+                            tr!("backup-item-count")
+                                .replace("%1", &snapshot.item_count.to_string()),
+                        );
+                        if ui
+                            .selectable_label(is_selected, tr!("btn-compare-snapshot"))
+                            .clicked()
+                        {
+                            compare = Some(snapshot.clone());
+                        }
+                        if ui
+                            .add_enabled(!read_only, egui::Button::new(tr!("btn-roll-back")))
+                            .clicked()
+                        {
+                            roll_back = Some(snapshot.clone());
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+    if let Some(entry) = compare {
+        let result = state.select_snapshot(&entry);
+        state.record_result(result, "compare snapshot");
+    }
+    if let Some(entry) = roll_back {
+        let result = state.roll_back_to_snapshot(&entry);
+        state.record_result(result, "roll back to snapshot");
+    }
+
+    if let Some((_, lines)) = &state.selected_snapshot {
+        ui.separator();
+        if lines.is_empty() {
+            ui.label(tr!("history-no-changes"));
+        } else {
+            ui.label(tr!("history-changes-since"));
+            egui::ScrollArea::vertical()
+                .id_salt("snapshot_diff")
+                .show(ui, |ui| {
+                    for line in lines {
+                        ui.monospace(line);
+                    }
+                });
+        }
+    }
+}
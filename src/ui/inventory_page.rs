@@ -30,6 +30,10 @@ pub fn draw_inventory_page(ui: &mut egui::Ui, state: &mut CsgoInventoryEditor) {
             });
         });
     } else {
+        if !state.get_load_warnings().is_empty() || state.inventory_needs_rewrite() {
+            draw_load_warnings(ui, state);
+        }
+
         egui::Panel::top("toolbar").show_inside(ui, |ui| {
             if state.is_live_rcon() {
                 let message = crate::ui::rcon_readonly_message(&state.current_language);
@@ -39,6 +43,63 @@ pub fn draw_inventory_page(ui: &mut egui::Ui, state: &mut CsgoInventoryEditor) {
             crate::ui::draw_toolbar(ui, state);
         });
 
+        if state.show_problems {
+            egui::Panel::bottom("problems").show_inside(ui, |ui| {
+                crate::ui::draw_problems_panel(ui, state);
+            });
+        }
+
         crate::ui::draw_item_grid(ui, state);
     }
 }
+
+fn draw_load_warnings(ui: &mut egui::Ui, state: &mut CsgoInventoryEditor) {
+    let mut dismissed = false;
+    let mut rewrite = false;
+    let needs_rewrite = state.inventory_needs_rewrite();
+
+    egui::Panel::top("load_warnings").show_inside(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(tr!("load-warnings-title")).color(egui::Color32::YELLOW));
+            if needs_rewrite {
+                rewrite = ui
+                    .add_enabled(
+                        !state.is_live_rcon(),
+                        egui::Button::new(tr!("btn-rewrite-inventory")),
+                    )
+                    .clicked();
+            } else if ui.button(tr!("load-warnings-dismiss")).clicked() {
+                dismissed = true;
+            }
+        });
+        ui.label(egui::RichText::new(tr!("load-warnings-help")).color(egui::Color32::GRAY));
+        if needs_rewrite {
+            ui.label(
+                egui::RichText::new(tr!("load-warnings-syntax")).color(egui::Color32::LIGHT_RED),
+            );
+        }
+        egui::CollapsingHeader::new(format!(
+            "{} ({})",
+            tr!("load-warnings-details"),
+            state.get_load_warnings().len()
+        ))
+        .id_salt("load_warnings_details")
+        .show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    for warning in state.get_load_warnings() {
+                        ui.label(egui::RichText::new(warning).monospace());
+                    }
+                });
+        });
+    });
+
+    if dismissed {
+        state.dismiss_load_warnings();
+    }
+    if rewrite {
+        let result = state.rewrite_inventory();
+        state.record_result(result, "rewrite inventory.txt");
+    }
+}
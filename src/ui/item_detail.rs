@@ -2,7 +2,8 @@ use crate::app::{
     CsgoInventoryEditor, EditItemState, ItemTemplate, SelectWindowItems, SelectWindowPurpose,
 };
 use crate::inventory::{
-    AVAILABLE_ATTRIBUTES, ItemAttribute, get_attribute_fluent_key, get_attribute_value_display_name,
+    AttributeValue, IGLootEntry, InventoryEdit, ItemAttribute, MAX_PAINT_SEED, WearTier,
+    get_attribute_value_display_name,
 };
 use eframe::egui;
 use egui_extras::{Column, TableBuilder};
@@ -30,11 +31,7 @@ pub fn draw_item_detail_windows(
 
         let item = &state.inventory.items[item_idx];
         let item_def_index = item.def_index;
-        let item_level = item.level;
-        let item_rarity = item.rarity;
-        let item_quality = item.quality;
-        let item_attributes = item.attributes.clone();
-        let base_custom_name = item.custom_name.clone().unwrap_or_default();
+        let saved_state = EditItemState::from_item(item);
         let display_name = state.get_item_display_name(item);
         let item_base_name = state
             .items_game
@@ -49,13 +46,7 @@ pub fn draw_item_detail_windows(
         state
             .edit_item_states
             .entry(item_id)
-            .or_insert_with(|| EditItemState {
-                level: item_level,
-                custom_name: base_custom_name.clone(),
-                rarity: item_rarity,
-                quality: item_quality,
-                attributes: item_attributes.clone(),
-            });
+            .or_insert_with(|| saved_state.clone());
 
         let mut edit_state = state
             .edit_item_states
@@ -63,11 +54,13 @@ pub fn draw_item_detail_windows(
             .cloned()
             .expect("edit state should exist after insertion");
 
-        let has_unsaved_changes = edit_state.level != item_level
-            || edit_state.custom_name != base_custom_name
-            || edit_state.rarity != item_rarity
-            || edit_state.quality != item_quality
-            || edit_state.attributes != item_attributes;
+        let has_unsaved_changes = edit_state.level != saved_state.level
+            || edit_state.custom_name != saved_state.custom_name
+            || edit_state.rarity != saved_state.rarity
+            || edit_state.quality != saved_state.quality
+            || edit_state.attributes != saved_state.attributes;
+        let invalid_attribute = edit_state.typed_attributes(&state.items_game).err();
+        let can_save = !read_only && invalid_attribute.is_none();
 
         egui::Window::new(format!("{} - {}", tr!("item-detail"), display_name))
             .id(egui::Id::new(format!("item_window_{}", item_id)))
@@ -80,14 +73,14 @@ pub fn draw_item_detail_windows(
             .show(ctx, |ui| {
                 ui.horizontal(|ui| {
                     if ui
-                        .add_enabled(!read_only, egui::Button::new(tr!("btn-save")))
+                        .add_enabled(can_save, egui::Button::new(tr!("btn-save")))
                         .clicked()
                     {
                         pending_save_item_id = Some(item_id);
                     }
                     ui.add_space(10.0);
                     if ui
-                        .add_enabled(!read_only, egui::Button::new(tr!("btn-save-close")))
+                        .add_enabled(can_save, egui::Button::new(tr!("btn-save-close")))
                         .clicked()
                     {
                         pending_save_item_id = Some(item_id);
@@ -125,6 +118,24 @@ pub fn draw_item_detail_windows(
                             egui::RichText::new(tr!("status-unsaved"))
                                 .color(egui::Color32::from_rgb(200, 150, 0))
                                 .size(14.0),
+                        )
+                        .on_hover_ui(|ui| {
+                            ui.strong(tr!("changes-on-save"));
+                            for line in state.preview_item_changes(item_id, &edit_state) {
+                                ui.monospace(line);
+                            }
+                        });
+                    }
+
+                    if let Some((attr_id, error)) = &invalid_attribute {
+                        ui.add_space(20.0);
+                        ui.colored_label(
+                            egui::Color32::from_rgb(220, 80, 80),
+                            format!(
+                                "{}: {}",
+                                tr!("invalid-attribute").replace("%1", &attr_id.to_string()),
+                                error
+                            ),
                         );
                     }
                 });
@@ -135,6 +146,25 @@ pub fn draw_item_detail_windows(
 
                 ui.separator();
 
+                let contents = state.items_game.case_contents(item_def_index).to_vec();
+                if !contents.is_empty() {
+                    let tab_id = egui::Id::new(("item_contents_tab", item_id));
+                    let mut show_contents =
+                        ui.data_mut(|d| *d.get_temp_mut_or_default::<bool>(tab_id));
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(&mut show_contents, false, tr!("tab-item-details"));
+                        ui.selectable_value(&mut show_contents, true, tr!("tab-case-contents"));
+                    });
+                    ui.data_mut(|d| d.insert_temp(tab_id, show_contents));
+                    ui.separator();
+
+                    if show_contents {
+                        draw_case_contents(ui, state, item_def_index, &contents);
+                        state.edit_item_states.insert(item_id_for_edit, edit_state);
+                        return;
+                    }
+                }
+
                 let table = TableBuilder::new(ui)
                     .id_salt(item_id)
                     .striped(true)
@@ -229,7 +259,9 @@ pub fn draw_item_detail_windows(
                 ui.separator();
 
                 ui.horizontal(|ui| {
-                    let can_add_attribute = AVAILABLE_ATTRIBUTES
+                    let can_add_attribute = state
+                        .items_game
+                        .get_all_attribute_ids()
                         .iter()
                         .any(|attr_id| !edit_state.attributes.contains_key(attr_id));
                     if ui
@@ -280,23 +312,32 @@ pub fn draw_item_detail_windows(
                     })
                     .body(|mut body| {
                         for (attr_id, _attr_value) in &attr_vec {
-                            let fluent_key = get_attribute_fluent_key(*attr_id);
-                            let attr_name = tr!(&fluent_key);
+                            let attr_name = state.get_attribute_name(*attr_id);
 
                             let edit_value = edit_state
                                 .attributes
                                 .get(attr_id)
                                 .cloned()
                                 .unwrap_or_else(|| {
-                                    item_attributes.get(attr_id).cloned().unwrap_or_default()
+                                    saved_state
+                                        .attributes
+                                        .get(attr_id)
+                                        .cloned()
+                                        .unwrap_or_default()
                                 });
 
-                            let attr_value_display = get_attribute_value_display_name(
-                                *attr_id,
+                            let attr_value_display = match AttributeValue::parse(
+                                state.items_game.get_attribute_value_type(*attr_id),
                                 &edit_value,
-                                &state.items_game,
-                                &state.translations,
-                            );
+                            ) {
+                                Ok(value) => get_attribute_value_display_name(
+                                    *attr_id,
+                                    &value,
+                                    &state.items_game,
+                                    &state.translations,
+                                ),
+                                Err(_) => edit_value.clone(),
+                            };
 
                             body.row(30.0, |mut row| {
                                 row.col(|ui| {
@@ -372,6 +413,36 @@ pub fn draw_item_detail_windows(
                                                     Some(item_id_for_edit);
                                             }
                                         });
+                                    } else if *attr_id == ItemAttribute::SkinPaintWear.id()
+                                        || *attr_id == ItemAttribute::SkinPaintSeed.id()
+                                    {
+                                        let paint_index = edit_state
+                                            .attributes
+                                            .get(&ItemAttribute::SkinPaintIndex.id())
+                                            .and_then(|value| value.trim().parse::<f32>().ok())
+                                            .map(|value| value as u32);
+                                        let value_mut = edit_state
+                                            .attributes
+                                            .entry(*attr_id)
+                                            .or_insert_with(|| edit_value.clone());
+                                        ui.horizontal(|ui| {
+                                            ui.add_enabled(
+                                                !read_only,
+                                                egui::TextEdit::singleline(value_mut)
+                                                    .desired_width(120.0),
+                                            );
+                                            if *attr_id == ItemAttribute::SkinPaintWear.id() {
+                                                draw_wear_hint(
+                                                    ui,
+                                                    state,
+                                                    value_mut,
+                                                    paint_index,
+                                                    read_only,
+                                                );
+                                            } else {
+                                                draw_seed_hint(ui, state, value_mut, paint_index);
+                                            }
+                                        });
                                     } else {
                                         let value_mut = edit_state
                                             .attributes
@@ -430,20 +501,17 @@ pub fn draw_item_detail_windows(
         && let Some(edit_state) = state.edit_item_states.get(&item_id)
         && let Some(item_idx) = state.get_item_index(item_id)
     {
-        let item = &mut state.inventory.items[item_idx];
-        item.level = edit_state.level;
-        item.rarity = edit_state.rarity;
-        item.quality = edit_state.quality;
-        item.custom_name = if edit_state.custom_name.is_empty() {
-            None
-        } else {
-            Some(edit_state.custom_name.clone())
-        };
-        item.attributes = edit_state.attributes.clone();
-    }
-
-    if pending_save_item_id.is_some() && !read_only {
-        let result = state.save_inventory();
+        let old_item = state.inventory.items[item_idx].clone();
+        let mut new_item = old_item.clone();
+        let result = edit_state
+            .clone()
+            .apply_to(&mut new_item, &state.items_game)
+            .and_then(
+                |()| match InventoryEdit::update_item(&old_item, &new_item) {
+                    Some(edit) => state.apply_edit(edit),
+                    None => Ok(()),
+                },
+            );
         state.record_result(result, "save inventory");
     }
 
@@ -486,13 +554,9 @@ pub fn draw_item_detail_windows(
         });
 
         if delete_confirmed {
-            if let Some(item_idx) = state.get_item_index(item_id) {
-                state.inventory.items.remove(item_idx);
-                state.mark_inventory_changed();
+            if state.get_item_index(item_id).is_some() {
+                let result = state.apply_edit(InventoryEdit::DeleteItem(item_id));
                 state.open_item_windows.remove(&item_id);
-                state.edit_item_states.remove(&item_id);
-
-                let result = state.save_inventory();
                 state.record_result(result, "save inventory after delete");
             }
             state.delete_confirm_item_id = None;
@@ -608,3 +672,186 @@ pub fn draw_item_detail_windows(
         }
     }
 }
+
+/// The skins a case drops, grouped by rarity from most common to rarest,
+/// with the case simulator above them.
+/// The exterior a wear falls into, and a warning with a clamp button when it
+/// is outside the paint kit's wear range.
+fn draw_wear_hint(
+    ui: &mut egui::Ui,
+    state: &CsgoInventoryEditor,
+    value: &mut String,
+    paint_index: Option<u32>,
+    read_only: bool,
+) {
+    let Ok(wear) = value.trim().parse::<f32>() else {
+        return;
+    };
+    ui.label(WearTier::from_wear(wear).get_display_name(&state.translations));
+
+    let Some(paint_index) = paint_index else {
+        return;
+    };
+    let (min, max) = state.items_game.get_paint_kit_wear_range(paint_index);
+    if !(min..=max).contains(&wear) {
+        ui.label(
+            egui::RichText::new(
+                tr!("wear-out-of-range")
+                    .replace("%1", &min.to_string())
+                    .replace("%2", &max.to_string()),
+            )
+            .color(egui::Color32::YELLOW),
+        );
+        if ui
+            .add_enabled(!read_only, egui::Button::new(tr!("btn-clamp-wear")))
+            .clicked()
+        {
+            *value = wear.clamp(min, max).to_string();
+        }
+    }
+}
+
+/// Notes seeds the game never gives out, and paint kits whose look does not
+/// depend on the seed.
+fn draw_seed_hint(
+    ui: &mut egui::Ui,
+    state: &CsgoInventoryEditor,
+    value: &str,
+    paint_index: Option<u32>,
+) {
+    let Ok(seed) = value.trim().parse::<f32>() else {
+        return;
+    };
+    if seed < 0.0 || seed > MAX_PAINT_SEED as f32 {
+        ui.label(
+            egui::RichText::new(
+                tr!("seed-out-of-range").replace("%1", &MAX_PAINT_SEED.to_string()),
+            )
+            .color(egui::Color32::YELLOW),
+        );
+    } else if let Some(paint_kit) =
+        paint_index.and_then(|index| state.items_game.paint_kits.get(&index))
+        && !paint_kit.seed_affects_pattern()
+    {
+        ui.label(egui::RichText::new(tr!("seed-no-effect")).color(egui::Color32::GRAY));
+    }
+}
+
+fn draw_case_contents(
+    ui: &mut egui::Ui,
+    state: &mut CsgoInventoryEditor,
+    case_def_index: u32,
+    contents: &[IGLootEntry],
+) {
+    draw_case_simulator(ui, state, case_def_index);
+    ui.separator();
+
+    egui::ScrollArea::vertical()
+        .id_salt("case_contents")
+        .show(ui, |ui| {
+            egui::Grid::new("case_contents_grid")
+                .striped(true)
+                .num_columns(3)
+                .show(ui, |ui| {
+                    ui.strong(tr!("item"));
+                    ui.strong(tr!("header-paintkit-name"));
+                    ui.strong(tr!("rarity"));
+                    ui.end_row();
+
+                    for entry in contents {
+                        ui.label(state.data_provider.get_item_display_name(entry.def_index));
+                        ui.label(
+                            state
+                                .data_provider
+                                .get_skin_display_name(entry.def_index, entry.paint_index)
+                                .unwrap_or_else(|| entry.paint_index.to_string()),
+                        );
+                        ui.label(state.get_rarity_name(entry.rarity));
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+/// Opening the case into the inventory, and simulated openings compared with
+/// the odds the config's rarity weights give.
+fn draw_case_simulator(ui: &mut egui::Ui, state: &mut CsgoInventoryEditor, case_def_index: u32) {
+    let read_only = state.is_live_rcon();
+    let mut open = false;
+    let mut simulate = false;
+
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(!read_only, egui::Button::new(tr!("btn-open-case")))
+            .clicked()
+        {
+            open = true;
+        }
+        ui.add_space(10.0);
+        ui.label(tr!("case-seed"));
+        let mut seed = state.case_seed;
+        if ui.add(egui::DragValue::new(&mut seed)).changed() {
+            state.reseed_cases(seed);
+        }
+        ui.label(tr!("case-simulation-runs"));
+        ui.add(egui::DragValue::new(&mut state.case_simulation_runs).range(1..=1_000_000));
+        if ui.button(tr!("btn-simulate-case")).clicked() {
+            simulate = true;
+        }
+    });
+
+    if open {
+        let result = state.open_case(case_def_index);
+        state.record_result(result, "open case");
+    }
+    if simulate {
+        let result = state.simulate_case_openings(case_def_index);
+        state.record_result(result, "simulate case openings");
+    }
+
+    let odds = match state.case_odds(case_def_index) {
+        Ok(odds) => odds,
+        Err(e) => {
+            ui.colored_label(egui::Color32::from_rgb(220, 80, 80), e);
+            return;
+        }
+    };
+    let stats = state
+        .case_statistics
+        .as_ref()
+        .filter(|(def_index, _)| *def_index == case_def_index)
+        .map(|(_, stats)| stats);
+
+    egui::Grid::new("case_odds_grid")
+        .striped(true)
+        .num_columns(3)
+        .show(ui, |ui| {
+            ui.strong(tr!("rarity"));
+            ui.strong(tr!("case-odds"));
+            ui.strong(match stats {
+                Some(stats) => tr!("case-simulated").replace("%1", &stats.openings.to_string()),
+                None => String::new(),
+            });
+            ui.end_row();
+
+            for (rarity, chance) in &odds {
+                ui.label(state.get_rarity_name(*rarity));
+                ui.label(format!("{:.3}%", chance * 100.0));
+                ui.label(match stats {
+                    Some(stats) => format!("{:.3}%", stats.share(*rarity) * 100.0),
+                    None => String::new(),
+                });
+                ui.end_row();
+            }
+
+            if let Some(stats) = stats {
+                ui.label("StatTrak™");
+                ui.label("");
+                ui.label(format!(
+                    "{:.3}%",
+                    f64::from(stats.stattrak) / f64::from(stats.openings.max(1)) * 100.0
+                ));
+                ui.end_row();
+            }
+        });
+}
@@ -0,0 +1,151 @@
+use eframe::egui;
+use egui_i18n::tr;
+use std::collections::HashMap;
+
+use crate::app::CsgoInventoryEditor;
+use crate::inventory::loadout::item_slot_group;
+use crate::inventory::{Equipped, Loadout, SLOT_GROUPS, Team, loadout};
+
+enum LoadoutAction {
+    Equip(u64, u32),
+    Unequip(u64),
+    UnequipDefault(u32),
+}
+
+/// The equipped items of one team, slot by slot, with a picker for each
+/// slot. Slots filled more than once are drawn in red.
+pub fn draw_loadout_page(ui: &mut egui::Ui, state: &mut CsgoInventoryEditor) {
+    ui.heading(tr!("loadout-title"));
+    ui.separator();
+
+    if let Some(message) = &state.status_message {
+        crate::ui::draw_status_message(ui, message);
+        ui.separator();
+    }
+
+    ui.horizontal(|ui| {
+        for team in Team::all() {
+            ui.selectable_value(&mut state.loadout_team, *team, tr!(team.fluent_key()));
+        }
+    });
+    ui.add_space(8.0);
+
+    let team = state.loadout_team;
+    let read_only = state.is_live_rcon();
+    let current = Loadout::of(&state.inventory);
+
+    // The items each slot group can take for this team, by name.
+    let mut candidates: HashMap<&str, Vec<(u64, String)>> = HashMap::new();
+    for item in &state.inventory.items {
+        if let Some(group) = item_slot_group(&state.items_game, item.def_index)
+            && loadout::item_teams(&state.items_game, item.def_index).contains(&team)
+        {
+            candidates
+                .entry(group.item_slot)
+                .or_default()
+                .push((item.id, state.get_item_display_name(item)));
+        }
+    }
+    for items in candidates.values_mut() {
+        items.sort_by(|a, b| a.1.cmp(&b.1));
+    }
+
+    let mut action = None;
+    egui::ScrollArea::vertical()
+        .id_salt("loadout_list")
+        .show(ui, |ui| {
+            egui::Grid::new("loadout_grid")
+                .striped(true)
+                .num_columns(3)
+                .show(ui, |ui| {
+                    let groups = SLOT_GROUPS
+                        .iter()
+                        .filter(|group| group.no_team == (team == Team::NoTeam));
+                    for group in groups {
+                        let numbered = group.slots.clone().count() > 1;
+                        for (position, slot) in group.slots.clone().enumerate() {
+                            let mut name = tr!(&group.fluent_key()).to_string();
+                            if numbered {
+                                name = format!("{} {}", name, position + 1);
+                            }
+                            ui.label(name);
+
+                            let equipped = current.get(team, slot);
+                            ui.vertical(|ui| {
+                                if equipped.is_empty() {
+                                    ui.label(
+                                        egui::RichText::new(tr!("loadout-empty"))
+                                            .color(egui::Color32::GRAY),
+                                    );
+                                }
+                                for entry in equipped {
+                                    let (label, unequip) = match *entry {
+                                        Equipped::Item(item_id) => (
+                                            state
+                                                .inventory
+                                                .items
+                                                .iter()
+                                                .find(|item| item.id == item_id)
+                                                .map(|item| state.get_item_display_name(item))
+                                                .unwrap_or_default(),
+                                            LoadoutAction::Unequip(item_id),
+                                        ),
+                                        Equipped::Default(def_index) => (
+                                            format!(
+                                                "{} ({})",
+                                                state
+                                                    .data_provider
+                                                    .get_item_display_name(def_index),
+                                                tr!("loadout-default")
+                                            ),
+                                            LoadoutAction::UnequipDefault(def_index),
+                                        ),
+                                    };
+                                    ui.horizontal(|ui| {
+                                        let mut text = egui::RichText::new(label);
+                                        if equipped.len() > 1 {
+                                            text = text.color(egui::Color32::RED);
+                                        }
+                                        ui.label(text);
+                                        if ui
+                                            .add_enabled(
+                                                !read_only,
+                                                egui::Button::new(tr!("btn-unequip")),
+                                            )
+                                            .clicked()
+                                        {
+                                            action = Some(unequip);
+                                        }
+                                    });
+                                }
+                            });
+
+                            let options = candidates
+                                .get(group.item_slot)
+                                .map(Vec::as_slice)
+                                .unwrap_or_default();
+                            ui.add_enabled_ui(!read_only && !options.is_empty(), |ui| {
+                                egui::ComboBox::from_id_salt(("loadout_equip", slot))
+                                    .selected_text(tr!("btn-equip"))
+                                    .show_ui(ui, |ui| {
+                                        for (item_id, name) in options {
+                                            if ui.selectable_label(false, name).clicked() {
+                                                action = Some(LoadoutAction::Equip(*item_id, slot));
+                                            }
+                                        }
+                                    });
+                            });
+                            ui.end_row();
+                        }
+                    }
+                });
+        });
+
+    let result = match action {
+        Some(LoadoutAction::Equip(item_id, slot)) => state.equip_item(item_id, team, slot),
+        Some(LoadoutAction::Unequip(item_id)) => state.unequip_item(item_id, team),
+        Some(LoadoutAction::UnequipDefault(def_index)) => state.unequip_default(def_index),
+        None => return,
+    };
+    state.record_result(result, "change loadout");
+}
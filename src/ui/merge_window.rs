@@ -0,0 +1,138 @@
+use eframe::egui;
+use egui_i18n::tr;
+
+use crate::app::CsgoInventoryEditor;
+use crate::inventory::MergeSide;
+
+/// Asks which side to keep when inventory.txt or config.txt was changed by
+/// another program in a way that conflicts with the editor's changes.
+pub fn draw_merge_windows(ctx: &egui::Context, state: &mut CsgoInventoryEditor) {
+    draw_inventory_merge(ctx, state);
+    draw_unreadable_inventory_change(ctx, state);
+    draw_config_merge(ctx, state);
+}
+
+fn draw_inventory_merge(ctx: &egui::Context, state: &mut CsgoInventoryEditor) {
+    let Some(mut merge) = state.inventory_merge.take() else {
+        return;
+    };
+    let mut apply = false;
+
+    egui::Modal::new(egui::Id::new("inventory_merge_modal")).show(ctx, |ui| {
+        ui.set_max_width(640.0);
+        ui.heading(tr!("merge-inventory-title"));
+        ui.label(tr!("merge-inventory-hint"));
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .max_height(420.0)
+            .show(ui, |ui| {
+                for conflict in &mut merge.conflicts {
+                    let name = conflict
+                        .ours
+                        .as_ref()
+                        .or(conflict.theirs.as_ref())
+                        .map(|item| state.get_item_display_name(item))
+                        .unwrap_or_default();
+                    ui.strong(format!("{} ({})", name, conflict.item_id));
+
+                    egui::Grid::new(("merge_conflict", conflict.item_id))
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            ui.radio_value(
+                                &mut conflict.resolution,
+                                MergeSide::Ours,
+                                tr!("merge-keep-mine"),
+                            );
+                            draw_changes(ui, &conflict.our_changes());
+                            ui.end_row();
+
+                            ui.radio_value(
+                                &mut conflict.resolution,
+                                MergeSide::Theirs,
+                                tr!("merge-keep-theirs"),
+                            );
+                            draw_changes(ui, &conflict.their_changes());
+                            ui.end_row();
+                        });
+                    ui.separator();
+                }
+            });
+
+        if ui.button(tr!("btn-apply-merge")).clicked() {
+            apply = true;
+        }
+    });
+
+    state.inventory_merge = Some(merge);
+    if apply {
+        let result = state.resolve_inventory_merge();
+        state.record_result(result, "merge inventory changes");
+    }
+}
+
+fn draw_unreadable_inventory_change(ctx: &egui::Context, state: &mut CsgoInventoryEditor) {
+    let Some(error) = &state.unreadable_inventory_change else {
+        return;
+    };
+    let mut keep_ours = None;
+
+    egui::Modal::new(egui::Id::new("unreadable_inventory_modal")).show(ctx, |ui| {
+        ui.set_max_width(640.0);
+        ui.heading(tr!("merge-inventory-title"));
+        ui.label(tr!("merge-unreadable-hint"));
+        ui.monospace(error);
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            if ui.button(tr!("merge-keep-mine")).clicked() {
+                keep_ours = Some(true);
+            }
+            if ui.button(tr!("merge-keep-theirs")).clicked() {
+                keep_ours = Some(false);
+            }
+        });
+    });
+
+    if let Some(keep_ours) = keep_ours {
+        let result = state.resolve_unreadable_inventory_change(keep_ours);
+        state.record_result(result, "resolve inventory changes");
+    }
+}
+
+fn draw_config_merge(ctx: &egui::Context, state: &mut CsgoInventoryEditor) {
+    let Some(merge) = &state.config_merge else {
+        return;
+    };
+    let mut keep_ours = None;
+
+    egui::Modal::new(egui::Id::new("config_merge_modal")).show(ctx, |ui| {
+        ui.set_max_width(640.0);
+        ui.heading(tr!("merge-config-title"));
+        ui.label(tr!("merge-config-hint"));
+        ui.separator();
+        draw_changes(ui, &merge.conflicts);
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            if ui.button(tr!("merge-keep-mine")).clicked() {
+                keep_ours = Some(true);
+            }
+            if ui.button(tr!("merge-keep-theirs")).clicked() {
+                keep_ours = Some(false);
+            }
+        });
+    });
+
+    if let Some(keep_ours) = keep_ours {
+        state.resolve_config_merge(keep_ours);
+    }
+}
+
+fn draw_changes(ui: &mut egui::Ui, lines: &[String]) {
+    ui.vertical(|ui| {
+        for line in lines {
+            ui.monospace(line);
+        }
+    });
+}
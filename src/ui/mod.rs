@@ -1,22 +1,32 @@
 pub mod helpers;
+pub mod history_page;
 pub mod inventory_page;
 pub mod item_detail;
 pub mod item_grid;
+pub mod loadout_page;
+pub mod merge_window;
+pub mod problems_panel;
 pub mod rcon_page;
 pub mod select_window;
 pub mod settings_page;
 pub mod sidebar;
 pub mod toolbar;
+pub mod trade_up_window;
 
 pub(crate) use helpers::{
     draw_named_combo, draw_status_message, is_chinese, rcon_readonly_message,
 };
 
+pub use history_page::draw_history_page;
 pub use inventory_page::draw_inventory_page;
 pub use item_detail::draw_item_detail_windows;
 pub use item_grid::draw_item_grid;
+pub use loadout_page::draw_loadout_page;
+pub use merge_window::draw_merge_windows;
+pub use problems_panel::draw_problems_panel;
 pub use rcon_page::draw_rcon_page;
 pub use select_window::draw_select_window;
 pub use settings_page::draw_settings_page;
 pub use sidebar::draw_sidebar;
 pub use toolbar::draw_toolbar;
+pub use trade_up_window::draw_trade_up_window;
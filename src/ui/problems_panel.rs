@@ -0,0 +1,75 @@
+use eframe::egui;
+use egui_i18n::tr;
+
+use crate::app::CsgoInventoryEditor;
+use crate::inventory::Severity;
+
+/// Lists the problems found in the inventory, each with a button to open the
+/// item and, where there is one, to apply its quick fix.
+pub fn draw_problems_panel(ui: &mut egui::Ui, state: &mut CsgoInventoryEditor) {
+    let read_only = state.is_live_rcon();
+    let problems = state.get_problems().to_vec();
+    let mut open_item = None;
+    let mut fix = None;
+
+    ui.horizontal(|ui| {
+        ui.strong(tr!("problems-title"));
+        if ui.button(tr!("btn-close-problems")).clicked() {
+            state.show_problems = false;
+        }
+    });
+
+    if problems.is_empty() {
+        ui.label(egui::RichText::new(tr!("problems-none")).color(egui::Color32::GRAY));
+        return;
+    }
+
+    egui::ScrollArea::vertical()
+        .id_salt("problem_list")
+        .max_height(200.0)
+        .show(ui, |ui| {
+            egui::Grid::new("problem_grid")
+                .striped(true)
+                .num_columns(4)
+                .show(ui, |ui| {
+                    for (index, problem) in problems.iter().enumerate() {
+                        let (label, color) = match problem.severity {
+                            Severity::Error => (tr!("problem-error"), egui::Color32::LIGHT_RED),
+                            Severity::Warning => (tr!("problem-warning"), egui::Color32::YELLOW),
+                        };
+                        ui.label(egui::RichText::new(label).color(color));
+
+                        let name = state
+                            .get_item_index(problem.item_id)
+                            .map(|idx| state.get_item_display_name(&state.inventory.items[idx]))
+                            .unwrap_or_default();
+                        if ui.link(format!("{} ({})", name, problem.item_id)).clicked() {
+                            open_item = Some(problem.item_id);
+                        }
+
+                        ui.label(&problem.message);
+
+                        if let Some(edit) = &problem.fix {
+                            if ui
+                                .add_enabled(!read_only, egui::Button::new(tr!("btn-quick-fix")))
+                                .on_hover_text(edit.description())
+                                .clicked()
+                            {
+                                fix = Some(index);
+                            }
+                        } else {
+                            ui.label("");
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+    if let Some(item_id) = open_item {
+        state.open_item_windows.insert(item_id);
+    }
+    if let Some(index) = fix {
+        let result = state.fix_problem(&problems[index]);
+        state.record_result(result, "fix problem");
+    }
+}
@@ -1,5 +1,5 @@
 use crate::app::{CsgoInventoryEditor, SelectWindowPurpose};
-use crate::inventory::get_attribute_value_display_name;
+use crate::inventory::{AttributeValue, get_attribute_value_display_name};
 use eframe::egui;
 use egui_i18n::tr;
 
@@ -482,12 +482,16 @@ fn selected_paint_label(state: &CsgoInventoryEditor) -> String {
     if state.rcon_ui.give_paint.trim().is_empty() {
         return "-".to_string();
     }
-    get_attribute_value_display_name(
-        crate::inventory::ItemAttribute::SkinPaintIndex.id(),
-        &state.rcon_ui.give_paint,
-        &state.items_game,
-        &state.translations,
-    )
+    let attr_id = crate::inventory::ItemAttribute::SkinPaintIndex.id();
+    match AttributeValue::for_attribute(attr_id, state.rcon_ui.give_paint.trim()) {
+        Ok(value) => get_attribute_value_display_name(
+            attr_id,
+            &value,
+            &state.items_game,
+            &state.translations,
+        ),
+        Err(_) => state.rcon_ui.give_paint.clone(),
+    }
 }
 
 fn build_manual_give_command(state: &CsgoInventoryEditor) -> Result<String, String> {
@@ -132,6 +132,23 @@ fn draw_config_page(ui: &mut egui::Ui, state: &mut CsgoInventoryEditor) {
                     }
                 });
                 ui.separator();
+                ui.label(tr!("config-rarity-weights"));
+                let rarity_names: Vec<String> = state
+                    .config
+                    .rarity_weights
+                    .keys()
+                    .map(|rarity| state.get_rarity_name(*rarity))
+                    .collect();
+                for (name, weight) in rarity_names
+                    .into_iter()
+                    .zip(state.config.rarity_weights.values_mut())
+                {
+                    ui.horizontal(|ui| {
+                        ui.label(name);
+                        ui.add(egui::DragValue::new(weight));
+                    });
+                }
+                ui.separator();
                 ui.label(text(&language, "目标 GC RCON", "Target GC RCON"));
                 ui.horizontal(|ui| {
                     ui.label(text(&language, "启用 RCON 监听:", "Enable RCON listener:"));
@@ -297,7 +314,104 @@ fn draw_settings_content(ui: &mut egui::Ui, state: &mut CsgoInventoryEditor) {
                 ui.label(tr!("settings-updating"));
             }
         });
+
+        ui.separator();
+        draw_item_ids(ui, state);
+
+        ui.separator();
+        draw_backups(ui, state);
+    });
+}
+
+fn draw_item_ids(ui: &mut egui::Ui, state: &mut CsgoInventoryEditor) {
+    ui.label(tr!("settings-item-ids"));
+
+    ui.horizontal(|ui| {
+        ui.label(tr!("settings-id-strategy"));
+        egui::ComboBox::from_id_salt("id_strategy_combo")
+            .selected_text(state.settings.id_strategy.display_name())
+            .show_ui(ui, |ui| {
+                for strategy in crate::inventory::IdStrategy::all() {
+                    ui.selectable_value(
+                        &mut state.settings.id_strategy,
+                        *strategy,
+                        strategy.display_name(),
+                    );
+                }
+            });
+
+        if ui.button(tr!("btn-switch")).clicked() {
+            let result = state.settings.save();
+            state.record_result(result, "save settings");
+        }
     });
+
+    let read_only = state.is_live_rcon();
+    if ui
+        .add_enabled(!read_only, egui::Button::new(tr!("btn-renumber-items")))
+        .on_hover_text(tr!("settings-renumber-hint"))
+        .clicked()
+    {
+        let result = state.renumber_items();
+        state.record_result(result, "renumber items");
+    }
+}
+
+fn draw_backups(ui: &mut egui::Ui, state: &mut CsgoInventoryEditor) {
+    ui.label(tr!("settings-backups"));
+
+    ui.horizontal(|ui| {
+        ui.label(tr!("settings-backup-count"));
+        ui.add(egui::DragValue::new(&mut state.settings.backup_count).range(0..=100));
+        if ui.button(tr!("btn-switch")).clicked() {
+            let result = state.settings.save();
+            state.record_result(result, "save settings");
+        }
+        if ui.button(tr!("btn-refresh-backups")).clicked() {
+            state.refresh_backup_list();
+        }
+    });
+
+    let read_only = state.is_live_rcon();
+    let mut restore = None;
+
+    let backups = state.get_backup_list();
+    if backups.is_empty() {
+        ui.label(egui::RichText::new(tr!("settings-no-backups")).color(egui::Color32::GRAY));
+    }
+
+    egui::ScrollArea::vertical()
+        .id_salt("backup_list")
+        .max_height(200.0)
+        .show(ui, |ui| {
+            egui::Grid::new("backup_grid")
+                .striped(true)
+                .num_columns(4)
+                .show(ui, |ui| {
+                    for backup in backups {
+                        ui.label(backup.entry.created.format("%Y-%m-%d %H:%M:%S").to_string());
+                        ui.label(&backup.entry.file_name);
+                        ui.label(match backup.item_count {
+                            Some(count) => {
+                                tr!("backup-item-count").replace("%1", &count.to_string())
+                            }
+                            None => String::new(),
+                        });
+                        if ui
+                            .add_enabled(!read_only, egui::Button::new(tr!("btn-restore-backup")))
+                            .clicked()
+                        {
+                            restore = Some(backup.entry.clone());
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+    if let Some(entry) = restore {
+        let result = state.restore_backup(&entry);
+        state.record_result(result, "restore backup");
+    }
 }
 
 fn draw_about_page(ui: &mut egui::Ui) {
@@ -11,12 +11,24 @@ pub fn draw_sidebar(ui: &mut egui::Ui, state: &mut crate::app::CsgoInventoryEdit
 
         ui.add_space(8.0);
 
+        if ui.button(tr!("sidebar-loadout")).clicked() {
+            state.current_page = crate::app::Page::Loadout;
+        }
+
+        ui.add_space(8.0);
+
         if ui.button("RCON").clicked() {
             state.current_page = crate::app::Page::Rcon;
         }
 
         ui.add_space(8.0);
 
+        if ui.button(tr!("sidebar-history")).clicked() {
+            state.current_page = crate::app::Page::History;
+        }
+
+        ui.add_space(8.0);
+
         if ui.button(tr!("sidebar-settings")).clicked() {
             state.current_page = crate::app::Page::Settings;
         }
@@ -16,6 +16,50 @@ pub fn draw_toolbar(ui: &mut egui::Ui, state: &mut CsgoInventoryEditor) {
             state.selected_template = Some(ItemTemplate::Empty);
         }
 
+        let can_edit = !state.is_live_rcon();
+        let undo = state.edit_history.undo_description().map(str::to_string);
+        let redo = state.edit_history.redo_description().map(str::to_string);
+
+        let undo_button = ui.add_enabled(
+            can_edit && undo.is_some(),
+            egui::Button::new(tr!("btn-undo")),
+        );
+        if undo_button.clicked() {
+            let result = state.undo();
+            state.record_result(result, "undo");
+        }
+        if let Some(description) = undo {
+            undo_button.on_hover_text(format!("{} (Ctrl+Z)", description));
+        }
+
+        let redo_button = ui.add_enabled(
+            can_edit && redo.is_some(),
+            egui::Button::new(tr!("btn-redo")),
+        );
+        if redo_button.clicked() {
+            let result = state.redo();
+            state.record_result(result, "redo");
+        }
+        if let Some(description) = redo {
+            redo_button.on_hover_text(format!("{} (Ctrl+Y)", description));
+        }
+
+        let problem_count = state.get_problems().len();
+        let problems_label = tr!("btn-problems").replace("%1", &problem_count.to_string());
+        if ui
+            .selectable_label(state.show_problems, problems_label)
+            .clicked()
+        {
+            state.show_problems = !state.show_problems;
+        }
+
+        if ui
+            .selectable_label(state.show_trade_up, tr!("btn-trade-up-contract"))
+            .clicked()
+        {
+            state.show_trade_up = !state.show_trade_up;
+        }
+
         if let Some(message) = &state.status_message {
             ui.add_space(12.0);
             crate::ui::draw_status_message(ui, message);
@@ -0,0 +1,148 @@
+use eframe::egui;
+use egui_i18n::tr;
+
+use crate::app::CsgoInventoryEditor;
+use crate::inventory::{AttributeValue, ItemAttribute, TRADE_UP_SIZE};
+
+/// Picks ten skins for a trade-up contract, shows what the contract can give
+/// and carries it out.
+pub fn draw_trade_up_window(ctx: &egui::Context, state: &mut CsgoInventoryEditor) {
+    if !state.show_trade_up {
+        return;
+    }
+
+    // Items deleted since they were picked drop out of the contract.
+    let inventory = &state.inventory;
+    state
+        .trade_up_inputs
+        .retain(|item_id| inventory.has_item_id(*item_id));
+
+    let read_only = state.is_live_rcon();
+    let mut open = true;
+    let mut execute = false;
+
+    egui::Window::new(tr!("trade-up-title"))
+        .open(&mut open)
+        .default_width(720.0)
+        .show(ctx, |ui| {
+            ui.label(
+                tr!("trade-up-hint")
+                    .replace("%1", &TRADE_UP_SIZE.to_string())
+                    .replace("%2", &state.trade_up_inputs.len().to_string()),
+            );
+            ui.separator();
+
+            ui.columns(2, |columns| {
+                draw_candidates(&mut columns[0], state);
+                execute = draw_outcomes(&mut columns[1], state, read_only);
+            });
+        });
+
+    if !open {
+        state.show_trade_up = false;
+    }
+    if execute {
+        let result = state.execute_trade_up();
+        state.record_result(result, "trade up");
+    }
+}
+
+/// Skins in the inventory, each with a checkbox to put it in the contract.
+fn draw_candidates(ui: &mut egui::Ui, state: &mut CsgoInventoryEditor) {
+    let mut candidates: Vec<(u64, String, u32, f32)> = state
+        .inventory
+        .items
+        .iter()
+        .filter_map(|item| {
+            let wear = item
+                .attributes
+                .get(&ItemAttribute::SkinPaintWear.id())
+                .and_then(AttributeValue::as_f32)?;
+            item.attributes.get(&ItemAttribute::SkinPaintIndex.id())?;
+            Some((
+                item.id,
+                state.get_item_display_name(item),
+                item.rarity,
+                wear,
+            ))
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.2.cmp(&b.2).then_with(|| a.1.cmp(&b.1)));
+
+    let full = state.trade_up_inputs.len() >= TRADE_UP_SIZE;
+    egui::ScrollArea::vertical()
+        .id_salt("trade_up_candidates")
+        .max_height(420.0)
+        .show(ui, |ui| {
+            egui::Grid::new("trade_up_candidate_grid")
+                .striped(true)
+                .num_columns(3)
+                .show(ui, |ui| {
+                    for (item_id, name, rarity, wear) in candidates {
+                        let mut picked = state.trade_up_inputs.contains(&item_id);
+                        if ui
+                            .add_enabled(picked || !full, egui::Checkbox::new(&mut picked, name))
+                            .changed()
+                        {
+                            if picked {
+                                state.trade_up_inputs.insert(item_id);
+                            } else {
+                                state.trade_up_inputs.remove(&item_id);
+                            }
+                        }
+                        ui.label(state.get_rarity_name(rarity));
+                        ui.label(format!("{:.6}", wear));
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+/// The possible results of the picked items, or why they make no contract.
+/// Returns whether the contract should be carried out.
+fn draw_outcomes(ui: &mut egui::Ui, state: &mut CsgoInventoryEditor, read_only: bool) -> bool {
+    let plan = match state.plan_trade_up() {
+        Ok(plan) => plan,
+        Err(message) => {
+            ui.label(egui::RichText::new(message).color(egui::Color32::GRAY));
+            return false;
+        }
+    };
+
+    egui::ScrollArea::vertical()
+        .id_salt("trade_up_outcomes")
+        .max_height(380.0)
+        .show(ui, |ui| {
+            egui::Grid::new("trade_up_outcome_grid")
+                .striped(true)
+                .num_columns(3)
+                .show(ui, |ui| {
+                    ui.strong(tr!("item"));
+                    ui.strong(tr!("case-odds"));
+                    ui.strong(tr!("trade-up-wear"));
+                    ui.end_row();
+
+                    for outcome in &plan.outcomes {
+                        let skin = state
+                            .data_provider
+                            .get_skin_display_name(outcome.def_index, outcome.paint_index)
+                            .unwrap_or_else(|| outcome.paint_index.to_string());
+                        let collection = state.items_game.item_sets[&outcome.collection]
+                            .get_display_name(&state.translations);
+                        ui.label(format!(
+                            "{} | {}",
+                            state.data_provider.get_item_display_name(outcome.def_index),
+                            skin
+                        ))
+                        .on_hover_text(collection);
+                        ui.label(format!("{:.1}%", outcome.chance * 100.0));
+                        ui.label(format!("{:.6}", outcome.wear));
+                        ui.end_row();
+                    }
+                });
+        });
+
+    ui.add_space(8.0);
+    ui.add_enabled(!read_only, egui::Button::new(tr!("btn-trade-up")))
+        .clicked()
+}